@@ -0,0 +1,16 @@
+use std::fs::create_dir_all;
+use std::path::PathBuf;
+
+use cosmwasm_schema::{export_schema_with_title, remove_schemas};
+
+use lending::schema::schemas;
+
+fn main() {
+    let out_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("schema");
+    create_dir_all(&out_dir).unwrap();
+    remove_schemas(&out_dir).unwrap();
+
+    for (title, schema) in schemas() {
+        export_schema_with_title(&schema, &out_dir, title);
+    }
+}
@@ -1,14 +1,74 @@
-use astroport::asset::Asset;
-use cosmwasm_std::{Attribute, CanonicalAddr, DepsMut, Response};
+use astroport::asset::{Asset, AssetInfo};
+use cosmwasm_std::{
+    to_binary, Addr, Api, Attribute, Binary, CanonicalAddr, ContractResult, CosmosMsg, Decimal256,
+    DepsMut, Env, Event, IbcMsg, IbcTimeout, Response, StdError, StdResult, Storage, SubMsg,
+    SubMsgExecutionResponse, Uint128, WasmMsg,
+};
+use cw0::{Duration, Expiration};
+use cw20::{Cw20ExecuteMsg, MinterResponse};
 
 use crate::{
     error::ContractError,
+    interest::RateCurve,
+    pool::{
+        accrue_interest, amount_to_shares, exceeds_ltv, flash_loan_fee, is_liquidatable, loan_debt,
+        repayment_parts, scale_debt_down, scale_debt_up, scaled_debt_value, seized_collateral,
+        shares_to_amount,
+    },
     state::{
-        load_borrow_request, load_state, store_borrow_request, store_borrow_request_id_to_addr,
-        store_state, BorrowRequest,
+        add_claimable, add_collateral_escrowed, add_request_collateral_escrowed,
+        load_borrow_request, load_borrower_profile, load_borrower_scaled_debt, load_config,
+        load_pending_reserve, load_state, may_load_borrow_request_id_to_addr, may_load_flash_loan,
+        may_load_ibc_channel, may_load_loan, may_load_payout, may_load_remote_collateral,
+        may_load_reserve, may_load_share_token_to_asset, read_claimable,
+        read_expired_borrow_requests, remove_borrow_request_expiry,
+        remove_borrow_request_id_to_addr, remove_borrow_request_indexes, remove_claimable,
+        remove_flash_loan, remove_ibc_counterparty, remove_loan, remove_payout,
+        remove_pending_reserve, remove_remote_collateral, store_borrow_request,
+        store_borrow_request_expiry, store_borrow_request_id_to_addr, store_borrow_request_indexes,
+        store_borrower_profile, store_flash_loan, store_ibc_counterparty, store_ibc_hook_config,
+        store_loan, store_payout, store_pending_release, store_pending_reserve,
+        store_remote_collateral, store_reserve, store_share_token_to_asset, store_state,
+        sub_collateral_escrowed, sub_request_collateral_escrowed, update_asset_stats,
+        update_borrower_scaled_debt, BorrowRequest, BorrowerRequirements, FlashLoan, IbcHookConfig,
+        Loan, Payout, PendingRelease, RemoteCollateral, Reserve,
     },
+    utils::{addr_canonicalize, parse_instantiate_address, to_transfer_msg},
 };
 
+use services::lending::{
+    events::{
+        BorrowEvent, BorrowRequestEvent, FlashLoanEvent, LendingEvent, LiquidateEvent,
+        LiquidityEvent, PayoutEvent, RepayEvent,
+    },
+    ExecuteMsg, LendingPacket, ReserveConfig,
+};
+
+/// Default number of expired borrow requests removed at once
+const DEFAULT_PRUNE_LIMIT: u32 = 10u32;
+/// Maximum number of expired borrow requests removed at once
+const MAX_PRUNE_LIMIT: u32 = 30u32;
+
+/// Seconds until a packet releasing remote collateral times out
+const IBC_PACKET_LIFETIME: u64 = 86_400u64;
+
+/// Reply id of the instantiation of a reserve share token
+pub const INSTANTIATE_SHARE_TOKEN_REPLY_ID: u64 = 1u64;
+/// Offset of reply ids of CW20 payouts, each of which is replied with the offset plus its id
+pub const PAYOUT_REPLY_ID_OFFSET: u64 = 1u64 << 32;
+
+/// Name of reserve share tokens
+const SHARE_TOKEN_NAME: &str = "Lending Share";
+/// Symbol of reserve share tokens
+const SHARE_TOKEN_SYMBOL: &str = "lSHARE";
+/// Decimals of reserve share tokens
+const SHARE_TOKEN_DECIMALS: u8 = 6u8;
+
+/// Maximum per-block borrow rate in billionths a reserve can reach at full utilization.
+/// Compounded over a year of 5 second blocks it is below 90%, so the borrow index stays far
+/// from overflowing for centuries
+const MAX_BORROW_RATE_PER_BILLION: u64 = 100u64;
+
 /// ## Description
 /// Make borrow request
 /// Returns [`Response`] with specified attributes and messages if operation was successful
@@ -16,25 +76,46 @@ use crate::{
 /// ## Params
 /// * **deps** is an object of type [`DepsMut`]
 ///
+/// * **env** is an object of type [`Env`]
+///
 /// * **borrower** is an object of type [`CanonicalAddr`]
 ///
 /// * **collateral** is an object of type [`Asset`]
 ///
-/// * **period** is [`u64`]
+/// * **period** is an object of type [`Duration`]
+///
+/// * **lender** is an [`Option`] of [`CanonicalAddr`], the only lender who can fund the request
+///
+/// * **expires_at** is an [`Option`] of [`Expiration`]
+///
+/// * **remote_collateral** is an [`Option`] of [`RemoteCollateral`] locked on a remote chain
+#[allow(clippy::too_many_arguments)]
 pub fn make_borrow_request(
     deps: DepsMut,
+    env: Env,
     borrower: &CanonicalAddr,
     collateral: Asset,
-    period: u64,
+    period: Duration,
+    lender: Option<CanonicalAddr>,
+    expires_at: Option<Expiration>,
+    remote_collateral: Option<RemoteCollateral>,
 ) -> Result<Response, ContractError> {
     if collateral.amount.is_zero() {
         return Err(ContractError::InvalidAmount {});
     }
 
-    if period == 0u64 {
+    if let Duration::Height(0u64) | Duration::Time(0u64) = period {
         return Err(ContractError::InvalidPeriod {});
     }
 
+    let expires_at = match expires_at {
+        Some(Expiration::Never {}) | None => None,
+        Some(expires_at) if expires_at.is_expired(&env.block) => {
+            return Err(ContractError::InvalidExpiration {});
+        }
+        expires_at => expires_at,
+    };
+
     let mut attrs: Vec<Attribute> = vec![Attribute::new("action", "make_borrow_request")];
 
     let mut state = load_state(deps.storage)?;
@@ -47,31 +128,1380 @@ pub fn make_borrow_request(
 
     let id = state.request_count;
 
+    if let Some(remote_collateral) = &remote_collateral {
+        store_remote_collateral(deps.storage, id, remote_collateral)?;
+    }
+    add_request_collateral_escrowed(deps.storage, id, &collateral)?;
+
     let mut borrow_request = load_borrow_request(deps.storage, borrower)?;
     borrow_request.push(BorrowRequest {
         id,
         collateral,
         period,
+        lender,
+        expires_at,
         borrowed_from: 0u64,
         borrowed_at: 0u64,
+        borrowed_at_time: 0u64,
     });
-    store_borrow_request(deps.storage, &borrower, &borrow_request)?;
+    store_borrow_request(deps.storage, borrower, &borrow_request)?;
     store_borrow_request_id_to_addr(deps.storage, id, borrower)?;
-    attrs.push(Attribute::new(
-        "borrow_request",
-        &format!(
-            "\
-            id: {},\n\
-            collateral: {},\n\
-            period: {},\n\
-            borrowed_from: 0,\n\
-            borrowed_at: 0,\n\
-            ",
-            id,
-            &borrow_request.last().unwrap().collateral,
-            period,
+    if let Some(expires_at) = &expires_at {
+        store_borrow_request_expiry(deps.storage, expires_at, id)?;
+    }
+    store_borrow_request_indexes(deps.storage, borrow_request.last().unwrap())?;
+    let event = LendingEvent::BorrowRequestCreated(to_borrow_request_event(
+        deps.api,
+        borrower,
+        borrow_request.last().unwrap(),
+    )?);
+
+    Ok(Response::new()
+        .add_attributes(attrs)
+        .add_event(event.into()))
+}
+
+/// ## Description
+/// Cancel a borrow request that has not been borrowed yet and refund its collateral
+/// Returns [`Response`] with specified attributes and messages if operation was successful
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`]
+///
+/// * **env** is an object of type [`Env`]
+///
+/// * **borrower** is an object of type [`CanonicalAddr`]
+///
+/// * **id** is [`u64`]
+pub fn cancel_borrow_request(
+    deps: DepsMut,
+    env: Env,
+    borrower: &CanonicalAddr,
+    id: u64,
+) -> Result<Response, ContractError> {
+    let owner = may_load_borrow_request_id_to_addr(deps.storage, id)?
+        .ok_or(ContractError::BorrowRequestNotFound {})?;
+    if owner != *borrower {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut borrow_request = load_borrow_request(deps.storage, borrower)?;
+    let index = borrow_request
+        .iter()
+        .position(|br| br.id == id)
+        .ok_or(ContractError::BorrowRequestNotFound {})?;
+    if borrow_request[index].borrowed_from != 0u64 {
+        return Err(ContractError::BorrowRequestAlreadyBorrowed {});
+    }
+
+    let cancelled = borrow_request.remove(index);
+    store_borrow_request(deps.storage, borrower, &borrow_request)?;
+    remove_borrow_request_id_to_addr(deps.storage, id);
+    if let Some(expires_at) = &cancelled.expires_at {
+        remove_borrow_request_expiry(deps.storage, expires_at, id);
+    }
+    remove_borrow_request_indexes(deps.storage, &cancelled);
+    sub_request_collateral_escrowed(deps.storage, id, &cancelled.collateral)?;
+
+    let collateral = cancelled.collateral.to_string();
+    let event = LendingEvent::BorrowRequestCancelled(to_borrow_request_event(
+        deps.api, borrower, &cancelled,
+    )?);
+    let refund = to_refund_msg(deps.storage, deps.api, &env, borrower, cancelled)?;
+
+    Ok(Response::new()
+        .add_submessage(refund)
+        .add_attributes(vec![
+            Attribute::new("action", "cancel_borrow_request"),
+            Attribute::new("id", id.to_string()),
+            Attribute::new("collateral", collateral),
+        ])
+        .add_event(event.into()))
+}
+
+/// ## Description
+/// Remove up to the given number of expired borrow requests and refund their collateral.
+/// Expired requests backed by collateral over a closed channel cannot be refunded, so they
+/// are only dropped from the expiry index and reported as stranded, to be removed by the
+/// owner
+/// Returns [`Response`] with specified attributes and messages if operation was successful
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`]
+///
+/// * **env** is an object of type [`Env`]
+///
+/// * **limit** is an [`Option`] of [`u32`]
+pub fn prune_expired(
+    deps: DepsMut,
+    env: Env,
+    limit: Option<u32>,
+) -> Result<Response, ContractError> {
+    let limit = limit.unwrap_or(DEFAULT_PRUNE_LIMIT).min(MAX_PRUNE_LIMIT) as usize;
+    let ids = read_expired_borrow_requests(deps.storage, &env.block, limit)?;
+
+    let mut msgs: Vec<SubMsg> = vec![];
+    let mut events: Vec<Event> = vec![];
+    let mut pruned: Vec<u64> = vec![];
+    let mut stranded: Vec<u64> = vec![];
+    for id in ids.iter() {
+        let borrower = may_load_borrow_request_id_to_addr(deps.storage, *id)?
+            .ok_or(ContractError::BorrowRequestNotFound {})?;
+        let mut borrow_request = load_borrow_request(deps.storage, &borrower)?;
+        let index = borrow_request
+            .iter()
+            .position(|br| br.id == *id)
+            .ok_or(ContractError::BorrowRequestNotFound {})?;
+
+        if let Some(expires_at) = &borrow_request[index].expires_at {
+            remove_borrow_request_expiry(deps.storage, expires_at, *id);
+        }
+        if let Some(remote) = may_load_remote_collateral(deps.storage, *id)? {
+            if may_load_ibc_channel(deps.storage, &remote.channel_id)?.is_none() {
+                stranded.push(*id);
+                continue;
+            }
+        }
+
+        let expired = borrow_request.remove(index);
+        store_borrow_request(deps.storage, &borrower, &borrow_request)?;
+        remove_borrow_request_id_to_addr(deps.storage, *id);
+        remove_borrow_request_indexes(deps.storage, &expired);
+        sub_request_collateral_escrowed(deps.storage, *id, &expired.collateral)?;
+
+        events.push(
+            LendingEvent::BorrowRequestExpired(to_borrow_request_event(
+                deps.api, &borrower, &expired,
+            )?)
+            .into(),
+        );
+        msgs.push(to_refund_msg(
+            deps.storage,
+            deps.api,
+            &env,
+            &borrower,
+            expired,
+        )?);
+        pruned.push(*id);
+    }
+
+    Ok(Response::new()
+        .add_submessages(msgs)
+        .add_attributes(vec![
+            Attribute::new("action", "prune_expired"),
+            Attribute::new(
+                "ids",
+                pruned
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect::<Vec<String>>()
+                    .join(","),
+            ),
+            Attribute::new(
+                "stranded",
+                stranded
+                    .iter()
+                    .map(|id| id.to_string())
+                    .collect::<Vec<String>>()
+                    .join(","),
+            ),
+        ])
+        .add_events(events))
+}
+
+/// ## Description
+/// Remove a borrow request whose remote collateral can no longer be released because its
+/// channel has closed, so that it stops counting as escrowed. The collateral is left to
+/// the counterpart contract to refund to the refund address
+/// Returns [`Response`] with specified attributes and messages if operation was successful
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`]
+///
+/// * **sender** is an object of type [`CanonicalAddr`]
+///
+/// * **id** is [`u64`]
+pub fn remove_stranded_borrow_request(
+    deps: DepsMut,
+    sender: &CanonicalAddr,
+    id: u64,
+) -> Result<Response, ContractError> {
+    if load_config(deps.storage)?.owner != *sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let borrower = may_load_borrow_request_id_to_addr(deps.storage, id)?
+        .ok_or(ContractError::BorrowRequestNotFound {})?;
+    let remote = may_load_remote_collateral(deps.storage, id)?
+        .ok_or(ContractError::BorrowRequestNotFound {})?;
+    if may_load_ibc_channel(deps.storage, &remote.channel_id)?.is_some() {
+        return Err(ContractError::ChannelStillOpen {});
+    }
+
+    let mut borrow_request = load_borrow_request(deps.storage, &borrower)?;
+    let index = borrow_request
+        .iter()
+        .position(|br| br.id == id)
+        .ok_or(ContractError::BorrowRequestNotFound {})?;
+
+    let stranded = borrow_request.remove(index);
+    store_borrow_request(deps.storage, &borrower, &borrow_request)?;
+    remove_borrow_request_id_to_addr(deps.storage, id);
+    if let Some(expires_at) = &stranded.expires_at {
+        remove_borrow_request_expiry(deps.storage, expires_at, id);
+    }
+    remove_borrow_request_indexes(deps.storage, &stranded);
+    sub_request_collateral_escrowed(deps.storage, id, &stranded.collateral)?;
+    remove_remote_collateral(deps.storage, id);
+
+    Ok(Response::new().add_attributes(vec![
+        Attribute::new("action", "remove_stranded_borrow_request"),
+        Attribute::new("id", id.to_string()),
+        Attribute::new("channel_id", remote.channel_id),
+        Attribute::new("refund_address", remote.refund_address),
+        Attribute::new("collateral", remote.collateral.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Create a pooled reserve of the given asset by instantiating its share token,
+/// the reserve is stored once the share token address is replied
+/// Returns [`Response`] with specified attributes and messages if operation was successful
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`]
+///
+/// * **env** is an object of type [`Env`]
+///
+/// * **sender** is an object of type [`CanonicalAddr`]
+///
+/// * **asset_info** is an object of type [`AssetInfo`]
+///
+/// * **config** is an object of type [`ReserveConfig`]
+pub fn create_reserve(
+    deps: DepsMut,
+    env: Env,
+    sender: &CanonicalAddr,
+    asset_info: AssetInfo,
+    config: ReserveConfig,
+) -> Result<Response, ContractError> {
+    if load_config(deps.storage)?.owner != *sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if may_load_reserve(deps.storage, &asset_info)?.is_some() {
+        return Err(ContractError::ReserveAlreadyExists {});
+    }
+
+    validate_reserve_config(&config)?;
+
+    store_pending_reserve(deps.storage, &asset_info, &config)?;
+
+    let instantiate = SubMsg::reply_on_success(
+        WasmMsg::Instantiate {
+            admin: None,
+            code_id: load_config(deps.storage)?.token_code_id,
+            msg: to_binary(&astroport::token::InstantiateMsg {
+                name: SHARE_TOKEN_NAME.to_string(),
+                symbol: SHARE_TOKEN_SYMBOL.to_string(),
+                decimals: SHARE_TOKEN_DECIMALS,
+                initial_balances: vec![],
+                mint: Some(MinterResponse {
+                    minter: env.contract.address.to_string(),
+                    cap: None,
+                }),
+            })?,
+            funds: vec![],
+            label: format!("{} share", asset_info),
+        },
+        INSTANTIATE_SHARE_TOKEN_REPLY_ID,
+    );
+
+    Ok(Response::new()
+        .add_submessage(instantiate)
+        .add_attributes(vec![
+            Attribute::new("action", "create_reserve"),
+            Attribute::new("asset", asset_info.to_string()),
+        ]))
+}
+
+/// ## Description
+/// Store the pending reserve with the address of its instantiated share token
+/// Returns [`Response`] with specified attributes if operation was successful
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`]
+///
+/// * **env** is an object of type [`Env`]
+///
+/// * **data** is an object of type [`Option<Binary>`], the data of the instantiation reply
+pub fn register_share_token(
+    deps: DepsMut,
+    env: Env,
+    data: Option<Binary>,
+) -> Result<Response, ContractError> {
+    let data = data.ok_or_else(|| StdError::generic_err("Missing share token instantiate data"))?;
+    let share_token = addr_canonicalize(deps.api, &parse_instantiate_address(&data)?)?;
+
+    let (asset_info, config) = load_pending_reserve(deps.storage)?;
+    remove_pending_reserve(deps.storage);
+
+    let reserve = to_reserve(deps.api, asset_info, share_token, config, env.block.height)?;
+    store_reserve(deps.storage, &reserve)?;
+    store_share_token_to_asset(deps.storage, &reserve.share_token, &reserve.asset_info)?;
+
+    Ok(Response::new().add_attributes(vec![
+        Attribute::new("action", "register_share_token"),
+        Attribute::new("asset", reserve.asset_info.to_string()),
+        Attribute::new("share_token", deps.api.addr_humanize(&reserve.share_token)?),
+    ]))
+}
+
+/// ## Description
+/// Update the config of a pooled reserve, accruing interest at the previous rate first
+/// Returns [`Response`] with specified attributes if operation was successful
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`]
+///
+/// * **env** is an object of type [`Env`]
+///
+/// * **sender** is an object of type [`CanonicalAddr`]
+///
+/// * **asset_info** is an object of type [`AssetInfo`]
+///
+/// * **config** is an object of type [`ReserveConfig`]
+pub fn update_reserve(
+    deps: DepsMut,
+    env: Env,
+    sender: &CanonicalAddr,
+    asset_info: AssetInfo,
+    config: ReserveConfig,
+) -> Result<Response, ContractError> {
+    if load_config(deps.storage)?.owner != *sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let mut reserve =
+        may_load_reserve(deps.storage, &asset_info)?.ok_or(ContractError::ReserveNotFound {})?;
+
+    validate_reserve_config(&config)?;
+
+    if config.collateral_info != reserve.collateral_info {
+        return Err(ContractError::InvalidReserveConfig {});
+    }
+
+    accrue_interest(&mut reserve, env.block.height)?;
+    let reserve = Reserve {
+        liquidity: reserve.liquidity,
+        total_shares: reserve.total_shares,
+        total_scaled_debt: reserve.total_scaled_debt,
+        borrow_index: reserve.borrow_index,
+        ..to_reserve(
+            deps.api,
+            reserve.asset_info,
+            reserve.share_token,
+            config,
+            reserve.last_accrued_at,
+        )?
+    };
+    store_reserve(deps.storage, &reserve)?;
+
+    Ok(Response::new().add_attributes(vec![
+        Attribute::new("action", "update_reserve"),
+        Attribute::new("asset", reserve.asset_info.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Deposit into a pooled reserve in exchange for share tokens minted at the current
+/// exchange rate
+/// Returns [`Response`] with specified attributes and messages if operation was successful
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`]
+///
+/// * **env** is an object of type [`Env`]
+///
+/// * **lender** is an object of type [`CanonicalAddr`]
+///
+/// * **asset** is an object of type [`Asset`]
+pub fn deposit(
+    deps: DepsMut,
+    env: Env,
+    lender: &CanonicalAddr,
+    asset: Asset,
+) -> Result<Response, ContractError> {
+    let mut reserve =
+        may_load_reserve(deps.storage, &asset.info)?.ok_or(ContractError::ReserveNotFound {})?;
+
+    accrue_interest(&mut reserve, env.block.height)?;
+
+    let shares = amount_to_shares(&reserve, asset.amount)?;
+    if shares.is_zero() {
+        return Err(ContractError::InvalidAmount {});
+    }
+
+    reserve.liquidity = reserve.liquidity.checked_add(asset.amount)?;
+    reserve.total_shares = reserve.total_shares.checked_add(shares)?;
+    store_reserve(deps.storage, &reserve)?;
+
+    let mint = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: deps.api.addr_humanize(&reserve.share_token)?.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Mint {
+            recipient: deps.api.addr_humanize(lender)?.to_string(),
+            amount: shares,
+        })?,
+        funds: vec![],
+    });
+
+    let event = LendingEvent::Deposited(LiquidityEvent {
+        lender: deps.api.addr_humanize(lender)?.to_string(),
+        asset: asset.clone(),
+        shares,
+    });
+
+    Ok(Response::new()
+        .add_message(mint)
+        .add_attributes(vec![
+            Attribute::new("action", "deposit"),
+            Attribute::new("asset", asset.to_string()),
+            Attribute::new("shares", shares.to_string()),
+        ])
+        .add_event(event.into()))
+}
+
+/// ## Description
+/// Burn share tokens of a pooled reserve in exchange for the underlying asset
+/// Returns [`Response`] with specified attributes and messages if operation was successful
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`]
+///
+/// * **env** is an object of type [`Env`]
+///
+/// * **lender** is an object of type [`CanonicalAddr`]
+///
+/// * **shares** is an object of type [`Asset`], the share tokens sent
+pub fn withdraw(
+    deps: DepsMut,
+    env: Env,
+    lender: &CanonicalAddr,
+    shares: Asset,
+) -> Result<Response, ContractError> {
+    let share_token = match &shares.info {
+        AssetInfo::Token { contract_addr } => addr_canonicalize(deps.api, contract_addr.as_str())?,
+        AssetInfo::NativeToken { .. } => return Err(ContractError::InvalidAsset {}),
+    };
+    let asset_info = may_load_share_token_to_asset(deps.storage, &share_token)?
+        .ok_or(ContractError::InvalidAsset {})?;
+    let shares = shares.amount;
+
+    let mut reserve =
+        may_load_reserve(deps.storage, &asset_info)?.ok_or(ContractError::ReserveNotFound {})?;
+
+    accrue_interest(&mut reserve, env.block.height)?;
+
+    let amount = shares_to_amount(&reserve, shares)?;
+    if amount.is_zero() {
+        return Err(ContractError::InvalidAmount {});
+    }
+    if amount > reserve.liquidity {
+        return Err(ContractError::InsufficientLiquidity {});
+    }
+
+    reserve.liquidity = reserve.liquidity.checked_sub(amount)?;
+    reserve.total_shares = reserve.total_shares.checked_sub(shares)?;
+    store_reserve(deps.storage, &reserve)?;
+
+    let burn = CosmosMsg::Wasm(WasmMsg::Execute {
+        contract_addr: deps.api.addr_humanize(&share_token)?.to_string(),
+        msg: to_binary(&Cw20ExecuteMsg::Burn { amount: shares })?,
+        funds: vec![],
+    });
+
+    let asset = Asset {
+        info: asset_info,
+        amount,
+    };
+    let transfer = to_payout_msg(deps.storage, deps.api, &asset, lender)?;
+    let event = LendingEvent::Withdrawn(LiquidityEvent {
+        lender: deps.api.addr_humanize(lender)?.to_string(),
+        asset: asset.clone(),
+        shares,
+    });
+
+    Ok(Response::new()
+        .add_message(burn)
+        .add_submessage(transfer)
+        .add_attributes(vec![
+            Attribute::new("action", "withdraw"),
+            Attribute::new("asset", asset.to_string()),
+            Attribute::new("shares", shares.to_string()),
+        ])
+        .add_event(event.into()))
+}
+
+/// ## Description
+/// Borrow from a pooled reserve against the given collateral
+/// Returns [`Response`] with specified attributes and messages if operation was successful
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`]
+///
+/// * **env** is an object of type [`Env`]
+///
+/// * **borrower** is an object of type [`CanonicalAddr`]
+///
+/// * **asset_info** is an object of type [`AssetInfo`]
+///
+/// * **amount** is an object of type [`Uint128`]
+///
+/// * **collateral** is an object of type [`Asset`]
+pub fn borrow(
+    deps: DepsMut,
+    env: Env,
+    borrower: &CanonicalAddr,
+    asset_info: AssetInfo,
+    amount: Uint128,
+    collateral: Asset,
+) -> Result<Response, ContractError> {
+    if amount.is_zero() || collateral.amount.is_zero() {
+        return Err(ContractError::InvalidAmount {});
+    }
+
+    let mut reserve =
+        may_load_reserve(deps.storage, &asset_info)?.ok_or(ContractError::ReserveNotFound {})?;
+
+    if collateral.info != reserve.collateral_info {
+        return Err(ContractError::InvalidAsset {});
+    }
+
+    accrue_interest(&mut reserve, env.block.height)?;
+
+    if amount > reserve.liquidity {
+        return Err(ContractError::InsufficientLiquidity {});
+    }
+
+    if exceeds_ltv(&reserve, collateral.amount, amount, reserve.max_ltv)? {
+        return Err(ContractError::InsufficientCollateral {});
+    }
+
+    let scaled_debt = scale_debt_up(&reserve, amount)?;
+    validate_borrower(deps.storage, &reserve, borrower, scaled_debt)?;
+
+    reserve.liquidity = reserve.liquidity.checked_sub(amount)?;
+    reserve.total_scaled_debt = reserve.total_scaled_debt.checked_add(scaled_debt)?;
+    store_reserve(deps.storage, &reserve)?;
+
+    let mut state = load_state(deps.storage)?;
+    state.loan_count = state.loan_count.checked_add(1u64).unwrap();
+    store_state(deps.storage, &state)?;
+
+    let loan = Loan {
+        id: state.loan_count,
+        borrower: borrower.clone(),
+        asset_info,
+        collateral,
+        scaled_debt,
+        principal: amount,
+        borrowed_at: env.block.height,
+    };
+    store_loan(deps.storage, &loan)?;
+    update_borrower_scaled_debt(deps.storage, &loan.asset_info, borrower, |debt| {
+        Ok(debt.checked_add(scaled_debt)?)
+    })?;
+
+    add_collateral_escrowed(deps.storage, &loan.collateral)?;
+    update_asset_stats(deps.storage, &loan.asset_info, |stats| {
+        stats.principal_outstanding = stats.principal_outstanding.checked_add(amount)?;
+        stats.volume_lent = stats.volume_lent.checked_add(amount)?;
+        stats.active_loans = stats.active_loans.checked_add(1u64).unwrap();
+        Ok(())
+    })?;
+
+    let asset = Asset {
+        info: loan.asset_info.clone(),
+        amount,
+    };
+    let mut profile = load_borrower_profile(deps.storage, borrower)?;
+    profile.loans_taken = profile.loans_taken.checked_add(1u64).unwrap();
+    profile.add_principal(&asset)?;
+    store_borrower_profile(deps.storage, borrower, &profile)?;
+
+    let borrower_addr = deps.api.addr_humanize(borrower)?;
+    let transfer = to_transfer_msg(&asset, &borrower_addr)?;
+    let event = LendingEvent::Borrowed(BorrowEvent {
+        loan_id: loan.id,
+        borrower: borrower_addr.to_string(),
+        asset: asset.clone(),
+        collateral: loan.collateral.clone(),
+    });
+
+    Ok(Response::new()
+        .add_message(transfer)
+        .add_attributes(vec![
+            Attribute::new("action", "borrow"),
+            Attribute::new("loan_id", loan.id.to_string()),
+            Attribute::new("asset", asset.to_string()),
+            Attribute::new("collateral", loan.collateral.to_string()),
+        ])
+        .add_event(event.into()))
+}
+
+/// ## Description
+/// Repay a pooled loan in part or in full, returning the collateral once fully repaid
+/// Returns [`Response`] with specified attributes and messages if operation was successful
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`]
+///
+/// * **env** is an object of type [`Env`]
+///
+/// * **payer** is an object of type [`CanonicalAddr`]
+///
+/// * **id** is [`u64`]
+///
+/// * **asset** is an object of type [`Asset`]
+pub fn repay(
+    deps: DepsMut,
+    env: Env,
+    payer: &CanonicalAddr,
+    id: u64,
+    asset: Asset,
+) -> Result<Response, ContractError> {
+    let mut loan = may_load_loan(deps.storage, id)?.ok_or(ContractError::LoanNotFound {})?;
+
+    if asset.info != loan.asset_info {
+        return Err(ContractError::InvalidAsset {});
+    }
+
+    let mut reserve = may_load_reserve(deps.storage, &loan.asset_info)?
+        .ok_or(ContractError::ReserveNotFound {})?;
+
+    accrue_interest(&mut reserve, env.block.height)?;
+
+    let debt = loan_debt(&reserve, &loan)?;
+    let (interest, principal) = repayment_parts(&loan, debt, asset.amount.min(debt));
+
+    let repaid_in_full = asset.amount >= debt;
+    let repaid = if repaid_in_full {
+        reserve.total_scaled_debt = reserve.total_scaled_debt.checked_sub(loan.scaled_debt)?;
+        remove_loan(deps.storage, id)?;
+        update_borrower_scaled_debt(deps.storage, &loan.asset_info, &loan.borrower, |debt| {
+            Ok(debt.checked_sub(loan.scaled_debt)?)
+        })?;
+        sub_collateral_escrowed(deps.storage, &loan.collateral)?;
+        update_asset_stats(deps.storage, &loan.asset_info, |stats| {
+            stats.active_loans = stats.active_loans.checked_sub(1u64).unwrap();
+            Ok(())
+        })?;
+
+        // Pooled loans have no deadline, so repaying in full is always on time
+        let mut profile = load_borrower_profile(deps.storage, &loan.borrower)?;
+        profile.loans_repaid_on_time = profile.loans_repaid_on_time.checked_add(1u64).unwrap();
+        store_borrower_profile(deps.storage, &loan.borrower, &profile)?;
+
+        debt
+    } else {
+        let scaled_debt = scale_debt_down(&reserve, asset.amount)?;
+        reserve.total_scaled_debt = reserve.total_scaled_debt.checked_sub(scaled_debt)?;
+        loan.scaled_debt = loan.scaled_debt.checked_sub(scaled_debt)?;
+        loan.principal = loan.principal.checked_sub(principal)?;
+        store_loan(deps.storage, &loan)?;
+        update_borrower_scaled_debt(deps.storage, &loan.asset_info, &loan.borrower, |debt| {
+            Ok(debt.checked_sub(scaled_debt)?)
+        })?;
+
+        asset.amount
+    };
+
+    reserve.liquidity = reserve.liquidity.checked_add(repaid)?;
+    store_reserve(deps.storage, &reserve)?;
+
+    update_asset_stats(deps.storage, &loan.asset_info, |stats| {
+        stats.interest_paid = stats.interest_paid.checked_add(interest)?;
+        stats.principal_outstanding = stats.principal_outstanding.checked_sub(principal)?;
+        Ok(())
+    })?;
+
+    // Payouts are dispatched only after the loan is settled in storage
+    let mut msgs: Vec<SubMsg> = vec![];
+    if repaid_in_full {
+        msgs.push(to_payout_msg(
+            deps.storage,
+            deps.api,
+            &loan.collateral,
+            &loan.borrower,
+        )?);
+        if asset.amount > debt {
+            let refund = Asset {
+                info: asset.info.clone(),
+                amount: asset.amount.checked_sub(debt)?,
+            };
+            msgs.push(to_payout_msg(deps.storage, deps.api, &refund, payer)?);
+        }
+    }
+
+    let remaining = debt.checked_sub(repaid)?;
+    let event = LendingEvent::Repaid(RepayEvent {
+        loan_id: id,
+        payer: deps.api.addr_humanize(payer)?.to_string(),
+        asset: Asset {
+            info: loan.asset_info.clone(),
+            amount: repaid,
+        },
+        remaining,
+    });
+
+    Ok(Response::new()
+        .add_submessages(msgs)
+        .add_attributes(vec![
+            Attribute::new("action", "repay"),
+            Attribute::new("loan_id", id.to_string()),
+            Attribute::new("repaid", repaid.to_string()),
+            Attribute::new("remaining", remaining.to_string()),
+        ])
+        .add_event(event.into()))
+}
+
+/// ## Description
+/// Repay an unhealthy pooled loan in full in exchange for its collateral plus a bonus,
+/// returning the rest of the collateral to the borrower
+/// Returns [`Response`] with specified attributes and messages if operation was successful
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`]
+///
+/// * **env** is an object of type [`Env`]
+///
+/// * **liquidator** is an object of type [`CanonicalAddr`]
+///
+/// * **id** is [`u64`]
+///
+/// * **asset** is an object of type [`Asset`]
+pub fn liquidate(
+    deps: DepsMut,
+    env: Env,
+    liquidator: &CanonicalAddr,
+    id: u64,
+    asset: Asset,
+) -> Result<Response, ContractError> {
+    let loan = may_load_loan(deps.storage, id)?.ok_or(ContractError::LoanNotFound {})?;
+
+    if asset.info != loan.asset_info {
+        return Err(ContractError::InvalidAsset {});
+    }
+
+    let mut reserve = may_load_reserve(deps.storage, &loan.asset_info)?
+        .ok_or(ContractError::ReserveNotFound {})?;
+
+    accrue_interest(&mut reserve, env.block.height)?;
+
+    let debt = loan_debt(&reserve, &loan)?;
+    if !is_liquidatable(&reserve, &loan, debt)? {
+        return Err(ContractError::NotLiquidatable {});
+    }
+
+    if asset.amount < debt {
+        return Err(ContractError::InvalidAmount {});
+    }
+
+    let seized = seized_collateral(&reserve, &loan, debt)?;
+
+    reserve.liquidity = reserve.liquidity.checked_add(debt)?;
+    reserve.total_scaled_debt = reserve.total_scaled_debt.checked_sub(loan.scaled_debt)?;
+    store_reserve(deps.storage, &reserve)?;
+    remove_loan(deps.storage, id)?;
+    update_borrower_scaled_debt(deps.storage, &loan.asset_info, &loan.borrower, |debt| {
+        Ok(debt.checked_sub(loan.scaled_debt)?)
+    })?;
+
+    let (interest, principal) = repayment_parts(&loan, debt, debt);
+    sub_collateral_escrowed(deps.storage, &loan.collateral)?;
+    update_asset_stats(deps.storage, &loan.asset_info, |stats| {
+        stats.interest_paid = stats.interest_paid.checked_add(interest)?;
+        stats.principal_outstanding = stats.principal_outstanding.checked_sub(principal)?;
+        stats.active_loans = stats.active_loans.checked_sub(1u64).unwrap();
+        stats.liquidated_loans = stats.liquidated_loans.checked_add(1u64).unwrap();
+        Ok(())
+    })?;
+
+    let mut profile = load_borrower_profile(deps.storage, &loan.borrower)?;
+    profile.loans_liquidated = profile.loans_liquidated.checked_add(1u64).unwrap();
+    store_borrower_profile(deps.storage, &loan.borrower, &profile)?;
+
+    let mut msgs: Vec<SubMsg> = vec![to_payout_msg(
+        deps.storage,
+        deps.api,
+        &Asset {
+            info: loan.collateral.info.clone(),
+            amount: seized,
+        },
+        liquidator,
+    )?];
+    if loan.collateral.amount > seized {
+        msgs.push(to_payout_msg(
+            deps.storage,
+            deps.api,
+            &Asset {
+                info: loan.collateral.info.clone(),
+                amount: loan.collateral.amount.checked_sub(seized)?,
+            },
+            &loan.borrower,
+        )?);
+    }
+    if asset.amount > debt {
+        msgs.push(to_payout_msg(
+            deps.storage,
+            deps.api,
+            &Asset {
+                info: asset.info.clone(),
+                amount: asset.amount.checked_sub(debt)?,
+            },
+            liquidator,
+        )?);
+    }
+
+    let event = LendingEvent::Liquidated(LiquidateEvent {
+        loan_id: id,
+        liquidator: deps.api.addr_humanize(liquidator)?.to_string(),
+        asset: Asset {
+            info: loan.asset_info.clone(),
+            amount: debt,
+        },
+        seized: Asset {
+            info: loan.collateral.info.clone(),
+            amount: seized,
+        },
+    });
+
+    Ok(Response::new()
+        .add_submessages(msgs)
+        .add_attributes(vec![
+            Attribute::new("action", "liquidate"),
+            Attribute::new("loan_id", id.to_string()),
+            Attribute::new("repaid", debt.to_string()),
+            Attribute::new("seized", seized.to_string()),
+        ])
+        .add_event(event.into()))
+}
+
+/// ## Description
+/// Lend from a pooled reserve within a single transaction, sending the amount and the
+/// callback to the borrower followed by a self-call verifying that it has been returned
+/// Returns [`Response`] with specified attributes and messages if operation was successful
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`]
+///
+/// * **env** is an object of type [`Env`]
+///
+/// * **borrower** is an object of type [`CanonicalAddr`]
+///
+/// * **asset_info** is an object of type [`AssetInfo`]
+///
+/// * **amount** is an object of type [`Uint128`]
+///
+/// * **callback_msg** is an object of type [`Binary`], executed on the borrower
+pub fn flash_loan(
+    deps: DepsMut,
+    env: Env,
+    borrower: &CanonicalAddr,
+    asset_info: AssetInfo,
+    amount: Uint128,
+    callback_msg: Binary,
+) -> Result<Response, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::InvalidAmount {});
+    }
+
+    let mut reserve =
+        may_load_reserve(deps.storage, &asset_info)?.ok_or(ContractError::ReserveNotFound {})?;
+
+    accrue_interest(&mut reserve, env.block.height)?;
+
+    if amount > reserve.liquidity {
+        return Err(ContractError::InsufficientLiquidity {});
+    }
+
+    let fee = flash_loan_fee(&reserve, amount)?;
+    let balance = asset_info.query_pool(&deps.querier, env.contract.address.clone())?;
+    store_flash_loan(
+        deps.storage,
+        &FlashLoan {
+            asset_info: asset_info.clone(),
+            balance,
+            fee,
+        },
+    )?;
+
+    let borrower_addr = deps.api.addr_humanize(borrower)?;
+    let asset = Asset {
+        info: asset_info,
+        amount,
+    };
+    let msgs: Vec<CosmosMsg> = vec![
+        to_transfer_msg(&asset, &borrower_addr)?,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: borrower_addr.to_string(),
+            msg: callback_msg,
+            funds: vec![],
+        }),
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            msg: to_binary(&ExecuteMsg::FinishFlashLoan {})?,
+            funds: vec![],
+        }),
+    ];
+
+    let event = LendingEvent::FlashLoaned(FlashLoanEvent {
+        borrower: borrower_addr.to_string(),
+        asset: asset.clone(),
+        fee,
+    });
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_attributes(vec![
+            Attribute::new("action", "flash_loan"),
+            Attribute::new("asset", asset.to_string()),
+            Attribute::new("fee", fee.to_string()),
+        ])
+        .add_event(event.into()))
+}
+
+/// ## Description
+/// Verify that the ongoing flash loan has been returned with its fee, crediting the fee
+/// to the lenders of the reserve
+/// Returns [`Response`] with specified attributes if operation was successful
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`]
+///
+/// * **env** is an object of type [`Env`]
+///
+/// * **sender** is an object of type [`Addr`]
+pub fn finish_flash_loan(
+    deps: DepsMut,
+    env: Env,
+    sender: &Addr,
+) -> Result<Response, ContractError> {
+    if *sender != env.contract.address {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let flash_loan = may_load_flash_loan(deps.storage)?.ok_or(ContractError::Unauthorized {})?;
+
+    let balance = flash_loan
+        .asset_info
+        .query_pool(&deps.querier, env.contract.address.clone())?;
+    if balance < flash_loan.balance.checked_add(flash_loan.fee)? {
+        return Err(ContractError::FlashLoanNotReturned {});
+    }
+
+    let mut reserve = may_load_reserve(deps.storage, &flash_loan.asset_info)?
+        .ok_or(ContractError::ReserveNotFound {})?;
+
+    accrue_interest(&mut reserve, env.block.height)?;
+
+    reserve.liquidity = reserve.liquidity.checked_add(flash_loan.fee)?;
+    store_reserve(deps.storage, &reserve)?;
+    remove_flash_loan(deps.storage);
+
+    Ok(Response::new().add_attributes(vec![
+        Attribute::new("action", "finish_flash_loan"),
+        Attribute::new("asset", flash_loan.asset_info.to_string()),
+        Attribute::new("fee", flash_loan.fee.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Allow channels over the given connection to a counterpart contract of the given port,
+/// or disallow them if no port is given
+/// Returns [`Response`] with specified attributes and messages if operation was successful
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`]
+///
+/// * **sender** is an object of type [`CanonicalAddr`]
+///
+/// * **connection_id** is [`String`]
+///
+/// * **port_id** is an [`Option`] of [`String`]
+pub fn set_ibc_counterparty(
+    deps: DepsMut,
+    sender: &CanonicalAddr,
+    connection_id: String,
+    port_id: Option<String>,
+) -> Result<Response, ContractError> {
+    if load_config(deps.storage)?.owner != *sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    match &port_id {
+        Some(port_id) => store_ibc_counterparty(deps.storage, &connection_id, port_id)?,
+        None => remove_ibc_counterparty(deps.storage, &connection_id),
+    }
+
+    Ok(Response::new().add_attributes(vec![
+        Attribute::new("action", "set_ibc_counterparty"),
+        Attribute::new("connection_id", connection_id),
+        Attribute::new("port_id", port_id.unwrap_or_default()),
+    ]))
+}
+
+/// ## Description
+/// Set the IBC hook middleware allowed to run IBC hook messages, or let borrowers run them
+/// if none is given, and the transfer channels tokens can arrive over
+/// Returns [`Response`] with specified attributes and messages if operation was successful
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`]
+///
+/// * **sender** is an object of type [`CanonicalAddr`]
+///
+/// * **hook** is an [`Option`] of [`CanonicalAddr`]
+///
+/// * **transfer_channels** is a [`Vec`] of [`String`]
+pub fn set_ibc_hook(
+    deps: DepsMut,
+    sender: &CanonicalAddr,
+    hook: Option<CanonicalAddr>,
+    transfer_channels: Vec<String>,
+) -> Result<Response, ContractError> {
+    if load_config(deps.storage)?.owner != *sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    let attrs = vec![
+        Attribute::new("action", "set_ibc_hook"),
+        Attribute::new(
+            "hook",
+            hook.as_ref()
+                .map(|hook| deps.api.addr_humanize(hook))
+                .transpose()?
+                .map(|hook| hook.to_string())
+                .unwrap_or_default(),
         ),
-    ));
+        Attribute::new("transfer_channels", transfer_channels.join(",")),
+    ];
+    store_ibc_hook_config(
+        deps.storage,
+        &IbcHookConfig {
+            hook,
+            transfer_channels,
+        },
+    )?;
 
     Ok(Response::new().add_attributes(attrs))
 }
+
+/// ## Description
+/// Settle a CW20 payout once its transfer is replied, leaving the asset claimable by the
+/// recipient if the transfer failed
+/// Returns [`Response`] with specified attributes and messages if operation was successful
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`]
+///
+/// * **id** is [`u64`]
+///
+/// * **result** is an object of type [`ContractResult<SubMsgExecutionResponse>`]
+pub fn settle_payout(
+    deps: DepsMut,
+    id: u64,
+    result: ContractResult<SubMsgExecutionResponse>,
+) -> Result<Response, ContractError> {
+    let payout = may_load_payout(deps.storage, id)?.ok_or_else(|| StdError::not_found("Payout"))?;
+    remove_payout(deps.storage, id);
+
+    match result {
+        ContractResult::Ok(_) => Ok(Response::new()),
+        ContractResult::Err(err) => {
+            add_claimable(deps.storage, &payout.recipient, &payout.asset)?;
+
+            let recipient = deps.api.addr_humanize(&payout.recipient)?.to_string();
+            let event = LendingEvent::PayoutDeferred(PayoutEvent {
+                recipient: recipient.clone(),
+                asset: payout.asset.clone(),
+            });
+
+            Ok(Response::new()
+                .add_attributes(vec![
+                    Attribute::new("action", "defer_payout"),
+                    Attribute::new("recipient", recipient),
+                    Attribute::new("asset", payout.asset.to_string()),
+                    Attribute::new("error", err),
+                ])
+                .add_event(event.into()))
+        }
+    }
+}
+
+/// ## Description
+/// Claim every payout to the claimer whose transfer failed
+/// Returns [`Response`] with specified attributes and messages if operation was successful
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`]
+///
+/// * **claimer** is an object of type [`CanonicalAddr`]
+pub fn claim_pending(deps: DepsMut, claimer: &CanonicalAddr) -> Result<Response, ContractError> {
+    let claimable = read_claimable(deps.storage, claimer)?;
+    if claimable.is_empty() {
+        return Err(ContractError::NothingToClaim {});
+    }
+
+    let recipient = deps.api.addr_humanize(claimer)?;
+    let mut msgs: Vec<CosmosMsg> = vec![];
+    let mut events: Vec<Event> = vec![];
+    for asset in claimable.iter() {
+        remove_claimable(deps.storage, claimer, &asset.info);
+        msgs.push(to_transfer_msg(asset, &recipient)?);
+        events.push(
+            LendingEvent::PendingClaimed(PayoutEvent {
+                recipient: recipient.to_string(),
+                asset: asset.clone(),
+            })
+            .into(),
+        );
+    }
+
+    Ok(Response::new()
+        .add_messages(msgs)
+        .add_attributes(vec![
+            Attribute::new("action", "claim_pending"),
+            Attribute::new(
+                "assets",
+                claimable
+                    .iter()
+                    .map(|asset| asset.to_string())
+                    .collect::<Vec<String>>()
+                    .join(","),
+            ),
+        ])
+        .add_events(events))
+}
+
+/// Returns the event describing the borrow request
+fn to_borrow_request_event(
+    api: &dyn Api,
+    borrower: &CanonicalAddr,
+    borrow_request: &BorrowRequest,
+) -> StdResult<BorrowRequestEvent> {
+    Ok(BorrowRequestEvent {
+        id: borrow_request.id,
+        borrower: api.addr_humanize(borrower)?.to_string(),
+        collateral: borrow_request.collateral.clone(),
+        period: borrow_request.period,
+        lender: borrow_request
+            .lender
+            .as_ref()
+            .map(|lender| api.addr_humanize(lender).map(|lender| lender.to_string()))
+            .transpose()?,
+        expires_at: borrow_request.expires_at,
+    })
+}
+
+/// Returns the message refunding the collateral of a removed borrow request. Collateral
+/// locked on a remote chain is released over IBC, and the request is kept aside until the
+/// release is acknowledged so that it can be restored if the release fails
+fn to_refund_msg(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    env: &Env,
+    borrower: &CanonicalAddr,
+    borrow_request: BorrowRequest,
+) -> Result<SubMsg, ContractError> {
+    let remote = match may_load_remote_collateral(storage, borrow_request.id)? {
+        Some(remote) => remote,
+        None => return to_payout_msg(storage, api, &borrow_request.collateral, borrower),
+    };
+
+    if may_load_ibc_channel(storage, &remote.channel_id)?.is_none() {
+        return Err(ContractError::ChannelNotFound {});
+    }
+
+    let packet = LendingPacket::ReleaseCollateral {
+        id: borrow_request.id,
+        refund_address: remote.refund_address,
+        collateral: remote.collateral,
+    };
+    store_pending_release(
+        storage,
+        &PendingRelease {
+            borrower: borrower.clone(),
+            borrow_request,
+        },
+    )?;
+
+    Ok(SubMsg::new(CosmosMsg::Ibc(IbcMsg::SendPacket {
+        channel_id: remote.channel_id,
+        data: to_binary(&packet)?,
+        timeout: IbcTimeout::with_timestamp(env.block.time.plus_seconds(IBC_PACKET_LIFETIME)),
+    })))
+}
+
+/// Returns the message paying the asset out to the recipient. A CW20 transfer is sent as a
+/// submessage replied to in any case, so that a token refusing the transfer leaves the asset
+/// claimable by the recipient instead of reverting the settlement that pays it out
+fn to_payout_msg(
+    storage: &mut dyn Storage,
+    api: &dyn Api,
+    asset: &Asset,
+    recipient: &CanonicalAddr,
+) -> Result<SubMsg, ContractError> {
+    let msg = to_transfer_msg(asset, &api.addr_humanize(recipient)?)?;
+
+    match &asset.info {
+        AssetInfo::NativeToken { .. } => Ok(SubMsg::new(msg)),
+        AssetInfo::Token { .. } => {
+            let id = store_payout(
+                storage,
+                &Payout {
+                    recipient: recipient.clone(),
+                    asset: asset.clone(),
+                },
+            )?;
+            Ok(SubMsg::reply_always(msg, PAYOUT_REPLY_ID_OFFSET + id))
+        }
+    }
+}
+
+/// ## Description
+/// Validates ratios and prices of [`ReserveConfig`]
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **config** is an object of type [`ReserveConfig`]
+fn validate_reserve_config(config: &ReserveConfig) -> Result<(), ContractError> {
+    if config.collateral_price.is_zero()
+        || config.max_ltv.is_zero()
+        || config.max_ltv > config.liquidation_threshold
+        || config.liquidation_threshold >= Decimal256::one()
+        || config.liquidation_bonus >= Decimal256::one()
+        || config.flash_loan_fee >= Decimal256::one()
+        || config.optimal_utilization.is_zero()
+        || config.optimal_utilization >= Decimal256::one()
+    {
+        return Err(ContractError::InvalidReserveConfig {});
+    }
+
+    // The rate is highest at full utilization, which bounds interest accrual
+    let max_rate = RateCurve::Kinked {
+        base_rate: config.base_rate,
+        slope1: config.slope1,
+        slope2: config.slope2,
+        optimal_utilization: config.optimal_utilization,
+    }
+    .rate(Decimal256::one())
+    .map_err(|_| ContractError::InvalidReserveConfig {})?;
+    if max_rate > Decimal256::from_ratio(MAX_BORROW_RATE_PER_BILLION, 1_000_000_000u64) {
+        return Err(ContractError::InvalidReserveConfig {});
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Validates that a borrower meets the [`BorrowerRequirements`] of a reserve to borrow the
+/// given scaled debt from it
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **reserve** is an object of type [`Reserve`]
+///
+/// * **borrower** is an object of type [`CanonicalAddr`]
+///
+/// * **scaled_debt** is an object of type [`Uint128`]
+fn validate_borrower(
+    storage: &dyn Storage,
+    reserve: &Reserve,
+    borrower: &CanonicalAddr,
+    scaled_debt: Uint128,
+) -> Result<(), ContractError> {
+    let requirements = &reserve.borrower_requirements;
+    if matches!(&requirements.allowlist, Some(allowlist) if !allowlist.contains(borrower)) {
+        return Err(ContractError::BorrowerRequirementsNotMet {});
+    }
+
+    let profile = load_borrower_profile(storage, borrower)?;
+    let loans_repaid = profile
+        .loans_repaid_on_time
+        .saturating_add(profile.loans_repaid_late);
+    if matches!(requirements.min_loans_repaid, Some(min) if loans_repaid < min)
+        || matches!(requirements.max_loans_liquidated, Some(max) if profile.loans_liquidated > max)
+    {
+        return Err(ContractError::BorrowerRequirementsNotMet {});
+    }
+
+    if let Some(max_outstanding_debt) = requirements.max_outstanding_debt {
+        let scaled_debt = load_borrower_scaled_debt(storage, &reserve.asset_info, borrower)?
+            .checked_add(scaled_debt)?;
+        if scaled_debt_value(reserve, scaled_debt)? > max_outstanding_debt {
+            return Err(ContractError::BorrowerRequirementsNotMet {});
+        }
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns an empty [`Reserve`] with the given config
+/// Otherwise returns [`ContractError`] if an address of the borrower allowlist is invalid
+/// ## Params
+/// * **api** is an object of type [`Api`]
+///
+/// * **asset_info** is an object of type [`AssetInfo`]
+///
+/// * **share_token** is an object of type [`CanonicalAddr`]
+///
+/// * **config** is an object of type [`ReserveConfig`]
+///
+/// * **height** is [`u64`]
+fn to_reserve(
+    api: &dyn Api,
+    asset_info: AssetInfo,
+    share_token: CanonicalAddr,
+    config: ReserveConfig,
+    height: u64,
+) -> Result<Reserve, ContractError> {
+    let allowlist = match config.borrower_allowlist {
+        Some(allowlist) => Some(
+            allowlist
+                .iter()
+                .map(|addr| addr_canonicalize(api, addr))
+                .collect::<Result<Vec<CanonicalAddr>, ContractError>>()?,
+        ),
+        None => None,
+    };
+
+    Ok(Reserve {
+        asset_info,
+        share_token,
+        collateral_info: config.collateral_info,
+        collateral_price: config.collateral_price,
+        max_ltv: config.max_ltv,
+        liquidation_threshold: config.liquidation_threshold,
+        liquidation_bonus: config.liquidation_bonus,
+        flash_loan_fee: config.flash_loan_fee,
+        rate_curve: RateCurve::Kinked {
+            base_rate: config.base_rate,
+            slope1: config.slope1,
+            slope2: config.slope2,
+            optimal_utilization: config.optimal_utilization,
+        },
+        liquidity: Uint128::zero(),
+        total_shares: Uint128::zero(),
+        total_scaled_debt: Uint128::zero(),
+        borrow_index: Decimal256::one(),
+        last_accrued_at: height,
+        borrower_requirements: BorrowerRequirements {
+            allowlist,
+            min_loans_repaid: config.min_loans_repaid,
+            max_loans_liquidated: config.max_loans_liquidated,
+            max_outstanding_debt: config.max_outstanding_debt,
+        },
+    })
+}
@@ -1,19 +1,28 @@
+use std::collections::BTreeMap;
+
 #[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
 use cosmwasm_std::{
-    entry_point, from_binary, to_binary, Binary, Coin, Deps, DepsMut, Env, MessageInfo, Response,
-    StdResult,
+    from_binary, to_binary, Attribute, Binary, CanonicalAddr, Coin, Deps, DepsMut, Env,
+    MessageInfo, Reply, Response, StdError, StdResult, Uint128,
 };
-use cw2::set_contract_version;
+use cw2::{get_contract_version, set_contract_version};
 
 use crate::{
-    commands,
+    commands::{self, INSTANTIATE_SHARE_TOKEN_REPLY_ID, PAYOUT_REPLY_ID_OFFSET},
     error::ContractError,
+    migration::MIGRATIONS,
     queries,
-    state::{store_state, State},
-    utils::{to_cw20_token, to_native_token},
+    state::{load_ibc_hook_config, may_load_flash_loan, store_config, store_state, Config, State},
+    utils::{
+        addr_canonicalize, to_cw20_token, to_ibc_denom, to_native_token, validate_addresses,
+        validate_asset_info,
+    },
 };
 
-use services::lending::{Cw20HookMsg, ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
+use services::lending::{
+    BatchMsg, Cw20HookMsg, ExecuteMsg, IbcHookMsg, InstantiateMsg, LoanCursor, MigrateMsg, QueryMsg,
+};
 
 /// Contract name that is used for migration.
 const CONTRACT_NAME: &str = "jihoonsong-lending";
@@ -24,11 +33,18 @@ const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 pub fn instantiate(
     deps: DepsMut,
     _env: Env,
-    _info: MessageInfo,
-    _msg: InstantiateMsg,
+    info: MessageInfo,
+    msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
+    store_config(
+        deps.storage,
+        &Config {
+            owner: addr_canonicalize(deps.api, info.sender.as_str())?,
+            token_code_id: msg.token_code_id,
+        },
+    )?;
     store_state(deps.storage, &State::default())?;
 
     Ok(Response::default())
@@ -37,51 +53,464 @@ pub fn instantiate(
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn execute(
     deps: DepsMut,
-    _env: Env,
+    env: Env,
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    // Nothing but returning the funds may run until an ongoing flash loan is finished,
+    // so that neither pool operations nor another flash loan can fake its return
+    if msg != (ExecuteMsg::FinishFlashLoan {}) && may_load_flash_loan(deps.storage)?.is_some() {
+        return Err(ContractError::FlashLoanInProgress {});
+    }
+
     match msg {
-        ExecuteMsg::MakeBorrowRequestCw20Token(cw20_receive_msg) => {
+        ExecuteMsg::Receive(cw20_receive_msg) => {
+            let sender = addr_canonicalize(deps.api, cw20_receive_msg.sender.as_str())?;
+            let asset = to_cw20_token(info.sender, cw20_receive_msg.amount)?;
             match from_binary(&cw20_receive_msg.msg) {
-                Ok(Cw20HookMsg::MakeBorrowRequest { period }) => {
-                    let borrower = deps
-                        .api
-                        .addr_canonicalize(cw20_receive_msg.sender.as_str())?;
-                    let collateral = to_cw20_token(info.sender, cw20_receive_msg.amount)?;
-                    commands::make_borrow_request(deps, &borrower, collateral, period)
+                Ok(Cw20HookMsg::MakeBorrowRequest {
+                    period,
+                    lender,
+                    expires_at,
+                }) => {
+                    let lender = lender
+                        .map(|lender| addr_canonicalize(deps.api, lender.as_str()))
+                        .transpose()?;
+                    commands::make_borrow_request(
+                        deps, env, &sender, asset, period, lender, expires_at, None,
+                    )
+                }
+                Ok(Cw20HookMsg::Deposit {}) => commands::deposit(deps, env, &sender, asset),
+                Ok(Cw20HookMsg::Withdraw {}) => commands::withdraw(deps, env, &sender, asset),
+                Ok(Cw20HookMsg::Borrow { asset_info, amount }) => {
+                    let asset_info = validate_asset_info(deps.api, asset_info)?;
+                    commands::borrow(deps, env, &sender, asset_info, amount, asset)
+                }
+                Ok(Cw20HookMsg::Repay { id }) => commands::repay(deps, env, &sender, id, asset),
+                Ok(Cw20HookMsg::Liquidate { id }) => {
+                    commands::liquidate(deps, env, &sender, id, asset)
                 }
                 Err(_) => Err(ContractError::InvalidCw20HookMsg {}),
             }
         }
-        ExecuteMsg::MakeBorrowRequestNativeToken { period } => {
+        ExecuteMsg::IbcHook(IbcHookMsg::MakeBorrowRequest {
+            borrower,
+            denom_trace,
+            period,
+            lender,
+            expires_at,
+        }) => {
+            validate_funds(&info.funds)?;
+            let sender = addr_canonicalize(deps.api, info.sender.as_str())?;
+            let borrower = addr_canonicalize(deps.api, borrower.as_str())?;
+            validate_ibc_hook(
+                deps.as_ref(),
+                &sender,
+                &borrower,
+                &denom_trace,
+                &info.funds[0].denom,
+            )?;
+            let collateral = to_native_token(info.funds[0].denom.clone(), info.funds[0].amount)?;
+            let lender = lender
+                .map(|lender| addr_canonicalize(deps.api, lender.as_str()))
+                .transpose()?;
+            commands::make_borrow_request(
+                deps, env, &borrower, collateral, period, lender, expires_at, None,
+            )
+        }
+        ExecuteMsg::MakeBorrowRequestNativeToken {
+            period,
+            lender,
+            expires_at,
+        } => {
             validate_funds(&info.funds)?;
-            let borrower = deps.api.addr_canonicalize(info.sender.as_str())?;
+            let borrower = addr_canonicalize(deps.api, info.sender.as_str())?;
             let collateral = to_native_token(info.funds[0].denom.clone(), info.funds[0].amount)?;
-            commands::make_borrow_request(deps, &borrower, collateral, period)
+            let lender = lender
+                .map(|lender| addr_canonicalize(deps.api, lender.as_str()))
+                .transpose()?;
+            commands::make_borrow_request(
+                deps, env, &borrower, collateral, period, lender, expires_at, None,
+            )
+        }
+        ExecuteMsg::CancelBorrowRequest { id } => {
+            validate_no_funds(&info.funds)?;
+            let borrower = addr_canonicalize(deps.api, info.sender.as_str())?;
+            commands::cancel_borrow_request(deps, env, &borrower, id)
+        }
+        ExecuteMsg::PruneExpired { limit } => {
+            validate_no_funds(&info.funds)?;
+            commands::prune_expired(deps, env, limit)
+        }
+        ExecuteMsg::CreateReserve {
+            asset_info,
+            mut config,
+        } => {
+            validate_no_funds(&info.funds)?;
+            let sender = addr_canonicalize(deps.api, info.sender.as_str())?;
+            let asset_info = validate_asset_info(deps.api, asset_info)?;
+            config.collateral_info = validate_asset_info(deps.api, config.collateral_info)?;
+            config.borrower_allowlist = config
+                .borrower_allowlist
+                .map(|allowlist| validate_addresses(deps.api, allowlist))
+                .transpose()?;
+            commands::create_reserve(deps, env, &sender, asset_info, config)
+        }
+        ExecuteMsg::UpdateReserve {
+            asset_info,
+            mut config,
+        } => {
+            validate_no_funds(&info.funds)?;
+            let sender = addr_canonicalize(deps.api, info.sender.as_str())?;
+            let asset_info = validate_asset_info(deps.api, asset_info)?;
+            config.collateral_info = validate_asset_info(deps.api, config.collateral_info)?;
+            config.borrower_allowlist = config
+                .borrower_allowlist
+                .map(|allowlist| validate_addresses(deps.api, allowlist))
+                .transpose()?;
+            commands::update_reserve(deps, env, &sender, asset_info, config)
+        }
+        ExecuteMsg::DepositNativeToken {} => {
+            validate_funds(&info.funds)?;
+            let lender = addr_canonicalize(deps.api, info.sender.as_str())?;
+            let asset = to_native_token(info.funds[0].denom.clone(), info.funds[0].amount)?;
+            commands::deposit(deps, env, &lender, asset)
+        }
+        ExecuteMsg::BorrowNativeToken { asset_info, amount } => {
+            validate_funds(&info.funds)?;
+            let borrower = addr_canonicalize(deps.api, info.sender.as_str())?;
+            let asset_info = validate_asset_info(deps.api, asset_info)?;
+            let collateral = to_native_token(info.funds[0].denom.clone(), info.funds[0].amount)?;
+            commands::borrow(deps, env, &borrower, asset_info, amount, collateral)
+        }
+        ExecuteMsg::RepayNativeToken { id } => {
+            validate_funds(&info.funds)?;
+            let payer = addr_canonicalize(deps.api, info.sender.as_str())?;
+            let asset = to_native_token(info.funds[0].denom.clone(), info.funds[0].amount)?;
+            commands::repay(deps, env, &payer, id, asset)
+        }
+        ExecuteMsg::LiquidateNativeToken { id } => {
+            validate_funds(&info.funds)?;
+            let liquidator = addr_canonicalize(deps.api, info.sender.as_str())?;
+            let asset = to_native_token(info.funds[0].denom.clone(), info.funds[0].amount)?;
+            commands::liquidate(deps, env, &liquidator, id, asset)
+        }
+        ExecuteMsg::FlashLoan {
+            asset,
+            amount,
+            callback_msg,
+        } => {
+            let borrower = addr_canonicalize(deps.api, info.sender.as_str())?;
+            let asset = validate_asset_info(deps.api, asset)?;
+            commands::flash_loan(deps, env, &borrower, asset, amount, callback_msg)
+        }
+        ExecuteMsg::FinishFlashLoan {} => commands::finish_flash_loan(deps, env, &info.sender),
+        ExecuteMsg::Batch(msgs) => execute_batch(deps, env, info, msgs),
+        ExecuteMsg::SetIbcCounterparty {
+            connection_id,
+            port_id,
+        } => {
+            validate_no_funds(&info.funds)?;
+            let sender = addr_canonicalize(deps.api, info.sender.as_str())?;
+            commands::set_ibc_counterparty(deps, &sender, connection_id, port_id)
+        }
+        ExecuteMsg::SetIbcHook {
+            hook,
+            transfer_channels,
+        } => {
+            validate_no_funds(&info.funds)?;
+            let sender = addr_canonicalize(deps.api, info.sender.as_str())?;
+            let hook = hook
+                .map(|hook| addr_canonicalize(deps.api, hook.as_str()))
+                .transpose()?;
+            commands::set_ibc_hook(deps, &sender, hook, transfer_channels)
+        }
+        ExecuteMsg::RemoveStrandedBorrowRequest { id } => {
+            validate_no_funds(&info.funds)?;
+            let sender = addr_canonicalize(deps.api, info.sender.as_str())?;
+            commands::remove_stranded_borrow_request(deps, &sender, id)
+        }
+        ExecuteMsg::ClaimPending {} => {
+            validate_no_funds(&info.funds)?;
+            let sender = addr_canonicalize(deps.api, info.sender.as_str())?;
+            commands::claim_pending(deps, &sender)
         }
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        INSTANTIATE_SHARE_TOKEN_REPLY_ID => {
+            let res = msg.result.into_result().map_err(StdError::generic_err)?;
+            commands::register_share_token(deps, env, res.data)
+        }
+        id if id > PAYOUT_REPLY_ID_OFFSET => {
+            commands::settle_payout(deps, id - PAYOUT_REPLY_ID_OFFSET, msg.result)
+        }
+        _ => Err(StdError::generic_err("Unknown reply id").into()),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     match msg {
         QueryMsg::State {} => to_binary(&queries::query_state(deps)?),
         QueryMsg::BorrowRequestById { id } => {
-            to_binary(&queries::query_borrow_request_by_id(deps, id)?)
+            to_binary(&queries::query_borrow_request_by_id(deps, env, id)?)
+        }
+        QueryMsg::BorrowRequestByAddr {
+            borrower,
+            include_directed,
+        } => to_binary(&queries::query_borrow_requests_by_addr(
+            deps,
+            env,
+            borrower,
+            include_directed.unwrap_or(false),
+        )?),
+        QueryMsg::BorrowRequests {
+            start_after,
+            limit,
+            include_directed,
+        } => to_binary(&queries::query_borrow_requests(
+            deps,
+            env,
+            start_after,
+            limit,
+            include_directed.unwrap_or(false),
+        )?),
+        QueryMsg::BorrowRequestsByCollateral {
+            asset_info,
+            start_after,
+            limit,
+            include_directed,
+        } => to_binary(&queries::query_borrow_requests_by_collateral(
+            deps,
+            env,
+            validate_asset_info(deps.api, asset_info)?,
+            start_after,
+            limit,
+            include_directed.unwrap_or(false),
+        )?),
+        QueryMsg::RequestsDirectedTo {
+            lender,
+            start_after,
+            limit,
+        } => to_binary(&queries::query_requests_directed_to(
+            deps,
+            env,
+            lender,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::RequestsExpiringBefore {
+            expiration,
+            start_after,
+            limit,
+        } => to_binary(&queries::query_requests_expiring_before(
+            deps,
+            env,
+            expiration,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::Reserve { asset_info } => to_binary(&queries::query_reserve(
+            deps,
+            env,
+            validate_asset_info(deps.api, asset_info)?,
+        )?),
+        QueryMsg::Loan { id } => to_binary(&queries::query_loan(deps, env, id)?),
+        QueryMsg::SimulateRepay { id, at_height } => {
+            to_binary(&queries::query_simulate_repay(deps, env, id, at_height)?)
+        }
+        QueryMsg::SimulateLiquidation { id } => {
+            to_binary(&queries::query_simulate_liquidation(deps, env, id)?)
+        }
+        QueryMsg::LoanHealth { id } => to_binary(&queries::query_loan_health(deps, env, id)?),
+        QueryMsg::LiquidatableLoans { start_after, limit } => {
+            let start_after = match start_after {
+                Some(cursor) => Some(LoanCursor {
+                    asset_info: validate_asset_info(deps.api, cursor.asset_info)?,
+                    ..cursor
+                }),
+                None => None,
+            };
+            to_binary(&queries::query_liquidatable_loans(
+                deps,
+                env,
+                start_after,
+                limit,
+            )?)
         }
-        QueryMsg::BorrowRequestByAddr { borrower } => {
-            to_binary(&queries::query_borrow_requests_by_addr(deps, borrower)?)
+        QueryMsg::BorrowerProfile { address } => {
+            to_binary(&queries::query_borrower_profile(deps, address)?)
         }
+        QueryMsg::Stats {} => to_binary(&queries::query_stats(deps)?),
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> StdResult<Response> {
-    Ok(Response::default())
+pub fn migrate(mut deps: DepsMut, _env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+    let stored = get_contract_version(deps.storage)?;
+    if stored.contract != CONTRACT_NAME {
+        return Err(ContractError::CannotMigrateContract {});
+    }
+
+    let from = parse_version(&stored.version)?;
+    if from > parse_version(CONTRACT_VERSION)? {
+        return Err(ContractError::CannotMigrateVersion {});
+    }
+
+    for (version, transform) in MIGRATIONS.iter() {
+        if from <= parse_version(version)? {
+            transform(deps.branch(), &msg)?;
+        }
+    }
+
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    Ok(Response::new().add_attributes(vec![
+        Attribute::new("action", "migrate"),
+        Attribute::new("from_version", stored.version),
+        Attribute::new("to_version", CONTRACT_VERSION),
+    ]))
+}
+
+/// ## Description
+/// Returns the major, minor and patch numbers of a version, which compare in release order
+/// Otherwise returns [`ContractError`] if the version is not of the form `major.minor.patch`
+/// ## Params
+/// * **version** is an object of type [`str`]
+fn parse_version(version: &str) -> Result<(u64, u64, u64), ContractError> {
+    let numbers = version
+        .split('.')
+        .map(|number| number.parse::<u64>())
+        .collect::<Result<Vec<u64>, _>>()
+        .map_err(|_| ContractError::CannotMigrateVersion {})?;
+
+    match numbers[..] {
+        [major, minor, patch] => Ok((major, minor, patch)),
+        _ => Err(ContractError::CannotMigrateVersion {}),
+    }
+}
+
+/// ## Description
+/// Runs the operations of a batch in order on behalf of the sender, each with its own funds,
+/// and merges their responses. Any failing operation fails the whole batch.
+/// Returns [`ContractError`] if an operation cannot be batched, funds are given to an
+/// operation which takes none, or the funds sent are not exactly the sum of the funds of
+/// every operation
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`]
+///
+/// * **env** is an object of type [`Env`]
+///
+/// * **info** is an object of type [`MessageInfo`]
+///
+/// * **msgs** is a vector of [`BatchMsg`]
+fn execute_batch(
+    mut deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    msgs: Vec<BatchMsg>,
+) -> Result<Response, ContractError> {
+    if msgs.is_empty() {
+        return Err(ContractError::InvalidBatchMsg {});
+    }
+
+    for batch_msg in msgs.iter() {
+        match batch_msg.msg {
+            ExecuteMsg::CancelBorrowRequest { .. } | ExecuteMsg::PruneExpired { .. } => {
+                validate_no_funds(&batch_msg.funds)?
+            }
+            ExecuteMsg::MakeBorrowRequestNativeToken { .. }
+            | ExecuteMsg::DepositNativeToken {}
+            | ExecuteMsg::BorrowNativeToken { .. }
+            | ExecuteMsg::RepayNativeToken { .. }
+            | ExecuteMsg::LiquidateNativeToken { .. } => (),
+            _ => return Err(ContractError::InvalidBatchMsg {}),
+        }
+    }
+
+    if sum_funds(msgs.iter().flat_map(|batch_msg| batch_msg.funds.iter()))?
+        != sum_funds(info.funds.iter())?
+    {
+        return Err(ContractError::InvalidFunds {});
+    }
+
+    let mut res = Response::new().add_attribute("action", "batch");
+    for BatchMsg { msg, funds } in msgs {
+        let info = MessageInfo {
+            sender: info.sender.clone(),
+            funds,
+        };
+        let batch_res = execute(deps.branch(), env.clone(), info, msg)?;
+        res.messages.extend(batch_res.messages);
+        res.attributes.extend(batch_res.attributes);
+        res.events.extend(batch_res.events);
+    }
+
+    Ok(res)
+}
+
+/// Returns the total amount of each denom in the given coins
+fn sum_funds<'a>(
+    funds: impl Iterator<Item = &'a Coin>,
+) -> Result<BTreeMap<String, Uint128>, ContractError> {
+    let mut total: BTreeMap<String, Uint128> = BTreeMap::new();
+    for coin in funds {
+        let amount = total.entry(coin.denom.clone()).or_default();
+        *amount = amount.checked_add(coin.amount)?;
+    }
+
+    Ok(total)
+}
+
+/// Returns [`ContractError`] if the sender is neither the IBC hook set by the owner nor,
+/// without one, the borrower, if the denom trace does not hash to the denom of the tokens,
+/// or if it does not start with an allowed transfer channel
+fn validate_ibc_hook(
+    deps: Deps,
+    sender: &CanonicalAddr,
+    borrower: &CanonicalAddr,
+    denom_trace: &str,
+    denom: &str,
+) -> Result<(), ContractError> {
+    let ibc_hook_config = load_ibc_hook_config(deps.storage)?;
+    if ibc_hook_config.hook.as_ref().unwrap_or(borrower) != sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if denom != to_ibc_denom(denom_trace)? {
+        return Err(ContractError::InvalidDenomTrace {});
+    }
+
+    let transfer_channel = denom_trace
+        .splitn(3, '/')
+        .take(2)
+        .collect::<Vec<&str>>()
+        .join("/");
+    if !ibc_hook_config
+        .transfer_channels
+        .contains(&transfer_channel)
+    {
+        return Err(ContractError::TransferChannelNotAllowed {});
+    }
+
+    Ok(())
+}
+
+/// Returns [`ContractError::InvalidFunds`] if funds are sent along with an operation which
+/// takes none, as they would be left in the contract
+fn validate_no_funds(funds: &[Coin]) -> Result<(), ContractError> {
+    if !funds.is_empty() {
+        return Err(ContractError::InvalidFunds {});
+    }
+
+    Ok(())
 }
 
-fn validate_funds(funds: &Vec<Coin>) -> Result<(), ContractError> {
+fn validate_funds(funds: &[Coin]) -> Result<(), ContractError> {
     if funds.len() != 1 {
         return Err(ContractError::InvalidFunds {});
     }
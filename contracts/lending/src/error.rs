@@ -15,9 +15,105 @@ pub enum ContractError {
     #[error("Invalid funds")]
     InvalidFunds {},
 
+    #[error("Invalid batch message")]
+    InvalidBatchMsg {},
+
+    #[error("Invalid migrate message")]
+    InvalidMigrateMsg {},
+
+    #[error("Cannot migrate from a different contract")]
+    CannotMigrateContract {},
+
+    #[error("Cannot migrate from a newer or unknown version")]
+    CannotMigrateVersion {},
+
     #[error("Invalid amount")]
     InvalidAmount {},
 
     #[error("Invalid period")]
     InvalidPeriod {},
+
+    #[error("Invalid expiration")]
+    InvalidExpiration {},
+
+    #[error("Invalid height")]
+    InvalidHeight {},
+
+    #[error("Invalid denom trace")]
+    InvalidDenomTrace {},
+
+    #[error("Borrow request not found")]
+    BorrowRequestNotFound {},
+
+    #[error("Borrow request already borrowed")]
+    BorrowRequestAlreadyBorrowed {},
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("Invalid address")]
+    InvalidAddress {},
+
+    #[error("Invalid asset")]
+    InvalidAsset {},
+
+    #[error("Invalid reserve config")]
+    InvalidReserveConfig {},
+
+    #[error("Borrower does not meet the requirements of the reserve")]
+    BorrowerRequirementsNotMet {},
+
+    #[error("Reserve already exists")]
+    ReserveAlreadyExists {},
+
+    #[error("Reserve not found")]
+    ReserveNotFound {},
+
+    #[error("Loan not found")]
+    LoanNotFound {},
+
+    #[error("Insufficient liquidity")]
+    InsufficientLiquidity {},
+
+    #[error("Insufficient collateral")]
+    InsufficientCollateral {},
+
+    #[error("Loan is not liquidatable")]
+    NotLiquidatable {},
+
+    #[error("Flash loan in progress")]
+    FlashLoanInProgress {},
+
+    #[error("Flash loan not returned")]
+    FlashLoanNotReturned {},
+
+    #[error("Invalid IBC channel order")]
+    InvalidIbcOrder {},
+
+    #[error("Invalid IBC channel version")]
+    InvalidIbcVersion {},
+
+    #[error("Invalid IBC packet")]
+    InvalidIbcPacket {},
+
+    #[error("IBC channel not found")]
+    ChannelNotFound {},
+
+    #[error("IBC channel is still open")]
+    ChannelStillOpen {},
+
+    #[error("IBC transfer channel not allowed")]
+    TransferChannelNotAllowed {},
+
+    #[error("Nothing to claim")]
+    NothingToClaim {},
+}
+
+impl From<ContractError> for StdError {
+    fn from(err: ContractError) -> Self {
+        match err {
+            ContractError::Std(err) => err,
+            err => StdError::generic_err(err.to_string()),
+        }
+    }
 }
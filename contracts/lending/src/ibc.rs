@@ -0,0 +1,278 @@
+#[cfg(not(feature = "library"))]
+use cosmwasm_std::entry_point;
+use cosmwasm_std::{
+    from_binary, to_binary, Attribute, Deps, DepsMut, Env, IbcBasicResponse, IbcChannel,
+    IbcChannelCloseMsg, IbcChannelConnectMsg, IbcChannelOpenMsg, IbcOrder, IbcPacket,
+    IbcPacketAckMsg, IbcPacketReceiveMsg, IbcPacketTimeoutMsg, IbcReceiveResponse, Storage,
+};
+
+use crate::{
+    commands,
+    error::ContractError,
+    state::{
+        add_request_collateral_escrowed, load_borrow_request, load_state,
+        may_load_ibc_counterparty, may_load_pending_release, remove_ibc_channel,
+        remove_pending_release, remove_remote_collateral, store_borrow_request,
+        store_borrow_request_expiry, store_borrow_request_id_to_addr, store_borrow_request_indexes,
+        store_ibc_channel, RemoteCollateral, StorageCache,
+    },
+    utils::{addr_canonicalize, to_native_token},
+};
+
+use services::lending::{LendingAck, LendingPacket, IBC_VERSION};
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_open(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelOpenMsg,
+) -> Result<(), ContractError> {
+    validate_channel(deps.as_ref(), msg.channel(), msg.counterparty_version())
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_connect(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelConnectMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let channel = msg.channel();
+    validate_channel(deps.as_ref(), channel, msg.counterparty_version())?;
+    store_ibc_channel(
+        deps.storage,
+        &channel.endpoint.channel_id,
+        &channel.connection_id,
+    )?;
+
+    Ok(IbcBasicResponse::new().add_attributes(vec![
+        Attribute::new("action", "ibc_channel_connect"),
+        Attribute::new("channel_id", &channel.endpoint.channel_id),
+    ]))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_channel_close(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcChannelCloseMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let channel = msg.channel();
+    remove_ibc_channel(deps.storage, &channel.endpoint.channel_id);
+
+    Ok(IbcBasicResponse::new().add_attributes(vec![
+        Attribute::new("action", "ibc_channel_close"),
+        Attribute::new("channel_id", &channel.endpoint.channel_id),
+    ]))
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_receive(
+    deps: DepsMut,
+    env: Env,
+    msg: IbcPacketReceiveMsg,
+) -> Result<IbcReceiveResponse, ContractError> {
+    // Failures are acknowledged rather than returned, so that the counterpart contract
+    // can unlock the collateral. As the transaction then succeeds, writes are cached and
+    // only committed if the borrow request is made
+    let mut cache = StorageCache::new(deps.storage);
+    let cached = DepsMut {
+        storage: &mut cache,
+        api: deps.api,
+        querier: deps.querier,
+    };
+    match receive_borrow_request(cached, env, &msg.packet) {
+        Ok(res) => {
+            cache.commit();
+            Ok(res)
+        }
+        Err(err) => Ok(IbcReceiveResponse::new()
+            .set_ack(to_binary(&LendingAck::Error(err.to_string()))?)
+            .add_attributes(vec![
+                Attribute::new("action", "make_borrow_request"),
+                Attribute::new("error", err.to_string()),
+            ])),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_ack(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcPacketAckMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let id = release_collateral_id(&msg.original_packet)?;
+    let ack = from_binary(&msg.acknowledgement.data)
+        .unwrap_or_else(|_| LendingAck::Error("Invalid acknowledgement".to_string()));
+
+    match ack {
+        LendingAck::Result(_) => {
+            remove_pending_release(deps.storage, id);
+            remove_remote_collateral(deps.storage, id);
+
+            Ok(IbcBasicResponse::new().add_attributes(vec![
+                Attribute::new("action", "release_collateral"),
+                Attribute::new("id", id.to_string()),
+            ]))
+        }
+        LendingAck::Error(err) => {
+            restore_borrow_request(deps.storage, id)?;
+
+            Ok(IbcBasicResponse::new().add_attributes(vec![
+                Attribute::new("action", "restore_borrow_request"),
+                Attribute::new("id", id.to_string()),
+                Attribute::new("error", err),
+            ]))
+        }
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn ibc_packet_timeout(
+    deps: DepsMut,
+    _env: Env,
+    msg: IbcPacketTimeoutMsg,
+) -> Result<IbcBasicResponse, ContractError> {
+    let id = release_collateral_id(&msg.packet)?;
+    restore_borrow_request(deps.storage, id)?;
+
+    Ok(IbcBasicResponse::new().add_attributes(vec![
+        Attribute::new("action", "restore_borrow_request"),
+        Attribute::new("id", id.to_string()),
+    ]))
+}
+
+/// ## Description
+/// Make borrow request against the collateral locked by the counterpart contract, which is
+/// recorded in its denom prefixed by the port and channel it came over
+/// Returns [`IbcReceiveResponse`] acknowledging the id of the borrow request if operation was
+/// successful
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`]
+///
+/// * **env** is an object of type [`Env`]
+///
+/// * **packet** is an object of type [`IbcPacket`]
+fn receive_borrow_request(
+    mut deps: DepsMut,
+    env: Env,
+    packet: &IbcPacket,
+) -> Result<IbcReceiveResponse, ContractError> {
+    let (borrower, refund_address, collateral, period, lender, expires_at) =
+        match from_binary(&packet.data) {
+            Ok(LendingPacket::MakeBorrowRequest {
+                borrower,
+                refund_address,
+                collateral,
+                period,
+                lender,
+                expires_at,
+            }) => (
+                borrower,
+                refund_address,
+                collateral,
+                period,
+                lender,
+                expires_at,
+            ),
+            _ => return Err(ContractError::InvalidIbcPacket {}),
+        };
+    if refund_address.is_empty() {
+        return Err(ContractError::InvalidIbcPacket {});
+    }
+
+    let borrower = addr_canonicalize(deps.api, &borrower)?;
+    let lender = lender
+        .map(|lender| addr_canonicalize(deps.api, &lender))
+        .transpose()?;
+    let asset = to_native_token(
+        format!(
+            "{}/{}/{}",
+            packet.dest.port_id, packet.dest.channel_id, collateral.denom
+        ),
+        collateral.amount,
+    )?;
+
+    let res = commands::make_borrow_request(
+        deps.branch(),
+        env,
+        &borrower,
+        asset,
+        period,
+        lender,
+        expires_at,
+        Some(RemoteCollateral {
+            channel_id: packet.dest.channel_id.clone(),
+            refund_address,
+            collateral,
+        }),
+    )?;
+    let id = load_state(deps.storage)?.request_count;
+
+    Ok(IbcReceiveResponse::new()
+        .set_ack(to_binary(&LendingAck::Result(to_binary(&id)?))?)
+        .add_attributes(res.attributes)
+        .add_attribute("channel_id", &packet.dest.channel_id)
+        .add_events(res.events))
+}
+
+/// ## Description
+/// Validates that the channel is unordered, of [`IBC_VERSION`], and to the counterpart
+/// contract allowed over its connection
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **deps** is an object of type [`Deps`]
+///
+/// * **channel** is an object of type [`IbcChannel`]
+///
+/// * **counterparty_version** is an [`Option`] of [`str`]
+fn validate_channel(
+    deps: Deps,
+    channel: &IbcChannel,
+    counterparty_version: Option<&str>,
+) -> Result<(), ContractError> {
+    if channel.order != IbcOrder::Unordered {
+        return Err(ContractError::InvalidIbcOrder {});
+    }
+
+    if channel.version != IBC_VERSION
+        || matches!(counterparty_version, Some(version) if version != IBC_VERSION)
+    {
+        return Err(ContractError::InvalidIbcVersion {});
+    }
+
+    match may_load_ibc_counterparty(deps.storage, &channel.connection_id)? {
+        Some(port_id) if port_id == channel.counterparty_endpoint.port_id => Ok(()),
+        _ => Err(ContractError::Unauthorized {}),
+    }
+}
+
+/// Returns the borrow request id of a packet releasing remote collateral
+fn release_collateral_id(packet: &IbcPacket) -> Result<u64, ContractError> {
+    match from_binary(&packet.data) {
+        Ok(LendingPacket::ReleaseCollateral { id, .. }) => Ok(id),
+        _ => Err(ContractError::InvalidIbcPacket {}),
+    }
+}
+
+/// Puts back a borrow request whose remote collateral failed to be released
+fn restore_borrow_request(storage: &mut dyn Storage, id: u64) -> Result<(), ContractError> {
+    let pending =
+        may_load_pending_release(storage, id)?.ok_or(ContractError::BorrowRequestNotFound {})?;
+    let borrower = pending.borrower;
+    let restored = pending.borrow_request;
+
+    store_borrow_request_id_to_addr(storage, id, &borrower)?;
+    if let Some(expires_at) = &restored.expires_at {
+        store_borrow_request_expiry(storage, expires_at, id)?;
+    }
+    store_borrow_request_indexes(storage, &restored)?;
+    add_request_collateral_escrowed(storage, id, &restored.collateral)?;
+    let mut borrow_request = load_borrow_request(storage, &borrower)?;
+    borrow_request.push(restored);
+    borrow_request.sort_by_key(|br| br.id);
+    store_borrow_request(storage, &borrower, &borrow_request)?;
+    remove_pending_release(storage, id);
+
+    Ok(())
+}
@@ -0,0 +1,307 @@
+use std::convert::TryFrom;
+
+use cosmwasm_std::{
+    Decimal256, Fraction, OverflowError, OverflowOperation, StdError, Uint128, Uint256, Uint512,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ContractError;
+
+/// Number of terms of the Taylor series of `e^x` used for continuous compounding
+const CONTINUOUS_TERMS: u64 = 20u64;
+
+/// ## Description
+/// This enum describes how interest accrues on a principal over a number of blocks.
+/// Every model rounds up, so that rounding is always in favor of the lender.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum InterestModel {
+    /// Interest accrues linearly on the principal, `1 + r * n`
+    Simple,
+    /// Interest is added to the principal every block, `(1 + r)^n`
+    Compound,
+    /// Interest is compounded continuously, `e^(r * n)`
+    Continuous,
+}
+
+/// ## Description
+/// This enum describes how the per-block interest rate is derived from the utilization
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RateCurve {
+    /// The same per-block rate regardless of utilization
+    Fixed { rate: Decimal256 },
+    /// A rate rising by `slope1` up to the optimal utilization and by `slope2` beyond it
+    Kinked {
+        base_rate: Decimal256,
+        slope1: Decimal256,
+        slope2: Decimal256,
+        optimal_utilization: Decimal256,
+    },
+}
+
+impl RateCurve {
+    /// ## Description
+    /// Returns the per-block interest rate at the given utilization
+    /// Otherwise returns [`ContractError`]
+    /// ## Params
+    /// * **utilization** is an object of type [`Decimal256`] between zero and one
+    pub fn rate(&self, utilization: Decimal256) -> Result<Decimal256, ContractError> {
+        match self {
+            RateCurve::Fixed { rate } => Ok(*rate),
+            RateCurve::Kinked {
+                base_rate,
+                slope1,
+                slope2,
+                optimal_utilization,
+            } => {
+                if utilization <= *optimal_utilization {
+                    add(
+                        *base_rate,
+                        from_atomics(checked_multiply_ratio(
+                            slope1.numerator(),
+                            utilization.numerator(),
+                            optimal_utilization.numerator(),
+                        )?),
+                    )
+                } else {
+                    add(
+                        add(*base_rate, *slope1)?,
+                        from_atomics(checked_multiply_ratio(
+                            slope2.numerator(),
+                            utilization
+                                .numerator()
+                                .checked_sub(optimal_utilization.numerator())?,
+                            Decimal256::one()
+                                .numerator()
+                                .checked_sub(optimal_utilization.numerator())?,
+                        )?),
+                    )
+                }
+            }
+        }
+    }
+}
+
+/// ## Description
+/// Returns the factor a principal grows by over the given number of blocks
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **model** is an object of type [`InterestModel`]
+///
+/// * **rate** is an object of type [`Decimal256`], the per-block interest rate
+///
+/// * **blocks** is [`u64`]
+pub fn growth_factor(
+    model: InterestModel,
+    rate: Decimal256,
+    blocks: u64,
+) -> Result<Decimal256, ContractError> {
+    match model {
+        InterestModel::Simple => add(Decimal256::one(), mul_int(rate, blocks)?),
+        InterestModel::Compound => pow_ceil(add(Decimal256::one(), rate)?, blocks),
+        InterestModel::Continuous => exp_ceil(mul_int(rate, blocks)?),
+    }
+}
+
+/// ## Description
+/// Returns the interest accrued on the principal over the given number of blocks
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **model** is an object of type [`InterestModel`]
+///
+/// * **principal** is an object of type [`Uint128`]
+///
+/// * **rate** is an object of type [`Decimal256`], the per-block interest rate
+///
+/// * **blocks** is [`u64`]
+pub fn accrued_interest(
+    model: InterestModel,
+    principal: Uint128,
+    rate: Decimal256,
+    blocks: u64,
+) -> Result<Uint128, ContractError> {
+    let factor = growth_factor(model, rate, blocks)?;
+    let interest = mul_ceil(
+        Uint256::from(principal),
+        from_atomics(factor.numerator() - Decimal256::one().numerator()),
+    )?;
+
+    Uint128::try_from(interest)
+        .map_err(|_| OverflowError::new(OverflowOperation::Mul, principal, factor).into())
+}
+
+/// ## Description
+/// Returns `amount * factor` rounded down
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **amount** is an object of type [`Uint256`]
+///
+/// * **factor** is an object of type [`Decimal256`]
+pub fn mul_floor(amount: Uint256, factor: Decimal256) -> Result<Uint256, ContractError> {
+    let product = amount.full_mul(factor.numerator()) / Uint512::from(factor.denominator());
+
+    Uint256::try_from(product)
+        .map_err(|_| OverflowError::new(OverflowOperation::Mul, amount, factor).into())
+}
+
+/// ## Description
+/// Returns `amount * factor` rounded up
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **amount** is an object of type [`Uint256`]
+///
+/// * **factor** is an object of type [`Decimal256`]
+pub fn mul_ceil(amount: Uint256, factor: Decimal256) -> Result<Uint256, ContractError> {
+    let product = ceil_div_512(
+        amount.full_mul(factor.numerator()),
+        Uint512::from(factor.denominator()),
+    );
+
+    Uint256::try_from(product)
+        .map_err(|_| OverflowError::new(OverflowOperation::Mul, amount, factor).into())
+}
+
+/// ## Description
+/// Returns `amount / divisor` rounded down
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **amount** is an object of type [`Uint256`]
+///
+/// * **divisor** is an object of type [`Decimal256`], which must not be zero
+pub fn div_floor(amount: Uint256, divisor: Decimal256) -> Result<Uint256, ContractError> {
+    let quotient = amount.full_mul(divisor.denominator()) / Uint512::from(divisor.numerator());
+
+    Uint256::try_from(quotient)
+        .map_err(|_| OverflowError::new(OverflowOperation::Mul, amount, divisor).into())
+}
+
+/// ## Description
+/// Returns `amount / divisor` rounded up
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **amount** is an object of type [`Uint256`]
+///
+/// * **divisor** is an object of type [`Decimal256`], which must not be zero
+pub fn div_ceil(amount: Uint256, divisor: Decimal256) -> Result<Uint256, ContractError> {
+    let quotient = ceil_div_512(
+        amount.full_mul(divisor.denominator()),
+        Uint512::from(divisor.numerator()),
+    );
+
+    Uint256::try_from(quotient)
+        .map_err(|_| OverflowError::new(OverflowOperation::Mul, amount, divisor).into())
+}
+
+/// ## Description
+/// Returns `a * b` rounded up
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **a** is an object of type [`Decimal256`]
+///
+/// * **b** is an object of type [`Decimal256`]
+pub fn mul_dec_ceil(a: Decimal256, b: Decimal256) -> Result<Decimal256, ContractError> {
+    Ok(from_atomics(mul_ceil(a.numerator(), b)?))
+}
+
+/// Returns `a + b`
+fn add(a: Decimal256, b: Decimal256) -> Result<Decimal256, ContractError> {
+    Ok(from_atomics(a.numerator().checked_add(b.numerator())?))
+}
+
+/// Returns `a * numerator / denominator` rounded down, or an error instead of panicking on
+/// a zero denominator or an overflow
+fn checked_multiply_ratio(
+    a: Uint256,
+    numerator: Uint256,
+    denominator: Uint256,
+) -> Result<Uint256, ContractError> {
+    let product = a
+        .full_mul(numerator)
+        .checked_div(Uint512::from(denominator))
+        .map_err(StdError::from)?;
+
+    Uint256::try_from(product)
+        .map_err(|_| OverflowError::new(OverflowOperation::Mul, a, numerator).into())
+}
+
+/// Returns `a * n`, which is exact
+fn mul_int(a: Decimal256, n: u64) -> Result<Decimal256, ContractError> {
+    Ok(from_atomics(a.numerator().checked_mul(Uint256::from(n))?))
+}
+
+/// Returns `base^exp` by repeated squaring, rounding up every multiplication
+fn pow_ceil(base: Decimal256, exp: u64) -> Result<Decimal256, ContractError> {
+    let mut result = Decimal256::one();
+    let mut base = base;
+    let mut exp = exp;
+
+    while exp > 0u64 {
+        if exp & 1u64 == 1u64 {
+            result = mul_dec_ceil(result, base)?;
+        }
+        exp >>= 1;
+        if exp > 0u64 {
+            base = mul_dec_ceil(base, base)?;
+        }
+    }
+
+    Ok(result)
+}
+
+/// Returns `e^x` rounded up
+///
+/// `x` is halved until it is at most one so that the Taylor series converges within
+/// [`CONTINUOUS_TERMS`] terms, and the result is squared back up the same number of times.
+fn exp_ceil(x: Decimal256) -> Result<Decimal256, ContractError> {
+    let mut x = x;
+    let mut squarings = 0u32;
+    while x > Decimal256::one() {
+        x = from_atomics(ceil_div_256(x.numerator(), Uint256::from(2u64)));
+        squarings += 1;
+    }
+
+    let mut sum = Decimal256::one();
+    let mut term = Decimal256::one();
+    for k in 1u64..=CONTINUOUS_TERMS {
+        term = from_atomics(ceil_div_256(
+            mul_dec_ceil(term, x)?.numerator(),
+            Uint256::from(k),
+        ));
+        if term.is_zero() {
+            break;
+        }
+        sum = add(sum, term)?;
+    }
+
+    for _ in 0..squarings {
+        sum = mul_dec_ceil(sum, sum)?;
+    }
+
+    Ok(sum)
+}
+
+/// Returns `numerator / denominator` rounded up
+fn ceil_div_512(numerator: Uint512, denominator: Uint512) -> Uint512 {
+    let quotient = numerator / denominator;
+    if (numerator % denominator).is_zero() {
+        quotient
+    } else {
+        quotient + Uint512::from(1u64)
+    }
+}
+
+/// Returns `numerator / denominator` rounded up
+fn ceil_div_256(numerator: Uint256, denominator: Uint256) -> Uint256 {
+    Uint256::try_from(ceil_div_512(
+        Uint512::from(numerator),
+        Uint512::from(denominator),
+    ))
+    .unwrap()
+}
+
+/// Returns a [`Decimal256`] whose 18 fractional digits are stored in `atomics`
+fn from_atomics(atomics: Uint256) -> Decimal256 {
+    Decimal256::from_ratio(atomics, Decimal256::one().numerator())
+}
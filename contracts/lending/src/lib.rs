@@ -1,7 +1,12 @@
 pub mod commands;
 pub mod contract;
 mod error;
+pub mod ibc;
+pub mod interest;
+mod migration;
+pub mod pool;
 pub mod queries;
+pub mod schema;
 pub mod state;
 pub mod utils;
 
@@ -0,0 +1,127 @@
+use astroport::asset::Asset;
+use cosmwasm_std::{CanonicalAddr, DepsMut, Order, StdResult};
+use cw0::Duration;
+use cw_storage_plus::{Item, Map};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::ContractError,
+    state::{
+        add_collateral_escrowed, store_borrow_request, store_borrow_request_id_to_addr,
+        store_borrow_request_indexes, store_config, store_state, BorrowRequest, Config, State,
+    },
+    utils::addr_canonicalize,
+};
+
+use services::lending::MigrateMsg;
+
+/// A state transform upgrading the storage of one version to the next
+type Migration = fn(DepsMut, &MigrateMsg) -> Result<(), ContractError>;
+
+/// ## Description
+/// State transforms in ascending order of the version each of them upgrades from
+pub const MIGRATIONS: &[(&str, Migration)] = &[("0.1.0", migrate_from_0_1_0)];
+
+/// ## Description
+/// Stores a struct of type [`State`] as of 0.1.0
+static STATE_0_1_0: Item<State0_1_0> = Item::new("state");
+
+/// ## Description
+/// A map which stores borrow requests as of 0.1.0 with
+/// [`CanonicalAddr`] type as key and [`Vec<BorrowRequest0_1_0>`] type as value
+static BORROW_REQUEST_0_1_0: Map<&[u8], Vec<BorrowRequest0_1_0>> = Map::new("borrow_request");
+
+/// ## Description
+/// A map which stores id and borrower address of each borrow request as of 0.1.0 with
+/// decimal id as key and [`CanonicalAddr`] type as value
+static BORROW_REQUEST_ID_TO_ADDR_0_1_0: Map<&str, CanonicalAddr> =
+    Map::new("borrow_request_id_to_addr");
+
+/// ## Description
+/// This structure describes state of lending contract as of 0.1.0
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+struct State0_1_0 {
+    request_count: u64,
+    response_count: u64,
+}
+
+/// ## Description
+/// This structure describes borrow request from borrower as of 0.1.0
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+struct BorrowRequest0_1_0 {
+    id: u64,
+    collateral: Asset,
+    period: u64,
+    borrowed_from: u64,
+    borrowed_at: u64,
+}
+
+/// ## Description
+/// Upgrades the storage of 0.1.0, which has neither config nor pooled reserves. The owner
+/// and share token code id are taken from the message, periods become block durations,
+/// the id map is rekeyed by integer id and borrow requests are indexed and counted
+/// in statistics
+/// Returns [`ContractError`] if the owner or share token code id is not given
+/// ## Params
+/// * **deps** is an object of type [`DepsMut`]
+///
+/// * **msg** is an object of type [`MigrateMsg`]
+fn migrate_from_0_1_0(deps: DepsMut, msg: &MigrateMsg) -> Result<(), ContractError> {
+    let (owner, token_code_id) = match (&msg.owner, msg.token_code_id) {
+        (Some(owner), Some(token_code_id)) => (owner, token_code_id),
+        _ => return Err(ContractError::InvalidMigrateMsg {}),
+    };
+    store_config(
+        deps.storage,
+        &Config {
+            owner: addr_canonicalize(deps.api, owner)?,
+            token_code_id,
+        },
+    )?;
+
+    let state = STATE_0_1_0.load(deps.storage)?;
+    store_state(
+        deps.storage,
+        &State {
+            request_count: state.request_count,
+            response_count: state.response_count,
+            loan_count: 0u64,
+        },
+    )?;
+
+    let ids = BORROW_REQUEST_ID_TO_ADDR_0_1_0
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect::<Vec<Vec<u8>>>();
+    for id in ids.iter() {
+        BORROW_REQUEST_ID_TO_ADDR_0_1_0.remove(deps.storage, &String::from_utf8_lossy(id));
+    }
+
+    let borrow_requests = BORROW_REQUEST_0_1_0
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect::<StdResult<Vec<(Vec<u8>, Vec<BorrowRequest0_1_0>)>>>()?;
+    for (borrower, legacy) in borrow_requests {
+        let borrower = CanonicalAddr::from(borrower);
+        let borrow_request = legacy
+            .into_iter()
+            .map(|br| BorrowRequest {
+                id: br.id,
+                collateral: br.collateral,
+                period: Duration::Height(br.period),
+                lender: None,
+                expires_at: None,
+                borrowed_from: br.borrowed_from,
+                borrowed_at: br.borrowed_at,
+                borrowed_at_time: 0u64,
+            })
+            .collect::<Vec<BorrowRequest>>();
+
+        for br in borrow_request.iter() {
+            store_borrow_request_id_to_addr(deps.storage, br.id, &borrower)?;
+            store_borrow_request_indexes(deps.storage, br)?;
+            add_collateral_escrowed(deps.storage, &br.collateral)?;
+        }
+        store_borrow_request(deps.storage, &borrower, &borrow_request)?;
+    }
+
+    Ok(())
+}
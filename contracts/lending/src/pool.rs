@@ -0,0 +1,334 @@
+use std::convert::TryFrom;
+
+use cosmwasm_std::{Decimal256, OverflowError, OverflowOperation, Uint128, Uint256};
+
+use crate::{
+    error::ContractError,
+    interest::{
+        div_ceil, div_floor, growth_factor, mul_ceil, mul_dec_ceil, mul_floor, InterestModel,
+    },
+    state::{Loan, Reserve},
+};
+
+/// ## Description
+/// Accrues interest of a reserve up to the given block height by growing its borrow index
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **reserve** is an object of type [`Reserve`]
+///
+/// * **height** is [`u64`]
+pub fn accrue_interest(reserve: &mut Reserve, height: u64) -> Result<(), ContractError> {
+    if height <= reserve.last_accrued_at {
+        return Ok(());
+    }
+
+    let rate = borrow_rate(reserve)?;
+    let blocks = height - reserve.last_accrued_at;
+    let factor = growth_factor(InterestModel::Compound, rate, blocks)?;
+
+    reserve.borrow_index = mul_dec_ceil(reserve.borrow_index, factor)?;
+    reserve.last_accrued_at = height;
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns the current per-block borrow rate of a reserve
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **reserve** is an object of type [`Reserve`]
+pub fn borrow_rate(reserve: &Reserve) -> Result<Decimal256, ContractError> {
+    reserve.rate_curve.rate(utilization(reserve)?)
+}
+
+/// ## Description
+/// Returns the ratio of total debt to liquidity plus total debt of a reserve
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **reserve** is an object of type [`Reserve`]
+pub fn utilization(reserve: &Reserve) -> Result<Decimal256, ContractError> {
+    let total_debt = total_debt(reserve)?;
+    let total_value = total_debt.checked_add(reserve.liquidity)?;
+    if total_value.is_zero() {
+        return Ok(Decimal256::zero());
+    }
+
+    Ok(Decimal256::from_ratio(total_debt, total_value))
+}
+
+/// ## Description
+/// Returns the amount owed to a reserve by all borrowers, rounded down
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **reserve** is an object of type [`Reserve`]
+pub fn total_debt(reserve: &Reserve) -> Result<Uint128, ContractError> {
+    to_uint128(mul_floor(
+        Uint256::from(reserve.total_scaled_debt),
+        reserve.borrow_index,
+    )?)
+}
+
+/// ## Description
+/// Returns the amount owed by a loan, rounded up
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **reserve** is an object of type [`Reserve`]
+///
+/// * **loan** is an object of type [`Loan`]
+pub fn loan_debt(reserve: &Reserve, loan: &Loan) -> Result<Uint128, ContractError> {
+    scaled_debt_value(reserve, loan.scaled_debt)
+}
+
+/// ## Description
+/// Returns the amount owed for the given scaled debt of a reserve, rounded up
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **reserve** is an object of type [`Reserve`]
+///
+/// * **scaled_debt** is an object of type [`Uint128`]
+pub fn scaled_debt_value(
+    reserve: &Reserve,
+    scaled_debt: Uint128,
+) -> Result<Uint128, ContractError> {
+    to_uint128(mul_ceil(Uint256::from(scaled_debt), reserve.borrow_index)?)
+}
+
+/// ## Description
+/// Returns the interest and principal parts of a repayment of a loan, paying the interest
+/// accrued on top of the principal first
+/// ## Params
+/// * **loan** is an object of type [`Loan`]
+///
+/// * **debt** is an object of type [`Uint128`], the amount owed by the loan
+///
+/// * **repaid** is an object of type [`Uint128`]
+pub fn repayment_parts(loan: &Loan, debt: Uint128, repaid: Uint128) -> (Uint128, Uint128) {
+    let interest = debt.saturating_sub(loan.principal).min(repaid);
+    let principal = repaid.saturating_sub(interest).min(loan.principal);
+
+    (interest, principal)
+}
+
+/// ## Description
+/// Returns the scaled debt a borrowed amount adds to a reserve, rounded up
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **reserve** is an object of type [`Reserve`]
+///
+/// * **amount** is an object of type [`Uint128`]
+pub fn scale_debt_up(reserve: &Reserve, amount: Uint128) -> Result<Uint128, ContractError> {
+    to_uint128(div_ceil(Uint256::from(amount), reserve.borrow_index)?)
+}
+
+/// ## Description
+/// Returns the scaled debt a repaid amount removes from a reserve, rounded down
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **reserve** is an object of type [`Reserve`]
+///
+/// * **amount** is an object of type [`Uint128`]
+pub fn scale_debt_down(reserve: &Reserve, amount: Uint128) -> Result<Uint128, ContractError> {
+    to_uint128(div_floor(Uint256::from(amount), reserve.borrow_index)?)
+}
+
+/// ## Description
+/// Returns the shares a deposit of the given amount is worth, rounded down
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **reserve** is an object of type [`Reserve`]
+///
+/// * **amount** is an object of type [`Uint128`]
+pub fn amount_to_shares(reserve: &Reserve, amount: Uint128) -> Result<Uint128, ContractError> {
+    if reserve.total_shares.is_zero() {
+        return Ok(amount);
+    }
+
+    let total_value = total_debt(reserve)?.checked_add(reserve.liquidity)?;
+    if total_value.is_zero() {
+        return Err(ContractError::InsufficientLiquidity {});
+    }
+
+    Ok(amount.multiply_ratio(reserve.total_shares, total_value))
+}
+
+/// ## Description
+/// Returns the amount the given shares are worth, rounded down
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **reserve** is an object of type [`Reserve`]
+///
+/// * **shares** is an object of type [`Uint128`]
+pub fn shares_to_amount(reserve: &Reserve, shares: Uint128) -> Result<Uint128, ContractError> {
+    if reserve.total_shares.is_zero() {
+        return Ok(Uint128::zero());
+    }
+
+    let total_value = total_debt(reserve)?.checked_add(reserve.liquidity)?;
+
+    Ok(shares.multiply_ratio(total_value, reserve.total_shares))
+}
+
+/// ## Description
+/// Returns the value of collateral in units of the reserve asset, rounded down
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **reserve** is an object of type [`Reserve`]
+///
+/// * **amount** is an object of type [`Uint128`]
+pub fn collateral_value(reserve: &Reserve, amount: Uint128) -> Result<Uint128, ContractError> {
+    to_uint128(mul_floor(Uint256::from(amount), reserve.collateral_price)?)
+}
+
+/// ## Description
+/// Returns the collateral worth the given amount of the reserve asset, rounded down
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **reserve** is an object of type [`Reserve`]
+///
+/// * **amount** is an object of type [`Uint128`]
+pub fn amount_to_collateral(reserve: &Reserve, amount: Uint128) -> Result<Uint128, ContractError> {
+    to_uint128(div_floor(Uint256::from(amount), reserve.collateral_price)?)
+}
+
+/// ## Description
+/// Returns true if the debt exceeds the given ratio of the collateral value
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **reserve** is an object of type [`Reserve`]
+///
+/// * **collateral** is an object of type [`Uint128`]
+///
+/// * **debt** is an object of type [`Uint128`]
+///
+/// * **ratio** is an object of type [`Decimal256`]
+pub fn exceeds_ltv(
+    reserve: &Reserve,
+    collateral: Uint128,
+    debt: Uint128,
+    ratio: Decimal256,
+) -> Result<bool, ContractError> {
+    let limit = mul_floor(Uint256::from(collateral_value(reserve, collateral)?), ratio)?;
+
+    Ok(Uint256::from(debt) > limit)
+}
+
+/// ## Description
+/// Returns true if the debt of a loan exceeds the liquidation threshold of its collateral value
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **reserve** is an object of type [`Reserve`]
+///
+/// * **loan** is an object of type [`Loan`]
+///
+/// * **debt** is an object of type [`Uint128`]
+pub fn is_liquidatable(
+    reserve: &Reserve,
+    loan: &Loan,
+    debt: Uint128,
+) -> Result<bool, ContractError> {
+    exceeds_ltv(
+        reserve,
+        loan.collateral.amount,
+        debt,
+        reserve.liquidation_threshold,
+    )
+}
+
+/// ## Description
+/// Returns the bonus paid to liquidators on top of the debt in units of the reserve asset,
+/// rounded down
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **reserve** is an object of type [`Reserve`]
+///
+/// * **debt** is an object of type [`Uint128`]
+pub fn liquidation_bonus(reserve: &Reserve, debt: Uint128) -> Result<Uint128, ContractError> {
+    to_uint128(mul_floor(Uint256::from(debt), reserve.liquidation_bonus)?)
+}
+
+/// ## Description
+/// Returns the ratio of the debt to the collateral value of a loan, or [`None`] if its
+/// collateral is worthless
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **reserve** is an object of type [`Reserve`]
+///
+/// * **loan** is an object of type [`Loan`]
+///
+/// * **debt** is an object of type [`Uint128`]
+pub fn loan_to_value(
+    reserve: &Reserve,
+    loan: &Loan,
+    debt: Uint128,
+) -> Result<Option<Decimal256>, ContractError> {
+    let value = collateral_value(reserve, loan.collateral.amount)?;
+    if value.is_zero() {
+        return Ok(None);
+    }
+
+    Ok(Some(Decimal256::from_ratio(debt, value)))
+}
+
+/// ## Description
+/// Returns the collateral price below which a loan becomes liquidatable, or [`None`] if the
+/// loan has no collateral to liquidate
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **reserve** is an object of type [`Reserve`]
+///
+/// * **loan** is an object of type [`Loan`]
+///
+/// * **debt** is an object of type [`Uint128`]
+pub fn liquidation_price(
+    reserve: &Reserve,
+    loan: &Loan,
+    debt: Uint128,
+) -> Result<Option<Decimal256>, ContractError> {
+    let limit = mul_floor(
+        Uint256::from(loan.collateral.amount),
+        reserve.liquidation_threshold,
+    )?;
+    if limit.is_zero() {
+        return Ok(None);
+    }
+
+    Ok(Some(Decimal256::from_ratio(Uint256::from(debt), limit)))
+}
+
+/// ## Description
+/// Returns the collateral seized from a liquidated loan, which is worth the debt plus
+/// the liquidation bonus and capped at the collateral of the loan
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **reserve** is an object of type [`Reserve`]
+///
+/// * **loan** is an object of type [`Loan`]
+///
+/// * **debt** is an object of type [`Uint128`]
+pub fn seized_collateral(
+    reserve: &Reserve,
+    loan: &Loan,
+    debt: Uint128,
+) -> Result<Uint128, ContractError> {
+    let bonus = liquidation_bonus(reserve, debt)?;
+    let seized = amount_to_collateral(reserve, debt.checked_add(bonus)?)?;
+
+    Ok(seized.min(loan.collateral.amount))
+}
+
+/// ## Description
+/// Returns the fee owed on top of a flash loan of the given amount, rounded up
+/// Otherwise returns [`ContractError`]
+/// ## Params
+/// * **reserve** is an object of type [`Reserve`]
+///
+/// * **amount** is an object of type [`Uint128`]
+pub fn flash_loan_fee(reserve: &Reserve, amount: Uint128) -> Result<Uint128, ContractError> {
+    to_uint128(mul_ceil(Uint256::from(amount), reserve.flash_loan_fee)?)
+}
+
+/// Converts [`Uint256`] into [`Uint128`]
+fn to_uint128(value: Uint256) -> Result<Uint128, ContractError> {
+    Uint128::try_from(value)
+        .map_err(|_| OverflowError::new(OverflowOperation::Mul, value, Uint128::MAX).into())
+}
@@ -1,8 +1,36 @@
-use cosmwasm_std::{Deps, StdResult};
+use astroport::asset::AssetInfo;
+use cosmwasm_std::{BlockInfo, Decimal256, Deps, Env, StdResult, Uint128};
+use cw0::Expiration;
 
-use crate::state::{load_borrow_request, load_borrow_request_id_to_addr, load_state};
+use crate::{
+    error::ContractError,
+    interest::RateCurve,
+    pool::{
+        accrue_interest, borrow_rate, collateral_value, is_liquidatable, liquidation_bonus,
+        liquidation_price, loan_debt, loan_to_value, repayment_parts, seized_collateral,
+        total_debt, utilization,
+    },
+    state::{
+        load_borrow_request, load_borrower_profile, load_state, may_load_borrow_request_id_to_addr,
+        may_load_loan, may_load_reserve, read_asset_stats, read_borrow_request_id_to_addr,
+        read_borrow_requests_by_collateral, read_borrow_requests_by_lender,
+        read_borrow_requests_expiring_before, read_loans_by_debt_ratio,
+        read_remote_collateral_stats, read_reserves, BorrowRequest, Loan, Reserve,
+    },
+    utils::addr_canonicalize,
+};
 
-use services::lending::{BorrowRequestResponse, StateResponse};
+use services::lending::{
+    AssetStatsResponse, BorrowRequestResponse, BorrowerProfileResponse, LoanCursor,
+    LoanHealthResponse, LoanResponse, RemoteCollateralStatsResponse, ReserveConfig,
+    ReserveResponse, SimulateLiquidationResponse, SimulateRepayResponse, StateResponse,
+    StatsResponse,
+};
+
+/// Default number of borrow requests returned by a listing query
+const DEFAULT_LIMIT: u32 = 10u32;
+/// Maximum number of borrow requests returned by a listing query
+const MAX_LIMIT: u32 = 30u32;
 
 /// ## Description
 /// Returns lending contract state in the [`StateResponse`] object
@@ -14,6 +42,7 @@ pub fn query_state(deps: Deps) -> StdResult<StateResponse> {
     let res = StateResponse {
         request_count: state.request_count,
         response_count: state.response_count,
+        loan_count: state.loan_count,
     };
 
     Ok(res)
@@ -24,45 +53,607 @@ pub fn query_state(deps: Deps) -> StdResult<StateResponse> {
 /// ## Params
 /// * **deps** is an object of type [`Deps`]
 ///
+/// * **env** is an object of type [`Env`]
+///
 /// * **id** is [`u64`]
-pub fn query_borrow_request_by_id(deps: Deps, id: u64) -> StdResult<BorrowRequestResponse> {
-    let borrower = load_borrow_request_id_to_addr(deps.storage, id)?;
+pub fn query_borrow_request_by_id(
+    deps: Deps,
+    env: Env,
+    id: u64,
+) -> StdResult<BorrowRequestResponse> {
+    let borrower = may_load_borrow_request_id_to_addr(deps.storage, id)?
+        .ok_or(ContractError::BorrowRequestNotFound {})?;
     let borrow_requests = load_borrow_request(deps.storage, &borrower)?;
-    let borrow_request = borrow_requests.iter().find(|br| br.id == id).unwrap();
+    let borrow_request = borrow_requests
+        .into_iter()
+        .find(|br| br.id == id)
+        .ok_or(ContractError::BorrowRequestNotFound {})?;
 
-    let res = BorrowRequestResponse {
-        id: borrow_request.id,
-        collateral: borrow_request.collateral.clone(),
-        period: borrow_request.period,
-        borrowed_from: borrow_request.borrowed_from,
-        borrowed_at: borrow_request.borrowed_at,
-    };
+    let res = to_borrow_request_response(deps, borrow_request, &env.block)?;
 
     Ok(res)
 }
 
 /// ## Description
-/// Returns a list of borrow requests by addr in the [`Vec<BorrowRequestResponse>`] object
+/// Returns a list of borrow requests by addr in the [`Vec<BorrowRequestResponse>`] object,
+/// leaving out requests directed to a lender unless asked for
 /// ## Params
 /// * **deps** is an object of type [`Deps`]
 ///
+/// * **env** is an object of type [`Env`]
+///
 /// * **borrower** is [`String`]
+///
+/// * **include_directed** is [`bool`]
 pub fn query_borrow_requests_by_addr(
     deps: Deps,
+    env: Env,
     borrower: String,
+    include_directed: bool,
+) -> StdResult<Vec<BorrowRequestResponse>> {
+    let borrower_raw = addr_canonicalize(deps.api, borrower.as_str())?;
+
+    load_borrow_request(deps.storage, &borrower_raw)?
+        .into_iter()
+        .filter(|br| include_directed || br.lender.is_none())
+        .map(|br| to_borrow_request_response(deps, br, &env.block))
+        .collect()
+}
+
+/// ## Description
+/// Returns a page of borrow requests of all borrowers in ascending order of id in the
+/// [`Vec<BorrowRequestResponse>`] object, leaving out requests directed to a lender
+/// unless asked for
+/// ## Params
+/// * **deps** is an object of type [`Deps`]
+///
+/// * **env** is an object of type [`Env`]
+///
+/// * **start_after** is an [`Option`] of [`u64`]
+///
+/// * **limit** is an [`Option`] of [`u32`]
+///
+/// * **include_directed** is [`bool`]
+pub fn query_borrow_requests(
+    deps: Deps,
+    env: Env,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    include_directed: bool,
+) -> StdResult<Vec<BorrowRequestResponse>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let mut res = vec![];
+    for item in read_borrow_request_id_to_addr(deps.storage, start_after) {
+        if res.len() >= limit {
+            break;
+        }
+
+        let (id, borrower) = item?;
+        let borrow_request = load_borrow_request(deps.storage, &borrower)?
+            .into_iter()
+            .find(|br| br.id == id)
+            .ok_or(ContractError::BorrowRequestNotFound {})?;
+        if include_directed || borrow_request.lender.is_none() {
+            res.push(to_borrow_request_response(
+                deps,
+                borrow_request,
+                &env.block,
+            )?);
+        }
+    }
+
+    Ok(res)
+}
+
+/// ## Description
+/// Returns a page of borrow requests against the given collateral in ascending order of id
+/// in the [`Vec<BorrowRequestResponse>`] object. Requests directed to a lender are left out
+/// unless asked for
+/// ## Params
+/// * **deps** is an object of type [`Deps`]
+///
+/// * **env** is an object of type [`Env`]
+///
+/// * **asset_info** is an object of type [`AssetInfo`]
+///
+/// * **start_after** is an [`Option`] of [`u64`]
+///
+/// * **limit** is an [`Option`] of [`u32`]
+///
+/// * **include_directed** is [`bool`]
+pub fn query_borrow_requests_by_collateral(
+    deps: Deps,
+    env: Env,
+    asset_info: AssetInfo,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+    include_directed: bool,
+) -> StdResult<Vec<BorrowRequestResponse>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let mut res: Vec<BorrowRequestResponse> = vec![];
+    for item in read_borrow_requests_by_collateral(deps.storage, &asset_info, start_after) {
+        if res.len() >= limit {
+            break;
+        }
+
+        let id = item?;
+        if let Some(borrow_request) = may_load_borrow_request_by_id(deps, id)? {
+            if include_directed || borrow_request.lender.is_none() {
+                res.push(to_borrow_request_response(
+                    deps,
+                    borrow_request,
+                    &env.block,
+                )?);
+            }
+        }
+    }
+
+    Ok(res)
+}
+
+/// ## Description
+/// Returns a page of borrow requests directed to the given lender in ascending order of id
+/// in the [`Vec<BorrowRequestResponse>`] object. These are open requests only the given
+/// lender may fund, not loans it has made
+/// ## Params
+/// * **deps** is an object of type [`Deps`]
+///
+/// * **env** is an object of type [`Env`]
+///
+/// * **lender** is [`String`]
+///
+/// * **start_after** is an [`Option`] of [`u64`]
+///
+/// * **limit** is an [`Option`] of [`u32`]
+pub fn query_requests_directed_to(
+    deps: Deps,
+    env: Env,
+    lender: String,
+    start_after: Option<u64>,
+    limit: Option<u32>,
+) -> StdResult<Vec<BorrowRequestResponse>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let lender_raw = addr_canonicalize(deps.api, lender.as_str())?;
+
+    read_borrow_requests_by_lender(deps.storage, &lender_raw, start_after, limit)?
+        .into_iter()
+        .map(|id| query_borrow_request_by_id(deps, env.clone(), id))
+        .collect()
+}
+
+/// ## Description
+/// Returns a page of borrow requests expiring before the given expiration, earliest expiry
+/// first, in the [`Vec<BorrowRequestResponse>`] object. Requests expiring at a block height
+/// are listed for a height and requests expiring at a time for a time. The page starts after
+/// the given expiry and id, so it stays in place when the request last returned has been
+/// pruned or cancelled in the meantime
+/// ## Params
+/// * **deps** is an object of type [`Deps`]
+///
+/// * **env** is an object of type [`Env`]
+///
+/// * **expiration** is an object of type [`Expiration`]
+///
+/// * **start_after** is an [`Option`] of expiry height, or time in nanoseconds, and id
+///
+/// * **limit** is an [`Option`] of [`u32`]
+pub fn query_requests_expiring_before(
+    deps: Deps,
+    env: Env,
+    expiration: Expiration,
+    start_after: Option<(u64, u64)>,
+    limit: Option<u32>,
 ) -> StdResult<Vec<BorrowRequestResponse>> {
-    let borrower_raw = deps.api.addr_canonicalize(borrower.as_str())?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    read_borrow_requests_expiring_before(deps.storage, &expiration, start_after, limit)?
+        .into_iter()
+        .map(|id| query_borrow_request_by_id(deps, env.clone(), id))
+        .collect()
+}
+
+/// ## Description
+/// Returns a pooled reserve with interest accrued up to the current block in the
+/// [`ReserveResponse`] object
+/// ## Params
+/// * **deps** is an object of type [`Deps`]
+///
+/// * **env** is an object of type [`Env`]
+///
+/// * **asset_info** is an object of type [`AssetInfo`]
+pub fn query_reserve(deps: Deps, env: Env, asset_info: AssetInfo) -> StdResult<ReserveResponse> {
+    let reserve = load_accrued_reserve(deps, &env, &asset_info)?;
+
+    let res = ReserveResponse {
+        total_debt: total_debt(&reserve)?,
+        utilization: utilization(&reserve)?,
+        borrow_rate: borrow_rate(&reserve)?,
+        config: to_reserve_config(deps, &reserve)?,
+        share_token: deps.api.addr_humanize(&reserve.share_token)?.to_string(),
+        asset_info: reserve.asset_info,
+        liquidity: reserve.liquidity,
+        total_shares: reserve.total_shares,
+        borrow_index: reserve.borrow_index,
+    };
+
+    Ok(res)
+}
+
+/// ## Description
+/// Returns a pooled loan with interest accrued up to the current block in the
+/// [`LoanResponse`] object
+/// ## Params
+/// * **deps** is an object of type [`Deps`]
+///
+/// * **env** is an object of type [`Env`]
+///
+/// * **id** is [`u64`]
+pub fn query_loan(deps: Deps, env: Env, id: u64) -> StdResult<LoanResponse> {
+    let loan = may_load_loan(deps.storage, id)?.ok_or(ContractError::LoanNotFound {})?;
+    let reserve = load_accrued_reserve(deps, &env, &loan.asset_info)?;
+
+    let debt = loan_debt(&reserve, &loan)?;
+
+    to_loan_response(deps, loan, debt)
+}
+
+/// ## Description
+/// Returns a page of pooled loans that can be liquidated at the current block in the
+/// [`Vec<LoanResponse>`] object, ordered by reserve and then from the highest debt per unit
+/// of collateral. The page starts after the given position in that order, so it stays in
+/// place when the loan last returned has been repaid or liquidated in the meantime
+/// ## Params
+/// * **deps** is an object of type [`Deps`]
+///
+/// * **env** is an object of type [`Env`]
+///
+/// * **start_after** is an [`Option`] of [`LoanCursor`]
+///
+/// * **limit** is an [`Option`] of [`u32`]
+pub fn query_liquidatable_loans(
+    deps: Deps,
+    env: Env,
+    start_after: Option<LoanCursor>,
+    limit: Option<u32>,
+) -> StdResult<Vec<LoanResponse>> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+
+    let mut res = vec![];
+    for mut reserve in read_reserves(deps.storage)? {
+        let start_after = match &start_after {
+            Some(cursor) if cursor.asset_info.as_bytes() > reserve.asset_info.as_bytes() => {
+                continue
+            }
+            Some(cursor) if cursor.asset_info == reserve.asset_info => {
+                Some((cursor.debt_ratio, cursor.id))
+            }
+            _ => None,
+        };
+        accrue_interest(&mut reserve, env.block.height)?;
+
+        let mut healthy_ratio: Option<Decimal256> = None;
+        for id in read_loans_by_debt_ratio(deps.storage, &reserve.asset_info, start_after) {
+            if res.len() >= limit {
+                return Ok(res);
+            }
+
+            let loan = may_load_loan(deps.storage, id?)?.ok_or(ContractError::LoanNotFound {})?;
+            // The debt of every loan of a reserve grows at the same rate, so once a loan is
+            // healthy all the loans after it are too. Ratios are rounded though, so loans
+            // sharing the ratio of a healthy loan are still checked
+            if matches!(healthy_ratio, Some(ratio) if loan.debt_ratio() < ratio) {
+                break;
+            }
+            let debt = loan_debt(&reserve, &loan)?;
+            if !is_liquidatable(&reserve, &loan, debt)? {
+                healthy_ratio = Some(loan.debt_ratio());
+                continue;
+            }
+            res.push(to_loan_response(deps, loan, debt)?);
+        }
+    }
+
+    Ok(res)
+}
+
+/// ## Description
+/// Returns the amount due to repay a pooled loan in full at the given block height, or at
+/// the current block if not given, in the [`SimulateRepayResponse`] object. The interest is
+/// accrued at the current borrow rate as repaying would
+/// ## Params
+/// * **deps** is an object of type [`Deps`]
+///
+/// * **env** is an object of type [`Env`]
+///
+/// * **id** is [`u64`]
+///
+/// * **at_height** is an [`Option`] of [`u64`]
+pub fn query_simulate_repay(
+    deps: Deps,
+    env: Env,
+    id: u64,
+    at_height: Option<u64>,
+) -> StdResult<SimulateRepayResponse> {
+    let at_height = at_height.unwrap_or(env.block.height);
+    if at_height < env.block.height {
+        return Err(ContractError::InvalidHeight {}.into());
+    }
+
+    let loan = may_load_loan(deps.storage, id)?.ok_or(ContractError::LoanNotFound {})?;
+    let mut reserve = may_load_reserve(deps.storage, &loan.asset_info)?
+        .ok_or(ContractError::ReserveNotFound {})?;
+    accrue_interest(&mut reserve, at_height)?;
+
+    let debt = loan_debt(&reserve, &loan)?;
+    let (interest, principal) = repayment_parts(&loan, debt, debt);
+
+    let res = SimulateRepayResponse {
+        id,
+        at_height,
+        debt,
+        interest,
+        principal,
+    };
+
+    Ok(res)
+}
+
+/// ## Description
+/// Returns the outcome of liquidating a pooled loan at the current block in the
+/// [`SimulateLiquidationResponse`] object, which is reported even if the loan is not
+/// liquidatable yet
+/// ## Params
+/// * **deps** is an object of type [`Deps`]
+///
+/// * **env** is an object of type [`Env`]
+///
+/// * **id** is [`u64`]
+pub fn query_simulate_liquidation(
+    deps: Deps,
+    env: Env,
+    id: u64,
+) -> StdResult<SimulateLiquidationResponse> {
+    let loan = may_load_loan(deps.storage, id)?.ok_or(ContractError::LoanNotFound {})?;
+    let reserve = load_accrued_reserve(deps, &env, &loan.asset_info)?;
+
+    let debt = loan_debt(&reserve, &loan)?;
+    let seized = seized_collateral(&reserve, &loan, debt)?;
+
+    let res = SimulateLiquidationResponse {
+        id,
+        liquidatable: is_liquidatable(&reserve, &loan, debt)?,
+        lender_payout: debt,
+        bonus: liquidation_bonus(&reserve, debt)?,
+        seized_collateral: seized,
+        borrower_refund: loan.collateral.amount.checked_sub(seized)?,
+    };
+
+    Ok(res)
+}
+
+/// ## Description
+/// Returns the collateral value, debt and loan-to-value ratio of a pooled loan at the
+/// current block in the [`LoanHealthResponse`] object
+/// ## Params
+/// * **deps** is an object of type [`Deps`]
+///
+/// * **env** is an object of type [`Env`]
+///
+/// * **id** is [`u64`]
+pub fn query_loan_health(deps: Deps, env: Env, id: u64) -> StdResult<LoanHealthResponse> {
+    let loan = may_load_loan(deps.storage, id)?.ok_or(ContractError::LoanNotFound {})?;
+    let reserve = load_accrued_reserve(deps, &env, &loan.asset_info)?;
+
+    let debt = loan_debt(&reserve, &loan)?;
+
+    let res = LoanHealthResponse {
+        id,
+        collateral_value: collateral_value(&reserve, loan.collateral.amount)?,
+        debt,
+        ltv: loan_to_value(&reserve, &loan, debt)?,
+        liquidation_threshold: reserve.liquidation_threshold,
+        liquidation_price: liquidation_price(&reserve, &loan, debt)?,
+    };
+
+    Ok(res)
+}
+
+/// ## Description
+/// Returns protocol-wide statistics of every asset, and of collateral locked on remote
+/// chains, in the [`StatsResponse`] object
+/// ## Params
+/// * **deps** is an object of type [`Deps`]
+pub fn query_stats(deps: Deps) -> StdResult<StatsResponse> {
+    let res = StatsResponse {
+        assets: read_asset_stats(deps.storage)?
+            .into_iter()
+            .map(|stats| AssetStatsResponse {
+                asset_info: stats.asset_info,
+                collateral_escrowed: stats.collateral_escrowed,
+                principal_outstanding: stats.principal_outstanding,
+                volume_lent: stats.volume_lent,
+                interest_paid: stats.interest_paid,
+                active_loans: stats.active_loans,
+                defaulted_loans: stats.defaulted_loans,
+                liquidated_loans: stats.liquidated_loans,
+            })
+            .collect(),
+        remote_collateral: read_remote_collateral_stats(deps.storage)?
+            .into_iter()
+            .map(|stats| RemoteCollateralStatsResponse {
+                channel_id: stats.channel_id,
+                denom: stats.denom,
+                collateral_escrowed: stats.collateral_escrowed,
+            })
+            .collect(),
+    };
+
+    Ok(res)
+}
+
+/// ## Description
+/// Returns credit history of a borrower in the [`BorrowerProfileResponse`] object
+/// ## Params
+/// * **deps** is an object of type [`Deps`]
+///
+/// * **address** is [`String`]
+pub fn query_borrower_profile(deps: Deps, address: String) -> StdResult<BorrowerProfileResponse> {
+    let borrower_raw = addr_canonicalize(deps.api, address.as_str())?;
+    let profile = load_borrower_profile(deps.storage, &borrower_raw)?;
 
-    let res = load_borrow_request(deps.storage, &borrower_raw)?
+    let res = BorrowerProfileResponse {
+        address,
+        loans_taken: profile.loans_taken,
+        loans_repaid_on_time: profile.loans_repaid_on_time,
+        loans_repaid_late: profile.loans_repaid_late,
+        loans_defaulted: profile.loans_defaulted,
+        loans_liquidated: profile.loans_liquidated,
+        principal_borrowed: profile.principal_borrowed,
+    };
+
+    Ok(res)
+}
+
+/// ## Description
+/// Returns a pooled reserve with interest accrued up to the current block
+/// ## Params
+/// * **deps** is an object of type [`Deps`]
+///
+/// * **env** is an object of type [`Env`]
+///
+/// * **asset_info** is an object of type [`AssetInfo`]
+fn load_accrued_reserve(deps: Deps, env: &Env, asset_info: &AssetInfo) -> StdResult<Reserve> {
+    let mut reserve =
+        may_load_reserve(deps.storage, asset_info)?.ok_or(ContractError::ReserveNotFound {})?;
+    accrue_interest(&mut reserve, env.block.height)?;
+
+    Ok(reserve)
+}
+
+/// ## Description
+/// Returns a borrow request by id if exists
+/// ## Params
+/// * **deps** is an object of type [`Deps`]
+///
+/// * **id** is [`u64`]
+fn may_load_borrow_request_by_id(deps: Deps, id: u64) -> StdResult<Option<BorrowRequest>> {
+    let borrower = match may_load_borrow_request_id_to_addr(deps.storage, id)? {
+        Some(borrower) => borrower,
+        None => return Ok(None),
+    };
+
+    Ok(load_borrow_request(deps.storage, &borrower)?
         .into_iter()
-        .map(|br| BorrowRequestResponse {
-            id: br.id,
-            collateral: br.collateral,
-            period: br.period,
-            borrowed_from: br.borrowed_from,
-            borrowed_at: br.borrowed_at,
-        })
-        .collect();
+        .find(|br| br.id == id))
+}
+
+/// ## Description
+/// Converts [`Loan`] and its debt into [`LoanResponse`]
+/// ## Params
+/// * **deps** is an object of type [`Deps`]
+///
+/// * **loan** is an object of type [`Loan`]
+///
+/// * **debt** is an object of type [`Uint128`]
+fn to_loan_response(deps: Deps, loan: Loan, debt: Uint128) -> StdResult<LoanResponse> {
+    let debt_ratio = loan.debt_ratio();
+
+    let res = LoanResponse {
+        id: loan.id,
+        borrower: deps.api.addr_humanize(&loan.borrower)?.to_string(),
+        asset_info: loan.asset_info,
+        collateral: loan.collateral,
+        debt,
+        principal: loan.principal,
+        borrowed_at: loan.borrowed_at,
+        debt_ratio,
+    };
 
     Ok(res)
 }
+
+/// ## Description
+/// Converts the configurable parameters of [`Reserve`] into [`ReserveConfig`]
+/// ## Params
+/// * **deps** is an object of type [`Deps`]
+///
+/// * **reserve** is an object of type [`Reserve`]
+fn to_reserve_config(deps: Deps, reserve: &Reserve) -> StdResult<ReserveConfig> {
+    let (base_rate, slope1, slope2, optimal_utilization) = match reserve.rate_curve {
+        RateCurve::Kinked {
+            base_rate,
+            slope1,
+            slope2,
+            optimal_utilization,
+        } => (base_rate, slope1, slope2, optimal_utilization),
+        RateCurve::Fixed { rate } => (
+            rate,
+            Decimal256::zero(),
+            Decimal256::zero(),
+            Decimal256::one(),
+        ),
+    };
+
+    let requirements = &reserve.borrower_requirements;
+    let borrower_allowlist = match &requirements.allowlist {
+        Some(allowlist) => Some(
+            allowlist
+                .iter()
+                .map(|addr| deps.api.addr_humanize(addr).map(|addr| addr.to_string()))
+                .collect::<StdResult<Vec<String>>>()?,
+        ),
+        None => None,
+    };
+
+    let res = ReserveConfig {
+        collateral_info: reserve.collateral_info.clone(),
+        collateral_price: reserve.collateral_price,
+        max_ltv: reserve.max_ltv,
+        liquidation_threshold: reserve.liquidation_threshold,
+        liquidation_bonus: reserve.liquidation_bonus,
+        flash_loan_fee: reserve.flash_loan_fee,
+        base_rate,
+        slope1,
+        slope2,
+        optimal_utilization,
+        borrower_allowlist,
+        min_loans_repaid: requirements.min_loans_repaid,
+        max_loans_liquidated: requirements.max_loans_liquidated,
+        max_outstanding_debt: requirements.max_outstanding_debt,
+    };
+
+    Ok(res)
+}
+
+/// ## Description
+/// Converts [`BorrowRequest`] into [`BorrowRequestResponse`] as of the given block
+/// ## Params
+/// * **deps** is an object of type [`Deps`]
+///
+/// * **borrow_request** is an object of type [`BorrowRequest`]
+///
+/// * **block** is an object of type [`BlockInfo`]
+fn to_borrow_request_response(
+    deps: Deps,
+    borrow_request: BorrowRequest,
+    block: &BlockInfo,
+) -> StdResult<BorrowRequestResponse> {
+    Ok(BorrowRequestResponse {
+        expired: borrow_request.is_expired(block),
+        deadline: borrow_request.deadline(),
+        remaining: borrow_request.remaining(block),
+        lender: borrow_request
+            .lender
+            .map(|lender| deps.api.addr_humanize(&lender).map(|addr| addr.to_string()))
+            .transpose()?,
+        id: borrow_request.id,
+        collateral: borrow_request.collateral,
+        period: borrow_request.period,
+        expires_at: borrow_request.expires_at,
+        borrowed_from: borrow_request.borrowed_from,
+        borrowed_at: borrow_request.borrowed_at,
+        borrowed_at_time: borrow_request.borrowed_at_time,
+    })
+}
@@ -0,0 +1,41 @@
+use schemars::{schema::RootSchema, schema_for};
+
+use services::lending::{
+    events::LendingEvent, BorrowRequestResponse, BorrowerProfileResponse, Cw20HookMsg, ExecuteMsg,
+    IbcHookMsg, InstantiateMsg, LendingAck, LendingPacket, LoanHealthResponse, LoanResponse,
+    MigrateMsg, QueryMsg, ReserveResponse, SimulateLiquidationResponse, SimulateRepayResponse,
+    StateResponse, StatsResponse,
+};
+
+/// ## Description
+/// Returns the title and schema of every message and response of the contract, which are
+/// written to `schema/` by `cargo run --example schema`. Titles are given explicitly because
+/// the doc comments of the types start with a heading, which would otherwise become the title
+pub fn schemas() -> Vec<(&'static str, RootSchema)> {
+    vec![
+        ("InstantiateMsg", schema_for!(InstantiateMsg)),
+        ("ExecuteMsg", schema_for!(ExecuteMsg)),
+        ("Cw20HookMsg", schema_for!(Cw20HookMsg)),
+        ("IbcHookMsg", schema_for!(IbcHookMsg)),
+        ("QueryMsg", schema_for!(QueryMsg)),
+        ("MigrateMsg", schema_for!(MigrateMsg)),
+        ("StateResponse", schema_for!(StateResponse)),
+        ("BorrowRequestResponse", schema_for!(BorrowRequestResponse)),
+        ("ReserveResponse", schema_for!(ReserveResponse)),
+        ("LoanResponse", schema_for!(LoanResponse)),
+        ("SimulateRepayResponse", schema_for!(SimulateRepayResponse)),
+        (
+            "SimulateLiquidationResponse",
+            schema_for!(SimulateLiquidationResponse),
+        ),
+        ("LoanHealthResponse", schema_for!(LoanHealthResponse)),
+        (
+            "BorrowerProfileResponse",
+            schema_for!(BorrowerProfileResponse),
+        ),
+        ("StatsResponse", schema_for!(StatsResponse)),
+        ("LendingPacket", schema_for!(LendingPacket)),
+        ("LendingAck", schema_for!(LendingAck)),
+        ("LendingEvent", schema_for!(LendingEvent)),
+    ]
+}
@@ -1,9 +1,24 @@
-use astroport::asset::Asset;
-use cosmwasm_std::{CanonicalAddr, StdResult, Storage};
-use cw_storage_plus::{Item, Map};
+use astroport::asset::{Asset, AssetInfo};
+use std::collections::BTreeMap;
+use std::convert::TryInto;
+
+use cosmwasm_std::{
+    BlockInfo, CanonicalAddr, Coin, Decimal256, Fraction, Order, Pair, StdError, StdResult,
+    Storage, Timestamp, Uint128,
+};
+use cw0::{Duration, Expiration};
+use cw_storage_plus::{Bound, Item, Map, U64Key};
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+use crate::interest::RateCurve;
+
+use services::lending::ReserveConfig;
+
+/// ## Description
+/// Stores a struct of type [`Config`] at the given key
+static CONFIG: Item<Config> = Item::new("config");
+
 /// ## Description
 /// Stores a struct of type [`State`] at the given key
 static STATE: Item<State> = Item::new("state");
@@ -16,25 +31,159 @@ static BORROW_REQUEST: Map<&[u8], Vec<BorrowRequest>> = Map::new("borrow_request
 /// ## Description
 /// A map which stores id and borrower address of each borrow request
 /// [`u64`] type as key and [`CanonicalAddr`] type as value
-static BORROW_REQUEST_ID_TO_ADDR: Map<&str, CanonicalAddr> = Map::new("borrow_request_id_to_addr");
+static BORROW_REQUEST_ID_TO_ADDR: Map<U64Key, CanonicalAddr> =
+    Map::new("borrow_request_id_to_addr");
 
 /// ## Description
-/// This structure describes state of lending contract
+/// A map which stores ids of borrow requests expiring at a block height with
+/// height and id bytes as key and [`u64`] id as value
+static BORROW_REQUEST_EXPIRY_HEIGHT: Map<&[u8], u64> = Map::new("borrow_request_expiry_height");
+
+/// ## Description
+/// A map which stores ids of borrow requests expiring at a block time with
+/// time in nanoseconds and id bytes as key and [`u64`] id as value
+static BORROW_REQUEST_EXPIRY_TIME: Map<&[u8], u64> = Map::new("borrow_request_expiry_time");
+
+/// ## Description
+/// A map which stores ids of borrow requests by collateral with
+/// [`AssetInfo`] bytes and [`u64`] id as key and [`u64`] id as value
+static BORROW_REQUEST_BY_COLLATERAL: Map<(&[u8], U64Key), u64> =
+    Map::new("borrow_request_by_collateral");
+
+/// ## Description
+/// A map which stores ids of borrow requests directed to a lender with
+/// [`CanonicalAddr`] of the lender and [`u64`] id as key and [`u64`] id as value
+static BORROW_REQUEST_BY_LENDER: Map<(&[u8], U64Key), u64> = Map::new("borrow_request_by_lender");
+
+/// ## Description
+/// A map which stores pooled reserves with
+/// [`AssetInfo`] bytes as key and [`Reserve`] type as value
+static RESERVE: Map<&[u8], Reserve> = Map::new("reserve");
+
+/// ## Description
+/// Stores the asset and config of a reserve whose share token is being instantiated
+static PENDING_RESERVE: Item<(AssetInfo, ReserveConfig)> = Item::new("pending_reserve");
+
+/// ## Description
+/// A map which stores the reserve asset of each share token with
+/// [`CanonicalAddr`] type as key and [`AssetInfo`] type as value
+static SHARE_TOKEN_TO_ASSET: Map<&[u8], AssetInfo> = Map::new("share_token_to_asset");
+
+/// ## Description
+/// A map which stores credit history of borrowers with
+/// [`CanonicalAddr`] type as key and [`BorrowerProfile`] type as value
+static BORROWER_PROFILE: Map<&[u8], BorrowerProfile> = Map::new("borrower_profile");
+
+/// ## Description
+/// Stores a struct of type [`FlashLoan`] while a flash loan is ongoing
+static FLASH_LOAN: Item<FlashLoan> = Item::new("flash_loan");
+
+/// ## Description
+/// A map which stores pooled loans with
+/// [`u64`] type as key and [`Loan`] type as value
+static LOAN: Map<U64Key, Loan> = Map::new("loan");
+
+/// ## Description
+/// A map which stores ids of pooled loans sorted by scaled debt per unit of collateral with
+/// [`AssetInfo`] bytes and ratio and id bytes as key and [`u64`] id as value
+static LOAN_BY_DEBT_RATIO: Map<(&[u8], &[u8]), u64> = Map::new("loan_by_debt_ratio");
+
+/// ## Description
+/// A map which stores the scaled debt each borrower owes a reserve across its loans with
+/// [`AssetInfo`] bytes and [`CanonicalAddr`] bytes as key and [`Uint128`] type as value
+static BORROWER_SCALED_DEBT: Map<(&[u8], &[u8]), Uint128> = Map::new("borrower_scaled_debt");
+
+/// ## Description
+/// A map which stores protocol-wide statistics of each asset with
+/// [`AssetInfo`] bytes as key and [`AssetStats`] type as value
+static ASSET_STATS: Map<&[u8], AssetStats> = Map::new("asset_stats");
+
+/// ## Description
+/// A map which stores statistics of collateral locked on remote chains, kept apart from
+/// [`ASSET_STATS`] as it is not held by the contract, with channel id bytes and remote
+/// denom bytes as key and [`RemoteCollateralStats`] type as value
+static REMOTE_COLLATERAL_STATS: Map<(&[u8], &[u8]), RemoteCollateralStats> =
+    Map::new("remote_collateral_stats");
+
+/// ## Description
+/// A map which stores the port of the counterpart contract allowed over each connection with
+/// connection id as key and port id as value
+static IBC_COUNTERPARTY: Map<&str, String> = Map::new("ibc_counterparty");
+
+/// ## Description
+/// A map which stores connected channels with
+/// channel id as key and connection id as value
+static IBC_CHANNEL: Map<&str, String> = Map::new("ibc_channel");
+
+/// ## Description
+/// A map which stores collateral locked on remote chains with
+/// [`u64`] borrow request id as key and [`RemoteCollateral`] type as value
+static REMOTE_COLLATERAL: Map<U64Key, RemoteCollateral> = Map::new("remote_collateral");
+
+/// ## Description
+/// A map which stores borrow requests whose remote collateral is being released with
+/// [`u64`] id as key and [`PendingRelease`] type as value
+static PENDING_RELEASE: Map<U64Key, PendingRelease> = Map::new("pending_release");
+
+/// ## Description
+/// Stores a struct of type [`IbcHookConfig`] at the given key
+static IBC_HOOK_CONFIG: Item<IbcHookConfig> = Item::new("ibc_hook_config");
+
+/// ## Description
+/// Stores the number of CW20 payouts has been dispatched, from which their reply ids are given
+static PAYOUT_COUNT: Item<u64> = Item::new("payout_count");
+
+/// ## Description
+/// A map which stores CW20 payouts awaiting their reply with
+/// [`u64`] id as key and [`Payout`] type as value
+static PAYOUT: Map<U64Key, Payout> = Map::new("payout");
+
+/// ## Description
+/// A map which stores failed payouts to be claimed with recipient [`CanonicalAddr`] and
+/// [`AssetInfo`] bytes as key and [`Asset`] type as value
+static CLAIMABLE: Map<(&[u8], &[u8]), Asset> = Map::new("claimable");
+
+/// ## Description
+/// This structure describes a CW20 transfer dispatched to a recipient, kept until its reply
+/// tells whether the asset has to be left claimable
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Payout {
+    // Recipient address
+    pub recipient: CanonicalAddr,
+    // Transferred asset
+    pub asset: Asset,
+}
+
+/// ## Description
+/// This structure describes config of lending contract
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Config {
+    /// Address allowed to manage pooled reserves
+    pub owner: CanonicalAddr,
+    /// Code id of the CW20 contract instantiated as the share token of each reserve
+    pub token_code_id: u64,
+}
+
+/// ## Description
+/// This structure describes who can run IBC hook messages and over which channels
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct IbcHookConfig {
+    /// IBC hook middleware allowed to run the messages, or the borrower if none
+    pub hook: Option<CanonicalAddr>,
+    /// `{port}/{channel}` transfer channels tokens can arrive over
+    pub transfer_channels: Vec<String>,
+}
+
+/// ## Description
+/// This structure describes state of lending contract
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
 pub struct State {
     /// The number of requests has been made
     pub request_count: u64,
     /// The number of responses has been made
     pub response_count: u64,
-}
-
-impl Default for State {
-    fn default() -> Self {
-        State {
-            request_count: 0u64,
-            response_count: 0u64,
-        }
-    }
+    /// The number of pooled loans has been made
+    pub loan_count: u64,
 }
 
 /// ## Description
@@ -45,12 +194,314 @@ pub struct BorrowRequest {
     pub id: u64,
     // Collateral asset
     pub collateral: Asset,
-    // Borrowing block period
-    pub period: u64,
+    // Borrowing period, either in blocks or in seconds
+    pub period: Duration,
+    // The only lender who can fund this request, if directed
+    pub lender: Option<CanonicalAddr>,
+    // A block height or time after which this request can no longer be funded
+    pub expires_at: Option<Expiration>,
     // An id of borrow response, which responding to this request
     pub borrowed_from: u64,
     // A block height that borrowing occured at
     pub borrowed_at: u64,
+    // A block time in seconds that borrowing occured at
+    pub borrowed_at_time: u64,
+}
+
+impl BorrowRequest {
+    /// ## Description
+    /// Returns true if the request has not been borrowed and has expired at the given block
+    /// ## Params
+    /// * **block** is an object of type [`BlockInfo`]
+    pub fn is_expired(&self, block: &BlockInfo) -> bool {
+        self.borrowed_from == 0u64
+            && matches!(&self.expires_at, Some(expires_at) if expires_at.is_expired(block))
+    }
+
+    /// ## Description
+    /// Returns the block height or time that borrowing ends at
+    /// Returns [`None`] if the request has not been borrowed yet
+    pub fn deadline(&self) -> Option<Expiration> {
+        if self.borrowed_from == 0u64 {
+            return None;
+        }
+
+        match self.period {
+            Duration::Height(blocks) => Some(Expiration::AtHeight(
+                self.borrowed_at.saturating_add(blocks),
+            )),
+            Duration::Time(seconds) => Some(Expiration::AtTime(Timestamp::from_seconds(
+                self.borrowed_at_time.saturating_add(seconds),
+            ))),
+        }
+    }
+
+    /// ## Description
+    /// Returns the number of blocks or seconds left until the deadline at the given block
+    /// Returns [`None`] if the request has not been borrowed yet
+    /// ## Params
+    /// * **block** is an object of type [`BlockInfo`]
+    pub fn remaining(&self, block: &BlockInfo) -> Option<Duration> {
+        match self.deadline()? {
+            Expiration::AtHeight(height) => {
+                Some(Duration::Height(height.saturating_sub(block.height)))
+            }
+            Expiration::AtTime(time) => Some(Duration::Time(
+                time.seconds().saturating_sub(block.time.seconds()),
+            )),
+            Expiration::Never {} => None,
+        }
+    }
+}
+
+/// ## Description
+/// This structure describes a pooled reserve lenders deposit into and borrowers draw from
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Reserve {
+    // Asset lent out by the reserve
+    pub asset_info: AssetInfo,
+    // CW20 token minted to lenders as a claim on the reserve
+    pub share_token: CanonicalAddr,
+    // Asset accepted as collateral
+    pub collateral_info: AssetInfo,
+    // Price of one unit of collateral in units of the reserve asset
+    pub collateral_price: Decimal256,
+    // Maximum loan-to-value ratio a loan can be opened at
+    pub max_ltv: Decimal256,
+    // Loan-to-value ratio above which a loan can be liquidated
+    pub liquidation_threshold: Decimal256,
+    // Share of the repaid debt paid to liquidators on top in collateral
+    pub liquidation_bonus: Decimal256,
+    // Share of a flash loan paid to lenders on top when it is returned
+    pub flash_loan_fee: Decimal256,
+    // Curve deriving the per-block borrow rate from the utilization
+    pub rate_curve: RateCurve,
+    // Amount available to borrow or withdraw
+    pub liquidity: Uint128,
+    // Total shares issued to lenders
+    pub total_shares: Uint128,
+    // Total debt divided by the borrow index at the time of each borrow
+    pub total_scaled_debt: Uint128,
+    // Debt growth since the reserve was created
+    pub borrow_index: Decimal256,
+    // A block height that interest was last accrued at
+    pub last_accrued_at: u64,
+    // What a borrower must meet to borrow from the reserve
+    pub borrower_requirements: BorrowerRequirements,
+}
+
+/// ## Description
+/// This structure describes what a borrower must meet to borrow from a pooled reserve, as
+/// required by its lenders
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct BorrowerRequirements {
+    // Borrowers allowed to borrow, or anyone if not set
+    pub allowlist: Option<Vec<CanonicalAddr>>,
+    // Minimum number of loans repaid in full
+    pub min_loans_repaid: Option<u64>,
+    // Maximum number of loans liquidated
+    pub max_loans_liquidated: Option<u64>,
+    // Maximum debt owed to the reserve across loans once borrowed
+    pub max_outstanding_debt: Option<Uint128>,
+}
+
+/// ## Description
+/// This structure describes a loan drawn from a pooled reserve
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct Loan {
+    // Id, which is start from 1
+    pub id: u64,
+    // Borrower address
+    pub borrower: CanonicalAddr,
+    // Borrowed asset
+    pub asset_info: AssetInfo,
+    // Collateral asset
+    pub collateral: Asset,
+    // Debt divided by the borrow index at the time of borrowing
+    pub scaled_debt: Uint128,
+    // Borrowed amount not yet repaid, as repayments go to interest first
+    pub principal: Uint128,
+    // A block height that borrowing occured at
+    pub borrowed_at: u64,
+}
+
+impl Loan {
+    /// ## Description
+    /// Returns the scaled debt per unit of collateral, which orders the loans of a reserve
+    /// by how close they are to being liquidated
+    pub fn debt_ratio(&self) -> Decimal256 {
+        Decimal256::from_ratio(self.scaled_debt, self.collateral.amount)
+    }
+}
+
+/// ## Description
+/// This structure describes how a borrower has behaved in past loans
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, JsonSchema)]
+pub struct BorrowerProfile {
+    // The number of loans taken
+    pub loans_taken: u64,
+    // The number of loans repaid in full by their deadline. Pooled loans have no deadline,
+    // so repaying one in full always counts here
+    pub loans_repaid_on_time: u64,
+    // The number of loans repaid in full after their deadline. Reserved until peer-to-peer
+    // loans with a deadline can be funded, so it stays zero
+    pub loans_repaid_late: u64,
+    // The number of loans defaulted on. Reserved until peer-to-peer loans with a deadline
+    // can be funded, so it stays zero
+    pub loans_defaulted: u64,
+    // The number of loans liquidated
+    pub loans_liquidated: u64,
+    // Total principal borrowed per asset
+    pub principal_borrowed: Vec<Asset>,
+}
+
+impl BorrowerProfile {
+    /// ## Description
+    /// Adds the given principal to the total borrowed of its asset
+    /// ## Params
+    /// * **principal** is an object of type [`Asset`]
+    pub fn add_principal(&mut self, principal: &Asset) -> StdResult<()> {
+        match self
+            .principal_borrowed
+            .iter_mut()
+            .find(|asset| asset.info == principal.info)
+        {
+            Some(asset) => asset.amount = asset.amount.checked_add(principal.amount)?,
+            None => self.principal_borrowed.push(principal.clone()),
+        }
+
+        Ok(())
+    }
+}
+
+/// ## Description
+/// This structure describes protocol-wide statistics of an asset, either as collateral or
+/// as a borrowed asset, which are maintained by each command
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AssetStats {
+    // Asset the statistics are of
+    pub asset_info: AssetInfo,
+    // Collateral held by borrow requests and pooled loans
+    pub collateral_escrowed: Uint128,
+    // Principal of pooled loans not yet repaid
+    pub principal_outstanding: Uint128,
+    // Total amount ever lent by pooled loans
+    pub volume_lent: Uint128,
+    // Total interest ever paid on pooled loans
+    pub interest_paid: Uint128,
+    // The number of pooled loans not yet repaid or liquidated
+    pub active_loans: u64,
+    // The number of loans defaulted on. Reserved until peer-to-peer loans with a deadline
+    // can be funded, so it stays zero
+    pub defaulted_loans: u64,
+    // The number of pooled loans liquidated
+    pub liquidated_loans: u64,
+}
+
+impl AssetStats {
+    /// ## Description
+    /// Returns empty statistics of the given asset
+    /// ## Params
+    /// * **asset_info** is an object of type [`AssetInfo`]
+    pub fn new(asset_info: AssetInfo) -> Self {
+        AssetStats {
+            asset_info,
+            collateral_escrowed: Uint128::zero(),
+            principal_outstanding: Uint128::zero(),
+            volume_lent: Uint128::zero(),
+            interest_paid: Uint128::zero(),
+            active_loans: 0u64,
+            defaulted_loans: 0u64,
+            liquidated_loans: 0u64,
+        }
+    }
+}
+
+/// ## Description
+/// This structure describes statistics of collateral locked by a counterpart contract on a
+/// remote chain
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RemoteCollateralStats {
+    // Channel to the counterpart contract
+    pub channel_id: String,
+    // Denom of the collateral on the remote chain
+    pub denom: String,
+    // Collateral held by borrow requests
+    pub collateral_escrowed: Uint128,
+}
+
+/// ## Description
+/// This structure describes an ongoing flash loan
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FlashLoan {
+    // Asset lent out
+    pub asset_info: AssetInfo,
+    // Balance of the contract before the flash loan was sent
+    pub balance: Uint128,
+    // Fee to be returned on top of the flash loan
+    pub fee: Uint128,
+}
+
+/// ## Description
+/// This structure describes collateral locked by a counterpart contract on a remote chain
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RemoteCollateral {
+    // Channel to the counterpart contract
+    pub channel_id: String,
+    // Address on the remote chain the collateral is refunded to
+    pub refund_address: String,
+    // Collateral in its denom on the remote chain
+    pub collateral: Coin,
+}
+
+/// ## Description
+/// This structure describes a borrow request removed until the release of its remote
+/// collateral is acknowledged, so that it can be restored if the release fails
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PendingRelease {
+    // Borrower address
+    pub borrower: CanonicalAddr,
+    // Removed borrow request
+    pub borrow_request: BorrowRequest,
+}
+
+/// ## Description
+/// Saves changes of [`Config`] struct in [`CONFIG`] storage
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **config** is a struct of type [`Config`] to be stored
+pub fn store_config(storage: &mut dyn Storage, config: &Config) -> StdResult<()> {
+    CONFIG.save(storage, config)
+}
+
+/// ## Description
+/// Returns struct of type [`Config`]
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+pub fn load_config(storage: &dyn Storage) -> StdResult<Config> {
+    CONFIG.load(storage)
+}
+
+/// ## Description
+/// Saves changes of [`IbcHookConfig`] struct in [`IBC_HOOK_CONFIG`] storage
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **ibc_hook_config** is a struct of type [`IbcHookConfig`] to be stored
+pub fn store_ibc_hook_config(
+    storage: &mut dyn Storage,
+    ibc_hook_config: &IbcHookConfig,
+) -> StdResult<()> {
+    IBC_HOOK_CONFIG.save(storage, ibc_hook_config)
+}
+
+/// ## Description
+/// Returns struct of type [`IbcHookConfig`], which allows no hook nor channel until set
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+pub fn load_ibc_hook_config(storage: &dyn Storage) -> StdResult<IbcHookConfig> {
+    Ok(IBC_HOOK_CONFIG.may_load(storage)?.unwrap_or_default())
 }
 
 /// ## Description
@@ -115,19 +566,49 @@ pub fn store_borrow_request_id_to_addr(
     id: u64,
     borrower: &CanonicalAddr,
 ) -> StdResult<()> {
-    BORROW_REQUEST_ID_TO_ADDR.save(storage, &id.to_string(), borrower)
+    BORROW_REQUEST_ID_TO_ADDR.save(storage, U64Key::from(id), borrower)
 }
 
 /// ## Description
-/// Returns an object of type [`CanonicalAddr`]
+/// Returns an object of type [`CanonicalAddr`] if exists
 /// ## Params
 /// * **storage** is an object of type [`Storage`]
 ///
 /// * **id** is [`u64`]
-pub fn load_borrow_request_id_to_addr(storage: &dyn Storage, id: u64) -> StdResult<CanonicalAddr> {
+pub fn may_load_borrow_request_id_to_addr(
+    storage: &dyn Storage,
+    id: u64,
+) -> StdResult<Option<CanonicalAddr>> {
+    BORROW_REQUEST_ID_TO_ADDR.may_load(storage, U64Key::from(id))
+}
+
+/// ## Description
+/// Returns an iterator over ids and borrower addresses of borrow requests in ascending
+/// order of id
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **start_after** is an [`Option`] of [`u64`], the id to start after
+pub fn read_borrow_request_id_to_addr(
+    storage: &dyn Storage,
+    start_after: Option<u64>,
+) -> impl Iterator<Item = StdResult<(u64, CanonicalAddr)>> + '_ {
     BORROW_REQUEST_ID_TO_ADDR
-        .may_load(storage, &id.to_string())
-        .map(|res| res.unwrap())
+        .range(
+            storage,
+            start_after.map(Bound::exclusive_int),
+            None,
+            Order::Ascending,
+        )
+        .map(|item| {
+            let (key, borrower) = item?;
+            let id = key
+                .as_slice()
+                .try_into()
+                .map(u64::from_be_bytes)
+                .map_err(|_| StdError::invalid_data_size(8usize, key.len()))?;
+            Ok((id, borrower))
+        })
 }
 
 /// ## Description
@@ -137,5 +618,999 @@ pub fn load_borrow_request_id_to_addr(storage: &dyn Storage, id: u64) -> StdResu
 ///
 /// * **id** is [`u64`]
 pub fn remove_borrow_request_id_to_addr(storage: &mut dyn Storage, id: u64) {
-    BORROW_REQUEST_ID_TO_ADDR.remove(storage, &id.to_string())
+    BORROW_REQUEST_ID_TO_ADDR.remove(storage, U64Key::from(id))
+}
+
+/// ## Description
+/// Saves the id of a borrow request in the expiry index of its height or time
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **expires_at** is an object of type [`Expiration`]
+///
+/// * **id** is [`u64`]
+pub fn store_borrow_request_expiry(
+    storage: &mut dyn Storage,
+    expires_at: &Expiration,
+    id: u64,
+) -> StdResult<()> {
+    match expires_at {
+        Expiration::AtHeight(height) => {
+            BORROW_REQUEST_EXPIRY_HEIGHT.save(storage, &expiry_key(*height, id), &id)
+        }
+        Expiration::AtTime(time) => {
+            BORROW_REQUEST_EXPIRY_TIME.save(storage, &expiry_key(time.nanos(), id), &id)
+        }
+        Expiration::Never {} => Ok(()),
+    }
+}
+
+/// ## Description
+/// Remove the id of a borrow request in the expiry index of its height or time
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **expires_at** is an object of type [`Expiration`]
+///
+/// * **id** is [`u64`]
+pub fn remove_borrow_request_expiry(storage: &mut dyn Storage, expires_at: &Expiration, id: u64) {
+    match expires_at {
+        Expiration::AtHeight(height) => {
+            BORROW_REQUEST_EXPIRY_HEIGHT.remove(storage, &expiry_key(*height, id))
+        }
+        Expiration::AtTime(time) => {
+            BORROW_REQUEST_EXPIRY_TIME.remove(storage, &expiry_key(time.nanos(), id))
+        }
+        Expiration::Never {} => (),
+    }
+}
+
+/// ## Description
+/// Returns up to the given number of ids of borrow requests expired at the given block,
+/// earliest expiry first with heights before times
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **block** is an object of type [`BlockInfo`]
+///
+/// * **limit** is [`usize`]
+pub fn read_expired_borrow_requests(
+    storage: &dyn Storage,
+    block: &BlockInfo,
+    limit: usize,
+) -> StdResult<Vec<u64>> {
+    let until_height = expiry_key(block.height, u64::MAX);
+    let until_time = expiry_key(block.time.nanos(), u64::MAX);
+
+    BORROW_REQUEST_EXPIRY_HEIGHT
+        .range(
+            storage,
+            None,
+            Some(Bound::inclusive(until_height)),
+            Order::Ascending,
+        )
+        .chain(BORROW_REQUEST_EXPIRY_TIME.range(
+            storage,
+            None,
+            Some(Bound::inclusive(until_time)),
+            Order::Ascending,
+        ))
+        .take(limit)
+        .map(|item| item.map(|(_, id)| id))
+        .collect()
+}
+
+/// ## Description
+/// Saves the id of a borrow request in the index of its collateral and, if directed,
+/// in the index of its lender
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **borrow_request** is an object of type [`BorrowRequest`]
+pub fn store_borrow_request_indexes(
+    storage: &mut dyn Storage,
+    borrow_request: &BorrowRequest,
+) -> StdResult<()> {
+    let id = borrow_request.id;
+    BORROW_REQUEST_BY_COLLATERAL.save(
+        storage,
+        (borrow_request.collateral.info.as_bytes(), U64Key::from(id)),
+        &id,
+    )?;
+    if let Some(lender) = &borrow_request.lender {
+        BORROW_REQUEST_BY_LENDER.save(storage, (lender.as_slice(), U64Key::from(id)), &id)?;
+    }
+
+    Ok(())
+}
+
+/// ## Description
+/// Remove the id of a borrow request in the index of its collateral and of its lender
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **borrow_request** is an object of type [`BorrowRequest`]
+pub fn remove_borrow_request_indexes(storage: &mut dyn Storage, borrow_request: &BorrowRequest) {
+    let id = borrow_request.id;
+    BORROW_REQUEST_BY_COLLATERAL.remove(
+        storage,
+        (borrow_request.collateral.info.as_bytes(), U64Key::from(id)),
+    );
+    if let Some(lender) = &borrow_request.lender {
+        BORROW_REQUEST_BY_LENDER.remove(storage, (lender.as_slice(), U64Key::from(id)));
+    }
+}
+
+/// ## Description
+/// Returns an iterator over ids of borrow requests against the given collateral in ascending
+/// order of id
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **asset_info** is an object of type [`AssetInfo`]
+///
+/// * **start_after** is an [`Option`] of [`u64`], the id to start after
+pub fn read_borrow_requests_by_collateral<'a>(
+    storage: &'a dyn Storage,
+    asset_info: &AssetInfo,
+    start_after: Option<u64>,
+) -> impl Iterator<Item = StdResult<u64>> + 'a {
+    BORROW_REQUEST_BY_COLLATERAL
+        .prefix(asset_info.as_bytes())
+        .range(
+            storage,
+            start_after.map(Bound::exclusive_int),
+            None,
+            Order::Ascending,
+        )
+        .map(|item| item.map(|(_, id)| id))
+}
+
+/// ## Description
+/// Returns up to the given number of ids of borrow requests directed to the given lender
+/// in ascending order of id
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **lender** is an object of type [`CanonicalAddr`]
+///
+/// * **start_after** is an [`Option`] of [`u64`], the id to start after
+///
+/// * **limit** is [`usize`]
+pub fn read_borrow_requests_by_lender(
+    storage: &dyn Storage,
+    lender: &CanonicalAddr,
+    start_after: Option<u64>,
+    limit: usize,
+) -> StdResult<Vec<u64>> {
+    BORROW_REQUEST_BY_LENDER
+        .prefix(lender.as_slice())
+        .range(
+            storage,
+            start_after.map(Bound::exclusive_int),
+            None,
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| item.map(|(_, id)| id))
+        .collect()
+}
+
+/// ## Description
+/// Returns up to the given number of ids of borrow requests expiring before the given
+/// expiration, earliest expiry first. Requests expiring at a block height are listed for
+/// a height and requests expiring at a time for a time
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **expiration** is an object of type [`Expiration`]
+///
+/// * **start_after** is an [`Option`] of expiry and id, the request to start after
+///
+/// * **limit** is [`usize`]
+pub fn read_borrow_requests_expiring_before(
+    storage: &dyn Storage,
+    expiration: &Expiration,
+    start_after: Option<(u64, u64)>,
+    limit: usize,
+) -> StdResult<Vec<u64>> {
+    let (index, until) = match expiration {
+        Expiration::AtHeight(height) => (&BORROW_REQUEST_EXPIRY_HEIGHT, *height),
+        Expiration::AtTime(time) => (&BORROW_REQUEST_EXPIRY_TIME, time.nanos()),
+        Expiration::Never {} => return Ok(vec![]),
+    };
+
+    index
+        .range(
+            storage,
+            start_after.map(|(expiry, id)| Bound::exclusive(expiry_key(expiry, id))),
+            Some(Bound::exclusive(expiry_key(until, 0u64))),
+            Order::Ascending,
+        )
+        .take(limit)
+        .map(|item| item.map(|(_, id)| id))
+        .collect()
+}
+
+/// Returns the key of an expiry index, which sorts by expiry and then by id
+fn expiry_key(expiry: u64, id: u64) -> Vec<u8> {
+    [expiry.to_be_bytes(), id.to_be_bytes()].concat()
+}
+
+/// ## Description
+/// Saves changes of [`Reserve`] struct in [`RESERVE`] storage
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **reserve** is a struct of type [`Reserve`] to be stored
+pub fn store_reserve(storage: &mut dyn Storage, reserve: &Reserve) -> StdResult<()> {
+    RESERVE.save(storage, reserve.asset_info.as_bytes(), reserve)
+}
+
+/// ## Description
+/// Returns struct of type [`Reserve`] if exists
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **asset_info** is an object of type [`AssetInfo`]
+pub fn may_load_reserve(
+    storage: &dyn Storage,
+    asset_info: &AssetInfo,
+) -> StdResult<Option<Reserve>> {
+    RESERVE.may_load(storage, asset_info.as_bytes())
+}
+
+/// ## Description
+/// Returns every pooled reserve in ascending order of [`AssetInfo`] bytes
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+pub fn read_reserves(storage: &dyn Storage) -> StdResult<Vec<Reserve>> {
+    RESERVE
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, reserve)| reserve))
+        .collect()
+}
+
+/// ## Description
+/// Saves the asset and config of a reserve in [`PENDING_RESERVE`] storage
+/// until its share token is instantiated
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **asset_info** is an object of type [`AssetInfo`]
+///
+/// * **config** is an object of type [`ReserveConfig`]
+pub fn store_pending_reserve(
+    storage: &mut dyn Storage,
+    asset_info: &AssetInfo,
+    config: &ReserveConfig,
+) -> StdResult<()> {
+    PENDING_RESERVE.save(storage, &(asset_info.clone(), config.clone()))
+}
+
+/// ## Description
+/// Returns the asset and config of the reserve whose share token is being instantiated
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+pub fn load_pending_reserve(storage: &dyn Storage) -> StdResult<(AssetInfo, ReserveConfig)> {
+    PENDING_RESERVE.load(storage)
+}
+
+/// ## Description
+/// Remove the pending reserve in [`PENDING_RESERVE`] storage
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+pub fn remove_pending_reserve(storage: &mut dyn Storage) {
+    PENDING_RESERVE.remove(storage)
+}
+
+/// ## Description
+/// Saves the reserve asset of a share token in [`SHARE_TOKEN_TO_ASSET`] storage
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **share_token** is an object of type [`CanonicalAddr`]
+///
+/// * **asset_info** is an object of type [`AssetInfo`]
+pub fn store_share_token_to_asset(
+    storage: &mut dyn Storage,
+    share_token: &CanonicalAddr,
+    asset_info: &AssetInfo,
+) -> StdResult<()> {
+    SHARE_TOKEN_TO_ASSET.save(storage, share_token.as_slice(), asset_info)
+}
+
+/// ## Description
+/// Returns the reserve asset of a share token if exists
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **share_token** is an object of type [`CanonicalAddr`]
+pub fn may_load_share_token_to_asset(
+    storage: &dyn Storage,
+    share_token: &CanonicalAddr,
+) -> StdResult<Option<AssetInfo>> {
+    SHARE_TOKEN_TO_ASSET.may_load(storage, share_token.as_slice())
+}
+
+/// ## Description
+/// Saves changes of [`BorrowerProfile`] struct in [`BORROWER_PROFILE`] storage
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **borrower** is an object of type [`CanonicalAddr`]
+///
+/// * **profile** is a struct of type [`BorrowerProfile`] to be stored
+pub fn store_borrower_profile(
+    storage: &mut dyn Storage,
+    borrower: &CanonicalAddr,
+    profile: &BorrowerProfile,
+) -> StdResult<()> {
+    BORROWER_PROFILE.save(storage, borrower.as_slice(), profile)
+}
+
+/// ## Description
+/// Returns struct of type [`BorrowerProfile`], which is empty if the borrower has never
+/// borrowed
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **borrower** is an object of type [`CanonicalAddr`]
+pub fn load_borrower_profile(
+    storage: &dyn Storage,
+    borrower: &CanonicalAddr,
+) -> StdResult<BorrowerProfile> {
+    BORROWER_PROFILE
+        .may_load(storage, borrower.as_slice())
+        .map(|res| res.unwrap_or_default())
+}
+
+/// ## Description
+/// Returns the scaled debt a borrower owes a reserve across its loans, which is zero if it
+/// has none
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **asset_info** is an object of type [`AssetInfo`]
+///
+/// * **borrower** is an object of type [`CanonicalAddr`]
+pub fn load_borrower_scaled_debt(
+    storage: &dyn Storage,
+    asset_info: &AssetInfo,
+    borrower: &CanonicalAddr,
+) -> StdResult<Uint128> {
+    BORROWER_SCALED_DEBT
+        .may_load(storage, (asset_info.as_bytes(), borrower.as_slice()))
+        .map(|res| res.unwrap_or_default())
+}
+
+/// ## Description
+/// Updates the scaled debt a borrower owes a reserve in [`BORROWER_SCALED_DEBT`] storage with
+/// the given action, removing it once it is zero
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **asset_info** is an object of type [`AssetInfo`]
+///
+/// * **borrower** is an object of type [`CanonicalAddr`]
+///
+/// * **action** is a function returning the updated scaled debt
+pub fn update_borrower_scaled_debt<A>(
+    storage: &mut dyn Storage,
+    asset_info: &AssetInfo,
+    borrower: &CanonicalAddr,
+    action: A,
+) -> StdResult<Uint128>
+where
+    A: FnOnce(Uint128) -> StdResult<Uint128>,
+{
+    let scaled_debt = action(load_borrower_scaled_debt(storage, asset_info, borrower)?)?;
+
+    let key = (asset_info.as_bytes(), borrower.as_slice());
+    if scaled_debt.is_zero() {
+        BORROWER_SCALED_DEBT.remove(storage, key);
+    } else {
+        BORROWER_SCALED_DEBT.save(storage, key, &scaled_debt)?;
+    }
+
+    Ok(scaled_debt)
+}
+
+/// ## Description
+/// Saves changes of [`FlashLoan`] struct in [`FLASH_LOAN`] storage
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **flash_loan** is a struct of type [`FlashLoan`] to be stored
+pub fn store_flash_loan(storage: &mut dyn Storage, flash_loan: &FlashLoan) -> StdResult<()> {
+    FLASH_LOAN.save(storage, flash_loan)
+}
+
+/// ## Description
+/// Returns struct of type [`FlashLoan`] if a flash loan is ongoing
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+pub fn may_load_flash_loan(storage: &dyn Storage) -> StdResult<Option<FlashLoan>> {
+    FLASH_LOAN.may_load(storage)
+}
+
+/// ## Description
+/// Remove the ongoing flash loan in [`FLASH_LOAN`] storage
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+pub fn remove_flash_loan(storage: &mut dyn Storage) {
+    FLASH_LOAN.remove(storage)
+}
+
+/// ## Description
+/// Saves changes of [`Loan`] struct in [`LOAN`] storage
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **loan** is a struct of type [`Loan`] to be stored
+pub fn store_loan(storage: &mut dyn Storage, loan: &Loan) -> StdResult<()> {
+    if let Some(stored) = LOAN.may_load(storage, U64Key::from(loan.id))? {
+        LOAN_BY_DEBT_RATIO.remove(
+            storage,
+            (
+                stored.asset_info.as_bytes(),
+                &debt_ratio_key(stored.debt_ratio(), stored.id),
+            ),
+        );
+    }
+    LOAN_BY_DEBT_RATIO.save(
+        storage,
+        (
+            loan.asset_info.as_bytes(),
+            &debt_ratio_key(loan.debt_ratio(), loan.id),
+        ),
+        &loan.id,
+    )?;
+    LOAN.save(storage, U64Key::from(loan.id), loan)
+}
+
+/// ## Description
+/// Returns struct of type [`Loan`] if exists
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **id** is [`u64`]
+pub fn may_load_loan(storage: &dyn Storage, id: u64) -> StdResult<Option<Loan>> {
+    LOAN.may_load(storage, U64Key::from(id))
+}
+
+/// ## Description
+/// Remove an object of type [`Loan`] in [`LOAN`] storage
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **id** is [`u64`]
+pub fn remove_loan(storage: &mut dyn Storage, id: u64) -> StdResult<()> {
+    if let Some(stored) = LOAN.may_load(storage, U64Key::from(id))? {
+        LOAN_BY_DEBT_RATIO.remove(
+            storage,
+            (
+                stored.asset_info.as_bytes(),
+                &debt_ratio_key(stored.debt_ratio(), stored.id),
+            ),
+        );
+    }
+    LOAN.remove(storage, U64Key::from(id));
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns an iterator over ids of pooled loans of a reserve from the highest scaled debt per
+/// unit of collateral to the lowest, which is also the order they become liquidatable in
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **asset_info** is an object of type [`AssetInfo`]
+///
+/// * **start_after** is an [`Option`] of debt ratio and id, the loan to start after
+pub fn read_loans_by_debt_ratio<'a>(
+    storage: &'a dyn Storage,
+    asset_info: &AssetInfo,
+    start_after: Option<(Decimal256, u64)>,
+) -> impl Iterator<Item = StdResult<u64>> + 'a {
+    LOAN_BY_DEBT_RATIO
+        .prefix(asset_info.as_bytes())
+        .range(
+            storage,
+            None,
+            start_after.map(|(ratio, id)| Bound::exclusive(debt_ratio_key(ratio, id))),
+            Order::Descending,
+        )
+        .map(|item| item.map(|(_, id)| id))
+}
+
+/// Returns the key of the debt ratio index, which sorts by scaled debt per unit of collateral
+/// and then by id
+fn debt_ratio_key(ratio: Decimal256, id: u64) -> Vec<u8> {
+    [
+        ratio.numerator().to_be_bytes().as_slice(),
+        id.to_be_bytes().as_slice(),
+    ]
+    .concat()
+}
+
+/// ## Description
+/// Saves the port of the counterpart contract allowed over a connection in
+/// [`IBC_COUNTERPARTY`] storage
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **connection_id** is an object of type [`str`]
+///
+/// * **port_id** is an object of type [`str`]
+pub fn store_ibc_counterparty(
+    storage: &mut dyn Storage,
+    connection_id: &str,
+    port_id: &str,
+) -> StdResult<()> {
+    IBC_COUNTERPARTY.save(storage, connection_id, &port_id.to_string())
+}
+
+/// ## Description
+/// Returns the port of the counterpart contract allowed over a connection if exists
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **connection_id** is an object of type [`str`]
+pub fn may_load_ibc_counterparty(
+    storage: &dyn Storage,
+    connection_id: &str,
+) -> StdResult<Option<String>> {
+    IBC_COUNTERPARTY.may_load(storage, connection_id)
+}
+
+/// ## Description
+/// Remove the counterpart contract allowed over a connection in [`IBC_COUNTERPARTY`] storage
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **connection_id** is an object of type [`str`]
+pub fn remove_ibc_counterparty(storage: &mut dyn Storage, connection_id: &str) {
+    IBC_COUNTERPARTY.remove(storage, connection_id)
+}
+
+/// ## Description
+/// Saves a connected channel in [`IBC_CHANNEL`] storage
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **channel_id** is an object of type [`str`]
+///
+/// * **connection_id** is an object of type [`str`]
+pub fn store_ibc_channel(
+    storage: &mut dyn Storage,
+    channel_id: &str,
+    connection_id: &str,
+) -> StdResult<()> {
+    IBC_CHANNEL.save(storage, channel_id, &connection_id.to_string())
+}
+
+/// ## Description
+/// Returns the connection id of a connected channel if exists
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **channel_id** is an object of type [`str`]
+pub fn may_load_ibc_channel(storage: &dyn Storage, channel_id: &str) -> StdResult<Option<String>> {
+    IBC_CHANNEL.may_load(storage, channel_id)
+}
+
+/// ## Description
+/// Remove a closed channel in [`IBC_CHANNEL`] storage
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **channel_id** is an object of type [`str`]
+pub fn remove_ibc_channel(storage: &mut dyn Storage, channel_id: &str) {
+    IBC_CHANNEL.remove(storage, channel_id)
+}
+
+/// ## Description
+/// Saves changes of [`RemoteCollateral`] struct in [`REMOTE_COLLATERAL`] storage
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **id** is [`u64`]
+///
+/// * **remote_collateral** is a struct of type [`RemoteCollateral`] to be stored
+pub fn store_remote_collateral(
+    storage: &mut dyn Storage,
+    id: u64,
+    remote_collateral: &RemoteCollateral,
+) -> StdResult<()> {
+    REMOTE_COLLATERAL.save(storage, U64Key::from(id), remote_collateral)
+}
+
+/// ## Description
+/// Returns struct of type [`RemoteCollateral`] if the borrow request is backed by
+/// collateral on a remote chain
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **id** is [`u64`]
+pub fn may_load_remote_collateral(
+    storage: &dyn Storage,
+    id: u64,
+) -> StdResult<Option<RemoteCollateral>> {
+    REMOTE_COLLATERAL.may_load(storage, U64Key::from(id))
+}
+
+/// ## Description
+/// Remove an object of type [`RemoteCollateral`] in [`REMOTE_COLLATERAL`] storage
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **id** is [`u64`]
+pub fn remove_remote_collateral(storage: &mut dyn Storage, id: u64) {
+    REMOTE_COLLATERAL.remove(storage, U64Key::from(id))
+}
+
+/// ## Description
+/// Saves changes of [`PendingRelease`] struct in [`PENDING_RELEASE`] storage
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **pending_release** is a struct of type [`PendingRelease`] to be stored
+pub fn store_pending_release(
+    storage: &mut dyn Storage,
+    pending_release: &PendingRelease,
+) -> StdResult<()> {
+    PENDING_RELEASE.save(
+        storage,
+        U64Key::from(pending_release.borrow_request.id),
+        pending_release,
+    )
+}
+
+/// ## Description
+/// Returns struct of type [`PendingRelease`] if exists
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **id** is [`u64`]
+pub fn may_load_pending_release(
+    storage: &dyn Storage,
+    id: u64,
+) -> StdResult<Option<PendingRelease>> {
+    PENDING_RELEASE.may_load(storage, U64Key::from(id))
+}
+
+/// ## Description
+/// Remove an object of type [`PendingRelease`] in [`PENDING_RELEASE`] storage
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **id** is [`u64`]
+pub fn remove_pending_release(storage: &mut dyn Storage, id: u64) {
+    PENDING_RELEASE.remove(storage, U64Key::from(id))
+}
+
+/// ## Description
+/// Saves [`Payout`] struct in [`PAYOUT`] storage under a new id and returns the id
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **payout** is a struct of type [`Payout`] to be stored
+pub fn store_payout(storage: &mut dyn Storage, payout: &Payout) -> StdResult<u64> {
+    let id = PAYOUT_COUNT.may_load(storage)?.unwrap_or_default() + 1;
+    PAYOUT_COUNT.save(storage, &id)?;
+    PAYOUT.save(storage, U64Key::from(id), payout)?;
+
+    Ok(id)
+}
+
+/// ## Description
+/// Returns struct of type [`Payout`] if exists
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **id** is [`u64`]
+pub fn may_load_payout(storage: &dyn Storage, id: u64) -> StdResult<Option<Payout>> {
+    PAYOUT.may_load(storage, U64Key::from(id))
+}
+
+/// ## Description
+/// Remove an object of type [`Payout`] in [`PAYOUT`] storage
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **id** is [`u64`]
+pub fn remove_payout(storage: &mut dyn Storage, id: u64) {
+    PAYOUT.remove(storage, U64Key::from(id))
+}
+
+/// ## Description
+/// Adds an asset to what the recipient can claim in [`CLAIMABLE`] storage
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **recipient** is an object of type [`CanonicalAddr`]
+///
+/// * **asset** is an object of type [`Asset`]
+pub fn add_claimable(
+    storage: &mut dyn Storage,
+    recipient: &CanonicalAddr,
+    asset: &Asset,
+) -> StdResult<()> {
+    CLAIMABLE.update(
+        storage,
+        (recipient.as_slice(), asset.info.as_bytes()),
+        |claimable| -> StdResult<Asset> {
+            let mut claimable = claimable.unwrap_or(Asset {
+                info: asset.info.clone(),
+                amount: Uint128::zero(),
+            });
+            claimable.amount = claimable.amount.checked_add(asset.amount)?;
+            Ok(claimable)
+        },
+    )?;
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns every asset the recipient can claim
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **recipient** is an object of type [`CanonicalAddr`]
+pub fn read_claimable(storage: &dyn Storage, recipient: &CanonicalAddr) -> StdResult<Vec<Asset>> {
+    CLAIMABLE
+        .prefix(recipient.as_slice())
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, asset)| asset))
+        .collect()
+}
+
+/// ## Description
+/// Remove an object of type [`Asset`] the recipient can claim in [`CLAIMABLE`] storage
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **recipient** is an object of type [`CanonicalAddr`]
+///
+/// * **asset_info** is an object of type [`AssetInfo`]
+pub fn remove_claimable(
+    storage: &mut dyn Storage,
+    recipient: &CanonicalAddr,
+    asset_info: &AssetInfo,
+) {
+    CLAIMABLE.remove(storage, (recipient.as_slice(), asset_info.as_bytes()))
+}
+
+/// ## Description
+/// Updates the [`AssetStats`] of an asset in [`ASSET_STATS`] storage with the given action,
+/// starting from empty statistics if the asset has none
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **asset_info** is an object of type [`AssetInfo`]
+///
+/// * **action** is a function updating the statistics
+pub fn update_asset_stats<A>(
+    storage: &mut dyn Storage,
+    asset_info: &AssetInfo,
+    action: A,
+) -> StdResult<AssetStats>
+where
+    A: FnOnce(&mut AssetStats) -> StdResult<()>,
+{
+    ASSET_STATS.update(storage, asset_info.as_bytes(), |stats| {
+        let mut stats = stats.unwrap_or_else(|| AssetStats::new(asset_info.clone()));
+        action(&mut stats)?;
+        Ok(stats)
+    })
+}
+
+/// ## Description
+/// Returns statistics of every asset that has any
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+pub fn read_asset_stats(storage: &dyn Storage) -> StdResult<Vec<AssetStats>> {
+    ASSET_STATS
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, stats)| stats))
+        .collect()
+}
+
+/// ## Description
+/// Adds collateral to the escrowed total of its asset in [`ASSET_STATS`] storage
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **collateral** is an object of type [`Asset`]
+pub fn add_collateral_escrowed(storage: &mut dyn Storage, collateral: &Asset) -> StdResult<()> {
+    update_asset_stats(storage, &collateral.info, |stats| {
+        stats.collateral_escrowed = stats.collateral_escrowed.checked_add(collateral.amount)?;
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+/// ## Description
+/// Removes collateral from the escrowed total of its asset in [`ASSET_STATS`] storage
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **collateral** is an object of type [`Asset`]
+pub fn sub_collateral_escrowed(storage: &mut dyn Storage, collateral: &Asset) -> StdResult<()> {
+    update_asset_stats(storage, &collateral.info, |stats| {
+        stats.collateral_escrowed = stats.collateral_escrowed.checked_sub(collateral.amount)?;
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+/// ## Description
+/// Adds the collateral of a borrow request to the escrowed total, in
+/// [`REMOTE_COLLATERAL_STATS`] storage if it is locked on a remote chain and in
+/// [`ASSET_STATS`] storage otherwise
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **id** is [`u64`]
+///
+/// * **collateral** is an object of type [`Asset`]
+pub fn add_request_collateral_escrowed(
+    storage: &mut dyn Storage,
+    id: u64,
+    collateral: &Asset,
+) -> StdResult<()> {
+    let remote = match may_load_remote_collateral(storage, id)? {
+        Some(remote) => remote,
+        None => return add_collateral_escrowed(storage, collateral),
+    };
+
+    REMOTE_COLLATERAL_STATS.update(
+        storage,
+        (
+            remote.channel_id.as_bytes(),
+            remote.collateral.denom.as_bytes(),
+        ),
+        |stats| -> StdResult<_> {
+            let mut stats = stats.unwrap_or(RemoteCollateralStats {
+                channel_id: remote.channel_id.clone(),
+                denom: remote.collateral.denom.clone(),
+                collateral_escrowed: Uint128::zero(),
+            });
+            stats.collateral_escrowed = stats.collateral_escrowed.checked_add(collateral.amount)?;
+            Ok(stats)
+        },
+    )?;
+
+    Ok(())
+}
+
+/// ## Description
+/// Removes the collateral of a borrow request from the escrowed total, in
+/// [`REMOTE_COLLATERAL_STATS`] storage if it is locked on a remote chain and in
+/// [`ASSET_STATS`] storage otherwise
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+///
+/// * **id** is [`u64`]
+///
+/// * **collateral** is an object of type [`Asset`]
+pub fn sub_request_collateral_escrowed(
+    storage: &mut dyn Storage,
+    id: u64,
+    collateral: &Asset,
+) -> StdResult<()> {
+    let remote = match may_load_remote_collateral(storage, id)? {
+        Some(remote) => remote,
+        None => return sub_collateral_escrowed(storage, collateral),
+    };
+
+    REMOTE_COLLATERAL_STATS.update(
+        storage,
+        (
+            remote.channel_id.as_bytes(),
+            remote.collateral.denom.as_bytes(),
+        ),
+        |stats| -> StdResult<_> {
+            let mut stats = stats.ok_or_else(|| StdError::not_found("RemoteCollateralStats"))?;
+            stats.collateral_escrowed = stats.collateral_escrowed.checked_sub(collateral.amount)?;
+            Ok(stats)
+        },
+    )?;
+
+    Ok(())
+}
+
+/// ## Description
+/// Returns statistics of collateral escrowed on every remote chain that has any
+/// ## Params
+/// * **storage** is an object of type [`Storage`]
+pub fn read_remote_collateral_stats(
+    storage: &dyn Storage,
+) -> StdResult<Vec<RemoteCollateralStats>> {
+    REMOTE_COLLATERAL_STATS
+        .range(storage, None, None, Order::Ascending)
+        .map(|item| item.map(|(_, stats)| stats))
+        .collect()
+}
+
+/// ## Description
+/// This structure describes a storage which keeps writes in memory on top of another
+/// storage until they are committed, so that the writes of a failed operation can be
+/// discarded
+pub struct StorageCache<'a> {
+    storage: &'a mut dyn Storage,
+    // Pending writes, with [`None`] for removed keys
+    writes: BTreeMap<Vec<u8>, Option<Vec<u8>>>,
+}
+
+impl<'a> StorageCache<'a> {
+    /// ## Description
+    /// Returns a cache without any pending write over the given storage
+    /// ## Params
+    /// * **storage** is an object of type [`Storage`]
+    pub fn new(storage: &'a mut dyn Storage) -> Self {
+        StorageCache {
+            storage,
+            writes: BTreeMap::new(),
+        }
+    }
+
+    /// ## Description
+    /// Applies the pending writes to the underlying storage
+    pub fn commit(self) {
+        for (key, value) in self.writes {
+            match value {
+                Some(value) => self.storage.set(&key, &value),
+                None => self.storage.remove(&key),
+            }
+        }
+    }
+}
+
+impl Storage for StorageCache<'_> {
+    fn get(&self, key: &[u8]) -> Option<Vec<u8>> {
+        match self.writes.get(key) {
+            Some(value) => value.clone(),
+            None => self.storage.get(key),
+        }
+    }
+
+    fn range<'b>(
+        &'b self,
+        start: Option<&[u8]>,
+        end: Option<&[u8]>,
+        order: Order,
+    ) -> Box<dyn Iterator<Item = Pair> + 'b> {
+        let mut pairs: BTreeMap<Vec<u8>, Vec<u8>> =
+            self.storage.range(start, end, Order::Ascending).collect();
+        for (key, value) in self.writes.iter() {
+            if matches!(start, Some(start) if key.as_slice() < start)
+                || matches!(end, Some(end) if key.as_slice() >= end)
+            {
+                continue;
+            }
+            match value {
+                Some(value) => pairs.insert(key.clone(), value.clone()),
+                None => pairs.remove(key),
+            };
+        }
+
+        match order {
+            Order::Ascending => Box::new(pairs.into_iter()),
+            Order::Descending => Box::new(pairs.into_iter().rev()),
+        }
+    }
+
+    fn set(&mut self, key: &[u8], value: &[u8]) {
+        self.writes.insert(key.to_vec(), Some(value.to_vec()));
+    }
+
+    fn remove(&mut self, key: &[u8]) {
+        self.writes.insert(key.to_vec(), None);
+    }
 }
@@ -1,26 +1,77 @@
+use std::fs::{read_dir, read_to_string};
+use std::path::PathBuf;
+
 use astroport::asset::{Asset, AssetInfo};
 use cosmwasm_std::{
     from_binary,
-    testing::{mock_dependencies, mock_env, mock_info},
-    to_binary, Addr, Coin, Uint128,
+    testing::{
+        mock_dependencies, mock_env, mock_ibc_channel_close_init, mock_ibc_channel_connect_ack,
+        mock_ibc_channel_open_try, mock_ibc_packet_ack, mock_ibc_packet_recv,
+        mock_ibc_packet_timeout, mock_info, MOCK_CONTRACT_ADDR,
+    },
+    to_binary, Addr, Api, Attribute, BankMsg, Binary, CanonicalAddr, Coin, ContractResult,
+    CosmosMsg, Decimal256, Deps, DepsMut, Env, Event, IbcAcknowledgement, IbcMsg, IbcOrder, Reply,
+    ReplyOn, Response, StdError, SubMsg, SubMsgExecutionResponse, Timestamp, Uint128, Uint256,
+    WasmMsg,
 };
-use cw20::Cw20ReceiveMsg;
+use cw0::{Duration, Expiration};
+use cw2::{get_contract_version, set_contract_version, ContractVersion};
+use cw20::{Cw20ExecuteMsg, Cw20ReceiveMsg};
+use cw_storage_plus::Map;
+use serde_json::json;
 
 use crate::{
-    contract::{execute, instantiate, query},
+    commands::{INSTANTIATE_SHARE_TOKEN_REPLY_ID, PAYOUT_REPLY_ID_OFFSET},
+    contract::{execute, instantiate, migrate, query, reply},
     error::ContractError,
+    ibc::{
+        ibc_channel_close, ibc_channel_connect, ibc_channel_open, ibc_packet_ack,
+        ibc_packet_receive, ibc_packet_timeout,
+    },
+    interest::{accrued_interest, InterestModel, RateCurve},
+    schema::schemas,
+    state::{
+        store_borrow_request, store_borrow_request_id_to_addr, store_loan, BorrowRequest, Loan,
+    },
 };
 
+use services::lending::events::{
+    BorrowEvent, BorrowRequestEvent, FlashLoanEvent, LendingEvent, LiquidateEvent, LiquidityEvent,
+    PayoutEvent, RepayEvent, ATTR_AMOUNT, ATTR_BORROWER, ATTR_COLLATERAL_CONTRACT,
+    ATTR_COLLATERAL_DENOM, ATTR_EXPIRES_AT_HEIGHT, ATTR_ID, ATTR_LENDER, ATTR_LOAN_ID,
+    ATTR_PERIOD_HEIGHT, ATTR_PERIOD_TIME, EVENT_BORROW_REQUEST_CANCELLED,
+    EVENT_BORROW_REQUEST_CREATED, EVENT_REPAID,
+};
 use services::lending::{
-    BorrowRequestResponse, Cw20HookMsg, ExecuteMsg, InstantiateMsg, QueryMsg, StateResponse,
+    AssetStatsResponse, BatchMsg, BorrowRequestResponse, BorrowerProfileResponse, Cw20HookMsg,
+    ExecuteMsg, IbcHookMsg, InstantiateMsg, LendingAck, LendingPacket, LoanCursor,
+    LoanHealthResponse, LoanResponse, MigrateMsg, QueryMsg, RemoteCollateralStatsResponse,
+    ReserveConfig, ReserveResponse, SimulateLiquidationResponse, SimulateRepayResponse,
+    StateResponse, StatsResponse, IBC_VERSION,
 };
 
 /// Mock address 0 used for test.
 const MOCK_ADDRESS_0: &str = "mock_address_0";
+/// Mock address 1 used for test.
+const MOCK_ADDRESS_1: &str = "mock_address_1";
+/// Mock address 2 used for test.
+const MOCK_ADDRESS_2: &str = "mock_address_2";
 /// Mock CW20 token address 0 used for test.
 const MOCK_CW20_TOKEN_0: &str = "cw20_token_0";
 /// Mock native token address 0 used for test.
 const MOCK_NATIVE_TOKEN_0: &str = "native_token_0";
+/// Mock native token address 1 used for test.
+const MOCK_NATIVE_TOKEN_1: &str = "native_token_1";
+/// Mock share token address 0 used for test.
+const MOCK_SHARE_TOKEN_0: &str = "share_token_0";
+/// Mock IBC channel id 0 used for test.
+const MOCK_CHANNEL_0: &str = "channel-0";
+/// Mock connection id of mock IBC channels used for test.
+const MOCK_CONNECTION_0: &str = "connection-2";
+/// Mock counterparty port id of mock IBC channels used for test.
+const MOCK_COUNTERPARTY_PORT_0: &str = "their_port";
+/// Mock CW20 code id used for test.
+const MOCK_TOKEN_CODE_ID: u64 = 10u64;
 
 /// ## Description
 /// Test if instantiation works properly
@@ -31,7 +82,9 @@ const MOCK_NATIVE_TOKEN_0: &str = "native_token_0";
 fn instantiate_contract() {
     let mut deps = mock_dependencies(&[]);
     let info = mock_info(MOCK_ADDRESS_0, &[]);
-    let msg = InstantiateMsg {};
+    let msg = InstantiateMsg {
+        token_code_id: MOCK_TOKEN_CODE_ID,
+    };
 
     instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
@@ -43,6 +96,7 @@ fn instantiate_contract() {
         StateResponse {
             request_count: 0u64,
             response_count: 0u64,
+            loan_count: 0u64,
         },
     );
 }
@@ -56,18 +110,25 @@ fn instantiate_contract() {
 fn make_borrow_request_test_case_0() {
     let mut deps = mock_dependencies(&[]);
     let info = mock_info(MOCK_ADDRESS_0, &[]);
-    let msg = InstantiateMsg {};
+    let msg = InstantiateMsg {
+        token_code_id: MOCK_TOKEN_CODE_ID,
+    };
 
     instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
     let amount = Uint128::from(1000u128);
-    let period = 17200u64;
+    let period = Duration::Height(17200u64);
 
     let info = mock_info(MOCK_CW20_TOKEN_0, &[]);
-    let msg = ExecuteMsg::MakeBorrowRequestCw20Token(Cw20ReceiveMsg {
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: MOCK_ADDRESS_0.to_string(),
         amount,
-        msg: to_binary(&Cw20HookMsg::MakeBorrowRequest { period }).unwrap(),
+        msg: to_binary(&Cw20HookMsg::MakeBorrowRequest {
+            period,
+            lender: None,
+            expires_at: None,
+        })
+        .unwrap(),
     });
 
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -79,6 +140,7 @@ fn make_borrow_request_test_case_0() {
                 mock_env(),
                 QueryMsg::BorrowRequestByAddr {
                     borrower: MOCK_ADDRESS_0.to_string(),
+                    include_directed: None,
                 },
             )
             .unwrap(),
@@ -93,8 +155,14 @@ fn make_borrow_request_test_case_0() {
                 amount,
             },
             period,
+            lender: None,
+            expires_at: None,
+            expired: false,
             borrowed_from: 0u64,
             borrowed_at: 0u64,
+            borrowed_at_time: 0u64,
+            deadline: None,
+            remaining: None,
         }]
     );
 }
@@ -108,18 +176,25 @@ fn make_borrow_request_test_case_0() {
 fn make_borrow_request_test_case_1() {
     let mut deps = mock_dependencies(&[]);
     let info = mock_info(MOCK_ADDRESS_0, &[]);
-    let msg = InstantiateMsg {};
+    let msg = InstantiateMsg {
+        token_code_id: MOCK_TOKEN_CODE_ID,
+    };
 
     instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
     let amount = Uint128::from(0u128);
-    let period = 17200u64;
+    let period = Duration::Height(17200u64);
 
     let info = mock_info(MOCK_CW20_TOKEN_0, &[]);
-    let msg = ExecuteMsg::MakeBorrowRequestCw20Token(Cw20ReceiveMsg {
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: MOCK_ADDRESS_0.to_string(),
         amount,
-        msg: to_binary(&Cw20HookMsg::MakeBorrowRequest { period }).unwrap(),
+        msg: to_binary(&Cw20HookMsg::MakeBorrowRequest {
+            period,
+            lender: None,
+            expires_at: None,
+        })
+        .unwrap(),
     });
 
     let res = execute(deps.as_mut(), mock_env(), info, msg);
@@ -139,18 +214,25 @@ fn make_borrow_request_test_case_1() {
 fn make_borrow_request_test_case_2() {
     let mut deps = mock_dependencies(&[]);
     let info = mock_info(MOCK_ADDRESS_0, &[]);
-    let msg = InstantiateMsg {};
+    let msg = InstantiateMsg {
+        token_code_id: MOCK_TOKEN_CODE_ID,
+    };
 
     instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
     let amount = Uint128::from(1000u128);
-    let period = 0u64;
+    let period = Duration::Height(0u64);
 
     let info = mock_info(MOCK_CW20_TOKEN_0, &[]);
-    let msg = ExecuteMsg::MakeBorrowRequestCw20Token(Cw20ReceiveMsg {
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: MOCK_ADDRESS_0.to_string(),
         amount,
-        msg: to_binary(&Cw20HookMsg::MakeBorrowRequest { period }).unwrap(),
+        msg: to_binary(&Cw20HookMsg::MakeBorrowRequest {
+            period,
+            lender: None,
+            expires_at: None,
+        })
+        .unwrap(),
     });
 
     let res = execute(deps.as_mut(), mock_env(), info, msg);
@@ -170,12 +252,14 @@ fn make_borrow_request_test_case_2() {
 fn make_borrow_request_test_case_3() {
     let mut deps = mock_dependencies(&[]);
     let info = mock_info(MOCK_ADDRESS_0, &[]);
-    let msg = InstantiateMsg {};
+    let msg = InstantiateMsg {
+        token_code_id: MOCK_TOKEN_CODE_ID,
+    };
 
     instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
     let amount = Uint128::from(1000u128);
-    let period = 17200u64;
+    let period = Duration::Height(17200u64);
 
     let info = mock_info(
         MOCK_ADDRESS_0,
@@ -184,7 +268,11 @@ fn make_borrow_request_test_case_3() {
             amount,
         }],
     );
-    let msg = ExecuteMsg::MakeBorrowRequestNativeToken { period };
+    let msg = ExecuteMsg::MakeBorrowRequestNativeToken {
+        period,
+        lender: None,
+        expires_at: None,
+    };
 
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
@@ -195,6 +283,7 @@ fn make_borrow_request_test_case_3() {
                 mock_env(),
                 QueryMsg::BorrowRequestByAddr {
                     borrower: MOCK_ADDRESS_0.to_string(),
+                    include_directed: None,
                 },
             )
             .unwrap(),
@@ -209,8 +298,14 @@ fn make_borrow_request_test_case_3() {
                 amount,
             },
             period,
+            lender: None,
+            expires_at: None,
+            expired: false,
             borrowed_from: 0u64,
             borrowed_at: 0u64,
+            borrowed_at_time: 0u64,
+            deadline: None,
+            remaining: None,
         }]
     );
 }
@@ -224,12 +319,14 @@ fn make_borrow_request_test_case_3() {
 fn make_borrow_request_test_case_4() {
     let mut deps = mock_dependencies(&[]);
     let info = mock_info(MOCK_ADDRESS_0, &[]);
-    let msg = InstantiateMsg {};
+    let msg = InstantiateMsg {
+        token_code_id: MOCK_TOKEN_CODE_ID,
+    };
 
     instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
     let amount = Uint128::from(0u128);
-    let period = 17200u64;
+    let period = Duration::Height(17200u64);
 
     let info = mock_info(
         MOCK_ADDRESS_0,
@@ -238,7 +335,11 @@ fn make_borrow_request_test_case_4() {
             amount,
         }],
     );
-    let msg = ExecuteMsg::MakeBorrowRequestNativeToken { period };
+    let msg = ExecuteMsg::MakeBorrowRequestNativeToken {
+        period,
+        lender: None,
+        expires_at: None,
+    };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg);
 
@@ -257,12 +358,14 @@ fn make_borrow_request_test_case_4() {
 fn make_borrow_request_test_case_5() {
     let mut deps = mock_dependencies(&[]);
     let info = mock_info(MOCK_ADDRESS_0, &[]);
-    let msg = InstantiateMsg {};
+    let msg = InstantiateMsg {
+        token_code_id: MOCK_TOKEN_CODE_ID,
+    };
 
     instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
     let amount = Uint128::from(1000u128);
-    let period = 0u64;
+    let period = Duration::Height(0u64);
 
     let info = mock_info(
         MOCK_ADDRESS_0,
@@ -271,7 +374,11 @@ fn make_borrow_request_test_case_5() {
             amount,
         }],
     );
-    let msg = ExecuteMsg::MakeBorrowRequestNativeToken { period };
+    let msg = ExecuteMsg::MakeBorrowRequestNativeToken {
+        period,
+        lender: None,
+        expires_at: None,
+    };
 
     let res = execute(deps.as_mut(), mock_env(), info, msg);
 
@@ -290,18 +397,25 @@ fn make_borrow_request_test_case_5() {
 fn make_borrow_request_test_case_6() {
     let mut deps = mock_dependencies(&[]);
     let info = mock_info(MOCK_ADDRESS_0, &[]);
-    let msg = InstantiateMsg {};
+    let msg = InstantiateMsg {
+        token_code_id: MOCK_TOKEN_CODE_ID,
+    };
 
     instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
 
     let amount = Uint128::from(1000u128);
-    let period = 17200u64;
+    let period = Duration::Height(17200u64);
 
     let info = mock_info(MOCK_CW20_TOKEN_0, &[]);
-    let msg = ExecuteMsg::MakeBorrowRequestCw20Token(Cw20ReceiveMsg {
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
         sender: MOCK_ADDRESS_0.to_string(),
         amount,
-        msg: to_binary(&Cw20HookMsg::MakeBorrowRequest { period }).unwrap(),
+        msg: to_binary(&Cw20HookMsg::MakeBorrowRequest {
+            period,
+            lender: None,
+            expires_at: None,
+        })
+        .unwrap(),
     });
 
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
@@ -313,7 +427,11 @@ fn make_borrow_request_test_case_6() {
             amount,
         }],
     );
-    let msg = ExecuteMsg::MakeBorrowRequestNativeToken { period };
+    let msg = ExecuteMsg::MakeBorrowRequestNativeToken {
+        period,
+        lender: None,
+        expires_at: None,
+    };
 
     let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
 
@@ -324,6 +442,7 @@ fn make_borrow_request_test_case_6() {
                 mock_env(),
                 QueryMsg::BorrowRequestByAddr {
                     borrower: MOCK_ADDRESS_0.to_string(),
+                    include_directed: None,
                 },
             )
             .unwrap(),
@@ -339,8 +458,14 @@ fn make_borrow_request_test_case_6() {
                     amount,
                 },
                 period,
+                lender: None,
+                expires_at: None,
+                expired: false,
                 borrowed_from: 0u64,
                 borrowed_at: 0u64,
+                borrowed_at_time: 0u64,
+                deadline: None,
+                remaining: None,
             },
             BorrowRequestResponse {
                 id: 2u64,
@@ -351,9 +476,4786 @@ fn make_borrow_request_test_case_6() {
                     amount,
                 },
                 period,
+                lender: None,
+                expires_at: None,
+                expired: false,
                 borrowed_from: 0u64,
                 borrowed_at: 0u64,
+                borrowed_at_time: 0u64,
+                deadline: None,
+                remaining: None,
             }
         ]
     );
 }
+
+/// ## Description
+/// Test if making borrow request works properly
+/// ## Test case 7
+/// Input: Native token with positive amount and positive time period
+/// Output: BorrowRequest struct with the given time period
+#[test]
+fn make_borrow_request_test_case_7() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info(MOCK_ADDRESS_0, &[]);
+    let msg = InstantiateMsg {
+        token_code_id: MOCK_TOKEN_CODE_ID,
+    };
+
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let amount = Uint128::from(1000u128);
+    let period = Duration::Time(604800u64);
+
+    let info = mock_info(
+        MOCK_ADDRESS_0,
+        &[Coin {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            amount,
+        }],
+    );
+    let msg = ExecuteMsg::MakeBorrowRequestNativeToken {
+        period,
+        lender: None,
+        expires_at: None,
+    };
+
+    let _res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    assert_eq!(
+        from_binary::<BorrowRequestResponse>(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::BorrowRequestById { id: 1u64 },
+            )
+            .unwrap(),
+        )
+        .unwrap(),
+        BorrowRequestResponse {
+            id: 1u64,
+            collateral: Asset {
+                info: AssetInfo::NativeToken {
+                    denom: MOCK_NATIVE_TOKEN_0.to_string(),
+                },
+                amount,
+            },
+            period,
+            lender: None,
+            expires_at: None,
+            expired: false,
+            borrowed_from: 0u64,
+            borrowed_at: 0u64,
+            borrowed_at_time: 0u64,
+            deadline: None,
+            remaining: None,
+        }
+    );
+}
+
+/// ## Description
+/// Test if making borrow request works properly
+/// ## Test case 8
+/// Input: Native token with zero time period
+/// Output: Error
+#[test]
+fn make_borrow_request_test_case_8() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info(MOCK_ADDRESS_0, &[]);
+    let msg = InstantiateMsg {
+        token_code_id: MOCK_TOKEN_CODE_ID,
+    };
+
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let amount = Uint128::from(1000u128);
+    let period = Duration::Time(0u64);
+
+    let info = mock_info(
+        MOCK_ADDRESS_0,
+        &[Coin {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            amount,
+        }],
+    );
+    let msg = ExecuteMsg::MakeBorrowRequestNativeToken {
+        period,
+        lender: None,
+        expires_at: None,
+    };
+
+    let res = execute(deps.as_mut(), mock_env(), info, msg);
+
+    match res {
+        Err(ContractError::InvalidPeriod {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+/// ## Description
+/// Test if making borrow request works properly
+/// ## Test case 9
+/// Input: Public request and request directed to mock address 1
+/// Output: Directed request listed only when asked for
+#[test]
+fn make_borrow_request_test_case_9() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info(MOCK_ADDRESS_0, &[]);
+    let msg = InstantiateMsg {
+        token_code_id: MOCK_TOKEN_CODE_ID,
+    };
+
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info(
+        MOCK_ADDRESS_0,
+        &[Coin {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    );
+    for lender in [None, Some(MOCK_ADDRESS_1.to_string())] {
+        let msg = ExecuteMsg::MakeBorrowRequestNativeToken {
+            period: Duration::Height(17200u64),
+            lender,
+            expires_at: None,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    }
+
+    let public = from_binary::<Vec<BorrowRequestResponse>>(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BorrowRequests {
+                start_after: None,
+                limit: None,
+                include_directed: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        public.iter().map(|br| br.id).collect::<Vec<u64>>(),
+        vec![1u64]
+    );
+
+    let all = from_binary::<Vec<BorrowRequestResponse>>(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BorrowRequests {
+                start_after: None,
+                limit: None,
+                include_directed: Some(true),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        all.iter().map(|br| br.lender.clone()).collect::<Vec<_>>(),
+        vec![None, Some(MOCK_ADDRESS_1.to_string())]
+    );
+
+    let by_addr = from_binary::<Vec<BorrowRequestResponse>>(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BorrowRequestByAddr {
+                borrower: MOCK_ADDRESS_0.to_string(),
+                include_directed: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(by_addr, public);
+
+    let page = from_binary::<Vec<BorrowRequestResponse>>(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BorrowRequests {
+                start_after: Some(1u64),
+                limit: Some(1u32),
+                include_directed: Some(true),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(page, vec![all[1].clone()]);
+}
+
+/// ## Description
+/// Test if making borrow request works properly
+/// ## Test case 10
+/// Input: Native token directed to a lender and expiring at a height
+/// Output: Created event with one attribute per field, then cancelled event on cancellation
+#[test]
+fn make_borrow_request_test_case_10() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info(MOCK_ADDRESS_0, &[]);
+    let msg = InstantiateMsg {
+        token_code_id: MOCK_TOKEN_CODE_ID,
+    };
+
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let env = mock_env();
+    let info = mock_info(
+        MOCK_ADDRESS_0,
+        &[Coin {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    );
+    let msg = ExecuteMsg::MakeBorrowRequestNativeToken {
+        period: Duration::Height(17200u64),
+        lender: Some(MOCK_ADDRESS_1.to_string()),
+        expires_at: Some(Expiration::AtHeight(env.block.height + 10u64)),
+    };
+    let res = execute(deps.as_mut(), env.clone(), info, msg).unwrap();
+
+    let event = |ty: &str| {
+        Event::new(ty)
+            .add_attribute(ATTR_ID, "1")
+            .add_attribute(ATTR_BORROWER, MOCK_ADDRESS_0)
+            .add_attribute(ATTR_COLLATERAL_DENOM, MOCK_NATIVE_TOKEN_0)
+            .add_attribute(ATTR_AMOUNT, "1000")
+            .add_attribute(ATTR_PERIOD_HEIGHT, "17200")
+            .add_attribute(ATTR_LENDER, MOCK_ADDRESS_1)
+            .add_attribute(
+                ATTR_EXPIRES_AT_HEIGHT,
+                (env.block.height + 10u64).to_string(),
+            )
+    };
+    assert_eq!(res.events, vec![event(EVENT_BORROW_REQUEST_CREATED)]);
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_0, &[]),
+        ExecuteMsg::CancelBorrowRequest { id: 1u64 },
+    )
+    .unwrap();
+    assert_eq!(res.events, vec![event(EVENT_BORROW_REQUEST_CANCELLED)]);
+
+    let info = mock_info(MOCK_CW20_TOKEN_0, &[]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: MOCK_ADDRESS_0.to_string(),
+        amount: Uint128::from(1000u128),
+        msg: to_binary(&Cw20HookMsg::MakeBorrowRequest {
+            period: Duration::Time(3600u64),
+            lender: None,
+            expires_at: None,
+        })
+        .unwrap(),
+    });
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.events,
+        vec![Event::new(EVENT_BORROW_REQUEST_CREATED)
+            .add_attribute(ATTR_ID, "2")
+            .add_attribute(ATTR_BORROWER, MOCK_ADDRESS_0)
+            .add_attribute(ATTR_COLLATERAL_CONTRACT, MOCK_CW20_TOKEN_0)
+            .add_attribute(ATTR_AMOUNT, "1000")
+            .add_attribute(ATTR_PERIOD_TIME, "3600")]
+    );
+}
+
+/// ## Description
+/// Test if parsing lending events works properly
+/// ## Test case 0
+/// Input: Events of making and pruning a borrow request as emitted on chain along with an
+/// unrelated event, then a lending event missing an attribute
+/// Output: Typed created and expired events, then error
+#[test]
+fn lending_event_test_case_0() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info(MOCK_ADDRESS_0, &[]);
+    let msg = InstantiateMsg {
+        token_code_id: MOCK_TOKEN_CODE_ID,
+    };
+
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let env = mock_env();
+    let info = mock_info(
+        MOCK_ADDRESS_0,
+        &[Coin {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    );
+    let expires_at = Expiration::AtTime(env.block.time.plus_seconds(100u64));
+    let msg = ExecuteMsg::MakeBorrowRequestNativeToken {
+        period: Duration::Time(3600u64),
+        lender: None,
+        expires_at: Some(expires_at),
+    };
+    let mut events = execute(deps.as_mut(), env.clone(), info, msg)
+        .unwrap()
+        .events;
+    let mut later = env;
+    later.block.time = later.block.time.plus_seconds(100u64);
+    events.extend(
+        execute(
+            deps.as_mut(),
+            later,
+            mock_info(MOCK_ADDRESS_1, &[]),
+            ExecuteMsg::PruneExpired { limit: None },
+        )
+        .unwrap()
+        .events,
+    );
+    let mut events: Vec<Event> = events
+        .into_iter()
+        .map(|event| {
+            Event::new(format!("wasm-{}", event.ty))
+                .add_attribute("_contract_address", MOCK_CONTRACT_ADDR)
+                .add_attributes(event.attributes)
+        })
+        .collect();
+    events.insert(
+        1usize,
+        Event::new("transfer").add_attribute(ATTR_AMOUNT, "1000"),
+    );
+
+    let borrow_request = BorrowRequestEvent {
+        id: 1u64,
+        borrower: MOCK_ADDRESS_0.to_string(),
+        collateral: Asset {
+            info: AssetInfo::NativeToken {
+                denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            },
+            amount: Uint128::from(1000u128),
+        },
+        period: Duration::Time(3600u64),
+        lender: None,
+        expires_at: Some(expires_at),
+    };
+    assert_eq!(
+        LendingEvent::parse_events(&events).unwrap(),
+        vec![
+            LendingEvent::BorrowRequestCreated(borrow_request.clone()),
+            LendingEvent::BorrowRequestExpired(borrow_request),
+        ]
+    );
+
+    assert!(LendingEvent::parse(
+        &Event::new(EVENT_BORROW_REQUEST_CREATED).add_attribute(ATTR_ID, "1")
+    )
+    .is_err());
+}
+
+/// ## Description
+/// Test if parsing lending events works properly
+/// ## Test case 1
+/// Input: Events of depositing, borrowing, repaying in two parts and withdrawing as emitted
+/// on chain, then a repay event missing an attribute
+/// Output: Typed deposited, borrowed, repaid and withdrawn events, then error
+#[test]
+fn lending_event_test_case_1() {
+    let mut deps = mock_dependencies(&[]);
+    mock_reserve(deps.as_mut(), 1000u128);
+
+    let native = |denom: &str, amount: u128| Asset {
+        info: AssetInfo::NativeToken {
+            denom: denom.to_string(),
+        },
+        amount: Uint128::from(amount),
+    };
+    let native_info = |denom: &str, amount: u128| {
+        mock_info(
+            MOCK_ADDRESS_2,
+            &[Coin {
+                denom: denom.to_string(),
+                amount: Uint128::from(amount),
+            }],
+        )
+    };
+
+    let mut events = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(
+            MOCK_ADDRESS_1,
+            &[Coin {
+                denom: MOCK_NATIVE_TOKEN_0.to_string(),
+                amount: Uint128::from(1000u128),
+            }],
+        ),
+        ExecuteMsg::DepositNativeToken {},
+    )
+    .unwrap()
+    .events;
+    events.extend(
+        mock_borrow(deps.as_mut(), 500u128, 1000u128)
+            .unwrap()
+            .events,
+    );
+    for amount in [200u128, 300u128] {
+        events.extend(
+            execute(
+                deps.as_mut(),
+                mock_env(),
+                native_info(MOCK_NATIVE_TOKEN_0, amount),
+                ExecuteMsg::RepayNativeToken { id: 1u64 },
+            )
+            .unwrap()
+            .events,
+        );
+    }
+    events.extend(
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MOCK_SHARE_TOKEN_0, &[]),
+            ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: MOCK_ADDRESS_1.to_string(),
+                amount: Uint128::from(1000u128),
+                msg: to_binary(&Cw20HookMsg::Withdraw {}).unwrap(),
+            }),
+        )
+        .unwrap()
+        .events,
+    );
+    let events: Vec<Event> = events
+        .into_iter()
+        .map(|event| {
+            Event::new(format!("wasm-{}", event.ty))
+                .add_attribute("_contract_address", MOCK_CONTRACT_ADDR)
+                .add_attributes(event.attributes)
+        })
+        .collect();
+
+    assert_eq!(
+        LendingEvent::parse_events(&events).unwrap(),
+        vec![
+            LendingEvent::Deposited(LiquidityEvent {
+                lender: MOCK_ADDRESS_1.to_string(),
+                asset: native(MOCK_NATIVE_TOKEN_0, 1000u128),
+                shares: Uint128::from(1000u128),
+            }),
+            LendingEvent::Borrowed(BorrowEvent {
+                loan_id: 1u64,
+                borrower: MOCK_ADDRESS_2.to_string(),
+                asset: native(MOCK_NATIVE_TOKEN_0, 500u128),
+                collateral: native(MOCK_NATIVE_TOKEN_1, 1000u128),
+            }),
+            LendingEvent::Repaid(RepayEvent {
+                loan_id: 1u64,
+                payer: MOCK_ADDRESS_2.to_string(),
+                asset: native(MOCK_NATIVE_TOKEN_0, 200u128),
+                remaining: Uint128::from(300u128),
+            }),
+            LendingEvent::Repaid(RepayEvent {
+                loan_id: 1u64,
+                payer: MOCK_ADDRESS_2.to_string(),
+                asset: native(MOCK_NATIVE_TOKEN_0, 300u128),
+                remaining: Uint128::zero(),
+            }),
+            LendingEvent::Withdrawn(LiquidityEvent {
+                lender: MOCK_ADDRESS_1.to_string(),
+                asset: native(MOCK_NATIVE_TOKEN_0, 1000u128),
+                shares: Uint128::from(1000u128),
+            }),
+        ]
+    );
+
+    assert!(LendingEvent::parse(
+        &Event::new(EVENT_REPAID)
+            .add_attribute(ATTR_LOAN_ID, "1")
+            .add_attribute(ATTR_AMOUNT, "200")
+    )
+    .is_err());
+}
+
+/// ## Description
+/// Test if parsing lending events works properly
+/// ## Test case 2
+/// Input: Liquidation, flash loan and payout events converted to events, with native token
+/// and CW20 token assets, then a liquidation event with both a denom and a contract
+/// Output: The same typed events parsed back, then error
+#[test]
+fn lending_event_test_case_2() {
+    let native = Asset {
+        info: AssetInfo::NativeToken {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+        },
+        amount: Uint128::from(500u128),
+    };
+    let cw20 = Asset {
+        info: AssetInfo::Token {
+            contract_addr: Addr::unchecked(MOCK_CW20_TOKEN_0),
+        },
+        amount: Uint128::from(875u128),
+    };
+    let payout = PayoutEvent {
+        recipient: MOCK_ADDRESS_1.to_string(),
+        asset: cw20.clone(),
+    };
+    let lending_events = vec![
+        LendingEvent::Liquidated(LiquidateEvent {
+            loan_id: 1u64,
+            liquidator: MOCK_ADDRESS_1.to_string(),
+            asset: native.clone(),
+            seized: cw20.clone(),
+        }),
+        LendingEvent::FlashLoaned(FlashLoanEvent {
+            borrower: MOCK_ADDRESS_2.to_string(),
+            asset: cw20,
+            fee: Uint128::from(1u128),
+        }),
+        LendingEvent::PayoutDeferred(payout.clone()),
+        LendingEvent::PendingClaimed(payout),
+    ];
+
+    let events: Vec<Event> = lending_events
+        .iter()
+        .cloned()
+        .map(|event| {
+            let event: Event = event.into();
+            Event::new(format!("wasm-{}", event.ty)).add_attributes(event.attributes)
+        })
+        .collect();
+    assert_eq!(LendingEvent::parse_events(&events).unwrap(), lending_events);
+
+    let event: Event = lending_events[0].clone().into();
+    assert!(
+        LendingEvent::parse(&event.add_attribute(ATTR_COLLATERAL_DENOM, MOCK_NATIVE_TOKEN_1))
+            .is_err()
+    );
+}
+
+/// ## Description
+/// Test if cancelling borrow request works properly
+/// ## Test case 0
+/// Input: Cancel from non-borrower, from borrower and of a cancelled request
+/// Output: Error, then collateral refunded to the borrower, then error
+#[test]
+fn cancel_borrow_request_test_case_0() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info(MOCK_ADDRESS_0, &[]);
+    let msg = InstantiateMsg {
+        token_code_id: MOCK_TOKEN_CODE_ID,
+    };
+
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info(MOCK_CW20_TOKEN_0, &[]);
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: MOCK_ADDRESS_0.to_string(),
+        amount: Uint128::from(1000u128),
+        msg: to_binary(&Cw20HookMsg::MakeBorrowRequest {
+            period: Duration::Height(17200u64),
+            lender: Some(MOCK_ADDRESS_1.to_string()),
+            expires_at: None,
+        })
+        .unwrap(),
+    });
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let msg = ExecuteMsg::CancelBorrowRequest { id: 1u64 };
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_1, &[]),
+        msg.clone(),
+    ) {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_0, &[]),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: MOCK_CW20_TOKEN_0.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: MOCK_ADDRESS_0.to_string(),
+                amount: Uint128::from(1000u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    assert!(query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::BorrowRequestById { id: 1u64 }
+    )
+    .is_err());
+
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_0, &[]),
+        msg,
+    ) {
+        Err(ContractError::BorrowRequestNotFound {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+/// ## Description
+/// Test if settling CW20 payouts and claiming failed ones works properly
+/// ## Test case 0
+/// Input: Cancelling two CW20 token requests, then replying the refund of the first one
+/// as succeeded and the refund of the second one as failed
+/// Output: Refunds sent as submessages replied in any case, the failed refund claimable
+/// only once by its borrower
+#[test]
+fn claim_pending_test_case_0() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info(MOCK_ADDRESS_0, &[]);
+    let msg = InstantiateMsg {
+        token_code_id: MOCK_TOKEN_CODE_ID,
+    };
+
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    for _ in 0..2 {
+        let info = mock_info(MOCK_CW20_TOKEN_0, &[]);
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: MOCK_ADDRESS_0.to_string(),
+            amount: Uint128::from(1000u128),
+            msg: to_binary(&Cw20HookMsg::MakeBorrowRequest {
+                period: Duration::Height(17200u64),
+                lender: None,
+                expires_at: None,
+            })
+            .unwrap(),
+        });
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    for id in 1u64..=2u64 {
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MOCK_ADDRESS_0, &[]),
+            ExecuteMsg::CancelBorrowRequest { id },
+        )
+        .unwrap();
+        assert_eq!(res.messages[0].id, PAYOUT_REPLY_ID_OFFSET + id);
+        assert_eq!(res.messages[0].reply_on, ReplyOn::Always);
+    }
+
+    let msg = Reply {
+        id: PAYOUT_REPLY_ID_OFFSET + 1u64,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    let res = reply(deps.as_mut(), mock_env(), msg.clone()).unwrap();
+    assert!(res.attributes.is_empty());
+    assert!(reply(deps.as_mut(), mock_env(), msg).is_err());
+
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_0, &[]),
+        ExecuteMsg::ClaimPending {},
+    ) {
+        Err(ContractError::NothingToClaim {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = Reply {
+        id: PAYOUT_REPLY_ID_OFFSET + 2u64,
+        result: ContractResult::Err("transfer failed".to_string()),
+    };
+    let res = reply(deps.as_mut(), mock_env(), msg).unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            Attribute::new("action", "defer_payout"),
+            Attribute::new("recipient", MOCK_ADDRESS_0),
+            Attribute::new("asset", format!("1000{}", MOCK_CW20_TOKEN_0)),
+            Attribute::new("error", "transfer failed"),
+        ]
+    );
+
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_1, &[]),
+        ExecuteMsg::ClaimPending {},
+    ) {
+        Err(ContractError::NothingToClaim {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_0, &[]),
+        ExecuteMsg::ClaimPending {},
+    )
+    .unwrap();
+    assert_eq!(res.messages.len(), 1);
+    assert_eq!(res.messages[0].reply_on, ReplyOn::Never);
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: MOCK_CW20_TOKEN_0.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: MOCK_ADDRESS_0.to_string(),
+                amount: Uint128::from(1000u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_0, &[]),
+        ExecuteMsg::ClaimPending {},
+    ) {
+        Err(ContractError::NothingToClaim {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+/// ## Description
+/// Test if settling CW20 payouts and claiming failed ones works properly
+/// ## Test case 1
+/// Input: Cancelling a native token request, then replying and claiming as if its refund
+/// had been deferred
+/// Output: Refund sent as a plain message with no reply and no payout recorded, an error
+/// for the reply and nothing to claim
+#[test]
+fn claim_pending_test_case_1() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info(MOCK_ADDRESS_0, &[]);
+    let msg = InstantiateMsg {
+        token_code_id: MOCK_TOKEN_CODE_ID,
+    };
+
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let info = mock_info(
+        MOCK_ADDRESS_0,
+        &[Coin {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    );
+    let msg = ExecuteMsg::MakeBorrowRequestNativeToken {
+        period: Duration::Height(17200u64),
+        lender: None,
+        expires_at: None,
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_0, &[]),
+        ExecuteMsg::CancelBorrowRequest { id: 1u64 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+            to_address: MOCK_ADDRESS_0.to_string(),
+            amount: vec![Coin {
+                denom: MOCK_NATIVE_TOKEN_0.to_string(),
+                amount: Uint128::from(1000u128),
+            }],
+        }))]
+    );
+    assert_eq!(res.messages[0].reply_on, ReplyOn::Never);
+
+    let msg = Reply {
+        id: PAYOUT_REPLY_ID_OFFSET + 1u64,
+        result: ContractResult::Err("transfer failed".to_string()),
+    };
+    assert!(reply(deps.as_mut(), mock_env(), msg).is_err());
+
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_0, &[]),
+        ExecuteMsg::ClaimPending {},
+    ) {
+        Err(ContractError::NothingToClaim {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+/// ## Description
+/// Test if settling CW20 payouts and claiming failed ones works properly
+/// ## Test case 2
+/// Input: Cancelling two CW20 token requests of the same borrower, replying the refund of the
+/// first one as failed and of the second one as succeeded, then claiming twice
+/// Output: Only the failed refund claimed, and nothing left to claim the second time
+#[test]
+fn claim_pending_test_case_2() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info(MOCK_ADDRESS_0, &[]);
+    let msg = InstantiateMsg {
+        token_code_id: MOCK_TOKEN_CODE_ID,
+    };
+
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    for amount in [1000u128, 500u128] {
+        let info = mock_info(MOCK_CW20_TOKEN_0, &[]);
+        let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+            sender: MOCK_ADDRESS_0.to_string(),
+            amount: Uint128::from(amount),
+            msg: to_binary(&Cw20HookMsg::MakeBorrowRequest {
+                period: Duration::Height(17200u64),
+                lender: None,
+                expires_at: None,
+            })
+            .unwrap(),
+        });
+        execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    }
+
+    for id in 1u64..=2u64 {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MOCK_ADDRESS_0, &[]),
+            ExecuteMsg::CancelBorrowRequest { id },
+        )
+        .unwrap();
+    }
+
+    let msg = Reply {
+        id: PAYOUT_REPLY_ID_OFFSET + 1u64,
+        result: ContractResult::Err("transfer failed".to_string()),
+    };
+    reply(deps.as_mut(), mock_env(), msg).unwrap();
+
+    let msg = Reply {
+        id: PAYOUT_REPLY_ID_OFFSET + 2u64,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    let res = reply(deps.as_mut(), mock_env(), msg).unwrap();
+    assert!(res.attributes.is_empty());
+    assert!(res.events.is_empty());
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_0, &[]),
+        ExecuteMsg::ClaimPending {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages,
+        vec![SubMsg::new(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: MOCK_CW20_TOKEN_0.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: MOCK_ADDRESS_0.to_string(),
+                amount: Uint128::from(1000u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        }))]
+    );
+
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_0, &[]),
+        ExecuteMsg::ClaimPending {},
+    ) {
+        Err(ContractError::NothingToClaim {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+/// ## Description
+/// Test if querying borrow requests by collateral and by lender works properly
+/// ## Test case 0
+/// Input: Public native token request, CW20 token and native token requests directed to
+/// mock address 1, then cancelling the last one
+/// Output: Requests listed by their collateral and by their lender in order of id, directed
+/// ones listed by collateral only when asked for, and dropped from both once cancelled
+#[test]
+fn borrow_request_index_test_case_0() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info(MOCK_ADDRESS_0, &[]);
+    let msg = InstantiateMsg {
+        token_code_id: MOCK_TOKEN_CODE_ID,
+    };
+
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let native_info = mock_info(
+        MOCK_ADDRESS_0,
+        &[Coin {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    );
+    let msg = ExecuteMsg::MakeBorrowRequestNativeToken {
+        period: Duration::Height(17200u64),
+        lender: None,
+        expires_at: None,
+    };
+    execute(deps.as_mut(), mock_env(), native_info.clone(), msg).unwrap();
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: MOCK_ADDRESS_0.to_string(),
+        amount: Uint128::from(1000u128),
+        msg: to_binary(&Cw20HookMsg::MakeBorrowRequest {
+            period: Duration::Height(17200u64),
+            lender: Some(MOCK_ADDRESS_1.to_string()),
+            expires_at: None,
+        })
+        .unwrap(),
+    });
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CW20_TOKEN_0, &[]),
+        msg,
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::MakeBorrowRequestNativeToken {
+        period: Duration::Height(17200u64),
+        lender: Some(MOCK_ADDRESS_1.to_string()),
+        expires_at: None,
+    };
+    execute(deps.as_mut(), mock_env(), native_info, msg).unwrap();
+
+    let query_ids = |deps: Deps, msg: QueryMsg| -> Vec<u64> {
+        from_binary::<Vec<BorrowRequestResponse>>(&query(deps, mock_env(), msg).unwrap())
+            .unwrap()
+            .iter()
+            .map(|br| br.id)
+            .collect()
+    };
+    let native = AssetInfo::NativeToken {
+        denom: MOCK_NATIVE_TOKEN_0.to_string(),
+    };
+
+    assert_eq!(
+        query_ids(
+            deps.as_ref(),
+            QueryMsg::BorrowRequestsByCollateral {
+                asset_info: native.clone(),
+                start_after: None,
+                limit: None,
+                include_directed: None,
+            }
+        ),
+        vec![1u64]
+    );
+    assert_eq!(
+        query_ids(
+            deps.as_ref(),
+            QueryMsg::BorrowRequestsByCollateral {
+                asset_info: native.clone(),
+                start_after: None,
+                limit: None,
+                include_directed: Some(true),
+            }
+        ),
+        vec![1u64, 3u64]
+    );
+    assert_eq!(
+        query_ids(
+            deps.as_ref(),
+            QueryMsg::BorrowRequestsByCollateral {
+                asset_info: native.clone(),
+                start_after: Some(1u64),
+                limit: Some(1u32),
+                include_directed: None,
+            }
+        ),
+        Vec::<u64>::new()
+    );
+    assert_eq!(
+        query_ids(
+            deps.as_ref(),
+            QueryMsg::BorrowRequestsByCollateral {
+                asset_info: native.clone(),
+                start_after: Some(1u64),
+                limit: Some(1u32),
+                include_directed: Some(true),
+            }
+        ),
+        vec![3u64]
+    );
+    assert_eq!(
+        query_ids(
+            deps.as_ref(),
+            QueryMsg::BorrowRequestsByCollateral {
+                asset_info: AssetInfo::Token {
+                    contract_addr: Addr::unchecked(MOCK_CW20_TOKEN_0),
+                },
+                start_after: None,
+                limit: None,
+                include_directed: Some(true),
+            }
+        ),
+        vec![2u64]
+    );
+    assert_eq!(
+        query_ids(
+            deps.as_ref(),
+            QueryMsg::RequestsDirectedTo {
+                lender: MOCK_ADDRESS_1.to_string(),
+                start_after: None,
+                limit: None,
+            }
+        ),
+        vec![2u64, 3u64]
+    );
+    assert_eq!(
+        query_ids(
+            deps.as_ref(),
+            QueryMsg::RequestsDirectedTo {
+                lender: MOCK_ADDRESS_0.to_string(),
+                start_after: None,
+                limit: None,
+            }
+        ),
+        Vec::<u64>::new()
+    );
+
+    let msg = ExecuteMsg::CancelBorrowRequest { id: 3u64 };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_0, &[]),
+        msg,
+    )
+    .unwrap();
+
+    assert_eq!(
+        query_ids(
+            deps.as_ref(),
+            QueryMsg::BorrowRequestsByCollateral {
+                asset_info: native,
+                start_after: None,
+                limit: None,
+                include_directed: Some(true),
+            }
+        ),
+        vec![1u64]
+    );
+    assert_eq!(
+        query_ids(
+            deps.as_ref(),
+            QueryMsg::RequestsDirectedTo {
+                lender: MOCK_ADDRESS_1.to_string(),
+                start_after: None,
+                limit: None,
+            }
+        ),
+        vec![2u64]
+    );
+}
+
+/// ## Description
+/// Test if pruning expired borrow requests works properly
+/// ## Test case 0
+/// Input: Requests expiring at a height, at a time and never, then pruning one at a time
+/// Output: Expired requests reported, then removed in order of expiry with collateral
+/// refunded, and an error for a request already expired when made
+#[test]
+fn prune_expired_test_case_0() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info(MOCK_ADDRESS_0, &[]);
+    let msg = InstantiateMsg {
+        token_code_id: MOCK_TOKEN_CODE_ID,
+    };
+
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let env = mock_env();
+    let info = mock_info(
+        MOCK_ADDRESS_0,
+        &[Coin {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    );
+    for expires_at in [
+        Some(Expiration::AtHeight(env.block.height + 10u64)),
+        Some(Expiration::AtTime(env.block.time.plus_seconds(100u64))),
+        None,
+    ] {
+        let msg = ExecuteMsg::MakeBorrowRequestNativeToken {
+            period: Duration::Height(17200u64),
+            lender: None,
+            expires_at,
+        };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+    }
+
+    let msg = ExecuteMsg::MakeBorrowRequestNativeToken {
+        period: Duration::Height(17200u64),
+        lender: None,
+        expires_at: Some(Expiration::AtHeight(env.block.height)),
+    };
+    match execute(deps.as_mut(), env.clone(), info, msg) {
+        Err(ContractError::InvalidExpiration {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let mut env = mock_env_after(10u64);
+    env.block.time = env.block.time.plus_seconds(100u64);
+
+    let res = from_binary::<Vec<BorrowRequestResponse>>(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::BorrowRequestByAddr {
+                borrower: MOCK_ADDRESS_0.to_string(),
+                include_directed: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.iter().map(|br| br.expired).collect::<Vec<bool>>(),
+        vec![true, true, false]
+    );
+
+    for id in [1u64, 2u64] {
+        let res = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info(MOCK_ADDRESS_1, &[]),
+            ExecuteMsg::PruneExpired { limit: Some(1u32) },
+        )
+        .unwrap();
+        assert_eq!(
+            res.messages
+                .into_iter()
+                .map(|msg| msg.msg)
+                .collect::<Vec<CosmosMsg>>(),
+            vec![CosmosMsg::Bank(BankMsg::Send {
+                to_address: MOCK_ADDRESS_0.to_string(),
+                amount: vec![Coin {
+                    denom: MOCK_NATIVE_TOKEN_0.to_string(),
+                    amount: Uint128::from(1000u128),
+                }],
+            })]
+        );
+        assert!(query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::BorrowRequestById { id }
+        )
+        .is_err());
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(MOCK_ADDRESS_1, &[]),
+        ExecuteMsg::PruneExpired { limit: None },
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+    assert!(query(deps.as_ref(), env, QueryMsg::BorrowRequestById { id: 3u64 }).is_ok());
+}
+
+/// ## Description
+/// Test if querying borrow requests expiring before an expiration works properly
+/// ## Test case 0
+/// Input: Requests expiring at three heights, at a time and never, then cancelling the
+/// request a page ended at
+/// Output: Requests expiring at a height before the given height, or at a time before the
+/// given time, earliest first, paged by expiry and id, and the next page kept in place once
+/// the cursor is cancelled
+#[test]
+fn requests_expiring_before_test_case_0() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info(MOCK_ADDRESS_0, &[]);
+    let msg = InstantiateMsg {
+        token_code_id: MOCK_TOKEN_CODE_ID,
+    };
+
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let height = mock_env().block.height;
+    let info = mock_info(
+        MOCK_ADDRESS_0,
+        &[Coin {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    );
+    for expires_at in [
+        Some(Expiration::AtHeight(height + 200u64)),
+        Some(Expiration::AtHeight(height + 100u64)),
+        Some(Expiration::AtTime(
+            mock_env().block.time.plus_seconds(100u64),
+        )),
+        None,
+        Some(Expiration::AtHeight(height + 150u64)),
+    ] {
+        let msg = ExecuteMsg::MakeBorrowRequestNativeToken {
+            period: Duration::Height(17200u64),
+            lender: None,
+            expires_at,
+        };
+        execute(deps.as_mut(), mock_env(), info.clone(), msg).unwrap();
+    }
+
+    let query_expiring = |deps: Deps,
+                          expiration: Expiration,
+                          start_after: Option<(u64, u64)>,
+                          limit: Option<u32>|
+     -> Vec<u64> {
+        from_binary::<Vec<BorrowRequestResponse>>(
+            &query(
+                deps,
+                mock_env(),
+                QueryMsg::RequestsExpiringBefore {
+                    expiration,
+                    start_after,
+                    limit,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+        .iter()
+        .map(|br| br.id)
+        .collect()
+    };
+    let query_ids = |deps: Deps, height: u64, start_after, limit| {
+        query_expiring(deps, Expiration::AtHeight(height), start_after, limit)
+    };
+
+    assert_eq!(
+        query_ids(deps.as_ref(), height + 200u64, None, None),
+        vec![2u64, 5u64]
+    );
+    assert_eq!(
+        query_ids(deps.as_ref(), height + 201u64, None, None),
+        vec![2u64, 5u64, 1u64]
+    );
+    assert_eq!(
+        query_ids(deps.as_ref(), height + 201u64, None, Some(2u32)),
+        vec![2u64, 5u64]
+    );
+    assert_eq!(
+        query_ids(
+            deps.as_ref(),
+            height + 201u64,
+            Some((height + 100u64, 2u64)),
+            Some(1u32)
+        ),
+        vec![5u64]
+    );
+    assert_eq!(
+        query_ids(deps.as_ref(), height + 100u64, None, None),
+        Vec::<u64>::new()
+    );
+
+    let time = mock_env().block.time;
+    assert_eq!(
+        query_expiring(
+            deps.as_ref(),
+            Expiration::AtTime(time.plus_seconds(101u64)),
+            None,
+            None
+        ),
+        vec![3u64]
+    );
+    assert_eq!(
+        query_expiring(
+            deps.as_ref(),
+            Expiration::AtTime(time.plus_seconds(101u64)),
+            Some((time.plus_seconds(100u64).nanos(), 3u64)),
+            None
+        ),
+        Vec::<u64>::new()
+    );
+    assert_eq!(
+        query_expiring(
+            deps.as_ref(),
+            Expiration::AtTime(time.plus_seconds(100u64)),
+            None,
+            None
+        ),
+        Vec::<u64>::new()
+    );
+    assert_eq!(
+        query_expiring(deps.as_ref(), Expiration::Never {}, None, None),
+        Vec::<u64>::new()
+    );
+
+    let msg = ExecuteMsg::CancelBorrowRequest { id: 5u64 };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_0, &[]),
+        msg,
+    )
+    .unwrap();
+
+    assert_eq!(
+        query_ids(
+            deps.as_ref(),
+            height + 201u64,
+            Some((height + 150u64, 5u64)),
+            Some(2u32)
+        ),
+        vec![1u64]
+    );
+}
+
+/// ## Description
+/// Test if deadline and remaining duration of borrowed requests are reported properly
+/// ## Test case 0
+/// Input: Borrowed requests with block period and time period
+/// Output: Deadline height and time with remaining blocks and seconds
+#[test]
+fn query_borrow_request_deadline() {
+    let mut deps = mock_dependencies(&[]);
+    let env = mock_env();
+    let borrower = deps.api.addr_canonicalize(MOCK_ADDRESS_0).unwrap();
+    let collateral = Asset {
+        info: AssetInfo::NativeToken {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+        },
+        amount: Uint128::from(1000u128),
+    };
+
+    let borrowed_at = env.block.height - 200u64;
+    let borrowed_at_time = env.block.time.seconds() - 1000u64;
+    store_borrow_request(
+        deps.as_mut().storage,
+        &borrower,
+        &vec![
+            BorrowRequest {
+                id: 1u64,
+                collateral: collateral.clone(),
+                period: Duration::Height(17200u64),
+                lender: None,
+                expires_at: None,
+                borrowed_from: 1u64,
+                borrowed_at,
+                borrowed_at_time,
+            },
+            BorrowRequest {
+                id: 2u64,
+                collateral,
+                period: Duration::Time(604800u64),
+                lender: None,
+                expires_at: None,
+                borrowed_from: 2u64,
+                borrowed_at,
+                borrowed_at_time,
+            },
+        ],
+    )
+    .unwrap();
+    store_borrow_request_id_to_addr(deps.as_mut().storage, 1u64, &borrower).unwrap();
+    store_borrow_request_id_to_addr(deps.as_mut().storage, 2u64, &borrower).unwrap();
+
+    let res = from_binary::<BorrowRequestResponse>(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::BorrowRequestById { id: 1u64 },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.deadline,
+        Some(Expiration::AtHeight(borrowed_at + 17200u64))
+    );
+    assert_eq!(res.remaining, Some(Duration::Height(17000u64)));
+
+    let res = from_binary::<BorrowRequestResponse>(
+        &query(deps.as_ref(), env, QueryMsg::BorrowRequestById { id: 2u64 }).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.deadline,
+        Some(Expiration::AtTime(Timestamp::from_seconds(
+            borrowed_at_time + 604800u64
+        )))
+    );
+    assert_eq!(res.remaining, Some(Duration::Time(603800u64)));
+}
+
+/// ## Description
+/// Test if accruing interest works properly
+/// ## Test case 0
+/// Input: Each interest model with positive principal, rate and blocks
+/// Output: Interest rounded up for each model
+#[test]
+fn accrued_interest_test_case_0() {
+    let principal = Uint128::from(1000u128);
+
+    assert_eq!(
+        accrued_interest(
+            InterestModel::Simple,
+            principal,
+            Decimal256::permille(1u64),
+            100u64
+        )
+        .unwrap(),
+        Uint128::from(100u128),
+    );
+    assert_eq!(
+        accrued_interest(
+            InterestModel::Compound,
+            principal,
+            Decimal256::percent(1u64),
+            2u64
+        )
+        .unwrap(),
+        Uint128::from(21u128),
+    );
+    assert_eq!(
+        accrued_interest(
+            InterestModel::Continuous,
+            principal,
+            Decimal256::percent(1u64),
+            100u64
+        )
+        .unwrap(),
+        Uint128::from(1719u128),
+    );
+    assert_eq!(
+        accrued_interest(
+            InterestModel::Continuous,
+            principal,
+            Decimal256::percent(1u64),
+            300u64
+        )
+        .unwrap(),
+        Uint128::from(19086u128),
+    );
+}
+
+/// ## Description
+/// Test if accruing interest works properly
+/// ## Test case 1
+/// Input: Interest smaller than one unit and zero blocks
+/// Output: Interest rounded up in favor of the lender, and zero for zero blocks
+#[test]
+fn accrued_interest_test_case_1() {
+    for model in [
+        InterestModel::Simple,
+        InterestModel::Compound,
+        InterestModel::Continuous,
+    ] {
+        assert_eq!(
+            accrued_interest(
+                model,
+                Uint128::from(1u128),
+                Decimal256::permille(1u64),
+                1u64
+            )
+            .unwrap(),
+            Uint128::from(1u128),
+        );
+        assert_eq!(
+            accrued_interest(
+                model,
+                Uint128::from(1000u128),
+                Decimal256::permille(1u64),
+                0u64
+            )
+            .unwrap(),
+            Uint128::zero(),
+        );
+    }
+}
+
+/// ## Description
+/// Test if accruing interest works properly
+/// ## Test case 2
+/// Input: Principal near Uint128::MAX
+/// Output: Interest up to Uint128::MAX, and OverflowError beyond it
+#[test]
+fn accrued_interest_test_case_2() {
+    for model in [
+        InterestModel::Simple,
+        InterestModel::Compound,
+        InterestModel::Continuous,
+    ] {
+        assert_eq!(
+            accrued_interest(model, Uint128::MAX, Decimal256::zero(), 17200u64).unwrap(),
+            Uint128::zero(),
+        );
+
+        match accrued_interest(model, Uint128::MAX, Decimal256::one(), 2u64) {
+            Err(ContractError::OverflowError(_)) => (),
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
+
+    assert_eq!(
+        accrued_interest(InterestModel::Simple, Uint128::MAX, Decimal256::one(), 1u64).unwrap(),
+        Uint128::MAX,
+    );
+    assert_eq!(
+        accrued_interest(
+            InterestModel::Compound,
+            Uint128::MAX - Uint128::from(1u128),
+            Decimal256::percent(50u64),
+            1u64
+        )
+        .unwrap(),
+        Uint128::MAX.multiply_ratio(1u128, 2u128),
+    );
+}
+
+/// ## Description
+/// Test if accruing interest works properly
+/// ## Test case 3
+/// Input: Growth factor exceeding Decimal256::MAX
+/// Output: OverflowError
+#[test]
+fn accrued_interest_test_case_3() {
+    for (model, blocks) in [
+        (InterestModel::Simple, u64::MAX),
+        (InterestModel::Compound, 1000u64),
+        (InterestModel::Continuous, 1000u64),
+    ] {
+        match accrued_interest(
+            model,
+            Uint128::from(1u128),
+            Decimal256::from_ratio(Uint256::MAX, Uint256::from(1u64 << 62)),
+            blocks,
+        ) {
+            Err(ContractError::OverflowError(_)) => (),
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
+}
+
+/// ## Description
+/// Returns a reserve config accepting native token 1 as collateral at price 1
+fn mock_reserve_config() -> ReserveConfig {
+    ReserveConfig {
+        collateral_info: AssetInfo::NativeToken {
+            denom: MOCK_NATIVE_TOKEN_1.to_string(),
+        },
+        collateral_price: Decimal256::one(),
+        max_ltv: Decimal256::percent(50u64),
+        liquidation_threshold: Decimal256::percent(75u64),
+        liquidation_bonus: Decimal256::percent(5u64),
+        flash_loan_fee: Decimal256::from_ratio(9u64, 10000u64),
+        base_rate: Decimal256::zero(),
+        slope1: Decimal256::from_ratio(1u64, 1_000_000_000u64),
+        slope2: Decimal256::from_ratio(1u64, 100_000_000u64),
+        optimal_utilization: Decimal256::percent(80u64),
+        borrower_allowlist: None,
+        min_loans_repaid: None,
+        max_loans_liquidated: None,
+        max_outstanding_debt: None,
+    }
+}
+
+/// ## Description
+/// Instantiates the contract with mock address 0 as owner, creates a reserve of native
+/// token 0 and deposits the given amount into it from mock address 1
+fn mock_reserve(mut deps: DepsMut, deposit: u128) {
+    let info = mock_info(MOCK_ADDRESS_0, &[]);
+    instantiate(
+        deps.branch(),
+        mock_env(),
+        info.clone(),
+        InstantiateMsg {
+            token_code_id: MOCK_TOKEN_CODE_ID,
+        },
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::CreateReserve {
+        asset_info: AssetInfo::NativeToken {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+        },
+        config: mock_reserve_config(),
+    };
+    execute(deps.branch(), mock_env(), info, msg).unwrap();
+    mock_share_token_reply(deps.branch(), MOCK_SHARE_TOKEN_0).unwrap();
+
+    let info = mock_info(
+        MOCK_ADDRESS_1,
+        &[Coin {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            amount: Uint128::from(deposit),
+        }],
+    );
+    let msg = ExecuteMsg::DepositNativeToken {};
+    execute(deps, mock_env(), info, msg).unwrap();
+}
+
+/// ## Description
+/// Replies to the instantiation of a share token with the given address encoded as
+/// `MsgInstantiateContractResponse`
+fn mock_share_token_reply(deps: DepsMut, share_token: &str) -> Result<Response, ContractError> {
+    let mut data = vec![0x0au8, share_token.len() as u8];
+    data.extend_from_slice(share_token.as_bytes());
+
+    let msg = Reply {
+        id: INSTANTIATE_SHARE_TOKEN_REPLY_ID,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: Some(Binary::from(data)),
+        }),
+    };
+    reply(deps, mock_env(), msg)
+}
+
+/// ## Description
+/// Borrows the given amount of native token 0 from mock address 2 against the given
+/// amount of native token 1
+fn mock_borrow(deps: DepsMut, amount: u128, collateral: u128) -> Result<Response, ContractError> {
+    let info = mock_info(
+        MOCK_ADDRESS_2,
+        &[Coin {
+            denom: MOCK_NATIVE_TOKEN_1.to_string(),
+            amount: Uint128::from(collateral),
+        }],
+    );
+    let msg = ExecuteMsg::BorrowNativeToken {
+        asset_info: AssetInfo::NativeToken {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+        },
+        amount: Uint128::from(amount),
+    };
+    execute(deps, mock_env(), info, msg)
+}
+
+/// ## Description
+/// Writes the storage of 0.1.0 with two native token requests of mock address 0, one of
+/// them for 100 blocks, and a CW20 token request of mock address 1 in between
+fn mock_storage_0_1_0(deps: DepsMut) {
+    set_contract_version(deps.storage, "jihoonsong-lending", "0.1.0").unwrap();
+    deps.storage.set(
+        b"state",
+        &serde_json::to_vec(&json!({ "request_count": 3u64, "response_count": 0u64 })).unwrap(),
+    );
+
+    let borrow_request = |id: u64, info: AssetInfo, amount: u128, period: u64| {
+        json!({
+            "id": id,
+            "collateral": Asset { info, amount: Uint128::from(amount) },
+            "period": period,
+            "borrowed_from": 0u64,
+            "borrowed_at": 0u64,
+        })
+    };
+    let native = AssetInfo::NativeToken {
+        denom: MOCK_NATIVE_TOKEN_0.to_string(),
+    };
+    let cw20 = AssetInfo::Token {
+        contract_addr: Addr::unchecked(MOCK_CW20_TOKEN_0),
+    };
+    let fixture = [
+        (
+            MOCK_ADDRESS_0,
+            vec![
+                borrow_request(1u64, native.clone(), 1000u128, 17200u64),
+                borrow_request(2u64, cw20, 500u128, 100u64),
+            ],
+            vec![1u64, 2u64],
+        ),
+        (
+            MOCK_ADDRESS_1,
+            vec![borrow_request(3u64, native, 2000u128, 17200u64)],
+            vec![3u64],
+        ),
+    ];
+
+    let borrow_requests = Map::<&[u8], Binary>::new("borrow_request");
+    let id_to_addr = Map::<&str, CanonicalAddr>::new("borrow_request_id_to_addr");
+    for (borrower, requests, ids) in fixture {
+        let borrower = deps.api.addr_canonicalize(borrower).unwrap();
+        deps.storage.set(
+            &borrow_requests.key(borrower.as_slice()),
+            &serde_json::to_vec(&requests).unwrap(),
+        );
+        for id in ids {
+            id_to_addr
+                .save(deps.storage, &id.to_string(), &borrower)
+                .unwrap();
+        }
+    }
+}
+
+/// ## Description
+/// Returns a mock environment the given number of blocks after [`mock_env`]
+fn mock_env_after(blocks: u64) -> Env {
+    let mut env = mock_env();
+    env.block.height += blocks;
+    env
+}
+
+/// ## Description
+/// Returns a cursor to start a page of liquidatable loans after the given loan
+fn mock_loan_cursor(loan: &LoanResponse) -> LoanCursor {
+    LoanCursor {
+        asset_info: loan.asset_info.clone(),
+        debt_ratio: loan.debt_ratio,
+        id: loan.id,
+    }
+}
+
+/// ## Description
+/// Test if the kinked rate curve works properly
+/// ## Test case 0
+/// Input: Utilization at zero, below, at and above the optimal utilization, then a base
+/// rate overflowing once the slopes are added
+/// Output: Rate rising by slope1 up to the optimal utilization and by slope2 beyond it,
+/// then an overflow error
+#[test]
+fn rate_curve_test_case_0() {
+    let curve = RateCurve::Kinked {
+        base_rate: Decimal256::percent(1u64),
+        slope1: Decimal256::percent(4u64),
+        slope2: Decimal256::percent(60u64),
+        optimal_utilization: Decimal256::percent(80u64),
+    };
+
+    assert_eq!(
+        curve.rate(Decimal256::zero()).unwrap(),
+        Decimal256::percent(1u64)
+    );
+    assert_eq!(
+        curve.rate(Decimal256::percent(40u64)).unwrap(),
+        Decimal256::percent(3u64)
+    );
+    assert_eq!(
+        curve.rate(Decimal256::percent(80u64)).unwrap(),
+        Decimal256::percent(5u64)
+    );
+    assert_eq!(
+        curve.rate(Decimal256::percent(90u64)).unwrap(),
+        Decimal256::percent(35u64)
+    );
+    assert_eq!(
+        curve.rate(Decimal256::one()).unwrap(),
+        Decimal256::percent(65u64)
+    );
+
+    let curve = RateCurve::Kinked {
+        base_rate: Decimal256::MAX,
+        slope1: Decimal256::percent(4u64),
+        slope2: Decimal256::percent(60u64),
+        optimal_utilization: Decimal256::percent(80u64),
+    };
+    match curve.rate(Decimal256::one()) {
+        Err(ContractError::OverflowError(_)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+/// ## Description
+/// Test if the kinked rate curve works properly
+/// ## Test case 1
+/// Input: Curves with zero and full optimal utilization, at and beyond the kink
+/// Output: Errors instead of panics where a segment of the curve is empty
+#[test]
+fn rate_curve_test_case_1() {
+    let curve = RateCurve::Kinked {
+        base_rate: Decimal256::percent(1u64),
+        slope1: Decimal256::percent(4u64),
+        slope2: Decimal256::percent(60u64),
+        optimal_utilization: Decimal256::zero(),
+    };
+    match curve.rate(Decimal256::zero()) {
+        Err(ContractError::Std(StdError::DivideByZero { .. })) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let curve = RateCurve::Kinked {
+        base_rate: Decimal256::percent(1u64),
+        slope1: Decimal256::percent(4u64),
+        slope2: Decimal256::percent(60u64),
+        optimal_utilization: Decimal256::one(),
+    };
+    assert_eq!(
+        curve.rate(Decimal256::one()).unwrap(),
+        Decimal256::percent(5u64)
+    );
+    match curve.rate(Decimal256::percent(101u64)) {
+        Err(ContractError::Std(StdError::DivideByZero { .. })) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let curve = RateCurve::Kinked {
+        base_rate: Decimal256::percent(1u64),
+        slope1: Decimal256::percent(4u64),
+        slope2: Decimal256::percent(60u64),
+        optimal_utilization: Decimal256::percent(101u64),
+    };
+    match curve.rate(Decimal256::percent(102u64)) {
+        Err(ContractError::OverflowError(_)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+/// ## Description
+/// Test if creating reserve works properly
+/// ## Test case 0
+/// Input: Valid config from owner, then the reply of the share token instantiation
+/// Output: Share token instantiated with the contract as minter, then empty reserve with
+/// the given config and share token
+#[test]
+fn create_reserve_test_case_0() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info(MOCK_ADDRESS_0, &[]);
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        InstantiateMsg {
+            token_code_id: MOCK_TOKEN_CODE_ID,
+        },
+    )
+    .unwrap();
+
+    let asset_info = AssetInfo::NativeToken {
+        denom: MOCK_NATIVE_TOKEN_0.to_string(),
+    };
+    let msg = ExecuteMsg::CreateReserve {
+        asset_info: asset_info.clone(),
+        config: mock_reserve_config(),
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(res.messages.len(), 1usize);
+    assert_eq!(res.messages[0].id, INSTANTIATE_SHARE_TOKEN_REPLY_ID);
+    assert_eq!(res.messages[0].reply_on, ReplyOn::Success);
+    match &res.messages[0].msg {
+        CosmosMsg::Wasm(WasmMsg::Instantiate { code_id, msg, .. }) => {
+            assert_eq!(*code_id, MOCK_TOKEN_CODE_ID);
+            assert_eq!(
+                msg,
+                &to_binary(&astroport::token::InstantiateMsg {
+                    name: "Lending Share".to_string(),
+                    symbol: "lSHARE".to_string(),
+                    decimals: 6u8,
+                    initial_balances: vec![],
+                    mint: Some(cw20::MinterResponse {
+                        minter: MOCK_CONTRACT_ADDR.to_string(),
+                        cap: None,
+                    }),
+                })
+                .unwrap()
+            );
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    assert!(query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::Reserve {
+            asset_info: asset_info.clone()
+        },
+    )
+    .is_err());
+
+    mock_share_token_reply(deps.as_mut(), MOCK_SHARE_TOKEN_0).unwrap();
+
+    assert_eq!(
+        from_binary::<ReserveResponse>(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Reserve {
+                    asset_info: asset_info.clone()
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap(),
+        ReserveResponse {
+            asset_info,
+            share_token: MOCK_SHARE_TOKEN_0.to_string(),
+            config: mock_reserve_config(),
+            liquidity: Uint128::zero(),
+            total_debt: Uint128::zero(),
+            total_shares: Uint128::zero(),
+            borrow_index: Decimal256::one(),
+            utilization: Decimal256::zero(),
+            borrow_rate: Decimal256::zero(),
+        }
+    );
+}
+
+/// ## Description
+/// Test if creating reserve works properly
+/// ## Test case 1
+/// Input: Valid config from non-owner, invalid config and existing reserve
+/// Output: Error
+#[test]
+fn create_reserve_test_case_1() {
+    let mut deps = mock_dependencies(&[]);
+    mock_reserve(deps.as_mut(), 1000u128);
+
+    let asset_info = AssetInfo::NativeToken {
+        denom: MOCK_NATIVE_TOKEN_1.to_string(),
+    };
+
+    let msg = ExecuteMsg::CreateReserve {
+        asset_info: asset_info.clone(),
+        config: mock_reserve_config(),
+    };
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_1, &[]),
+        msg,
+    ) {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::CreateReserve {
+        asset_info,
+        config: ReserveConfig {
+            max_ltv: Decimal256::percent(80u64),
+            ..mock_reserve_config()
+        },
+    };
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_0, &[]),
+        msg,
+    ) {
+        Err(ContractError::InvalidReserveConfig {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::CreateReserve {
+        asset_info: AssetInfo::NativeToken {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+        },
+        config: mock_reserve_config(),
+    };
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_0, &[]),
+        msg,
+    ) {
+        Err(ContractError::ReserveAlreadyExists {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+/// ## Description
+/// Test if creating reserve works properly
+/// ## Test case 2
+/// Input: Reply of the share token instantiation with malformed data and unknown reply id
+/// Output: Error
+#[test]
+fn create_reserve_test_case_2() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info(MOCK_ADDRESS_0, &[]);
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        InstantiateMsg {
+            token_code_id: MOCK_TOKEN_CODE_ID,
+        },
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::CreateReserve {
+        asset_info: AssetInfo::NativeToken {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+        },
+        config: mock_reserve_config(),
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    for data in [vec![0x12u8, 0x01u8, 0x61u8], vec![0x0au8, 0x05u8, 0x61u8]] {
+        let msg = Reply {
+            id: INSTANTIATE_SHARE_TOKEN_REPLY_ID,
+            result: ContractResult::Ok(SubMsgExecutionResponse {
+                events: vec![],
+                data: Some(Binary::from(data)),
+            }),
+        };
+        match reply(deps.as_mut(), mock_env(), msg) {
+            Err(ContractError::Std(_)) => (),
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
+
+    let msg = Reply {
+        id: INSTANTIATE_SHARE_TOKEN_REPLY_ID + 1u64,
+        result: ContractResult::Ok(SubMsgExecutionResponse {
+            events: vec![],
+            data: None,
+        }),
+    };
+    match reply(deps.as_mut(), mock_env(), msg) {
+        Err(ContractError::Std(_)) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+/// ## Description
+/// Test if creating and updating reserve works properly
+/// ## Test case 3
+/// Input: Configs whose borrow rate at full utilization exceeds the maximum, whose slopes
+/// overflow and whose liquidation bonus is not below one
+/// Output: Error on both creating and updating reserve, and the reserve left unchanged
+#[test]
+fn create_reserve_test_case_3() {
+    let mut deps = mock_dependencies(&[]);
+    mock_reserve(deps.as_mut(), 1000u128);
+
+    let configs = vec![
+        ReserveConfig {
+            slope2: Decimal256::percent(1u64),
+            ..mock_reserve_config()
+        },
+        ReserveConfig {
+            base_rate: Decimal256::MAX,
+            ..mock_reserve_config()
+        },
+        ReserveConfig {
+            liquidation_bonus: Decimal256::one(),
+            ..mock_reserve_config()
+        },
+    ];
+    for config in configs {
+        let msg = ExecuteMsg::CreateReserve {
+            asset_info: AssetInfo::NativeToken {
+                denom: MOCK_NATIVE_TOKEN_1.to_string(),
+            },
+            config: config.clone(),
+        };
+        match execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MOCK_ADDRESS_0, &[]),
+            msg,
+        ) {
+            Err(ContractError::InvalidReserveConfig {}) => (),
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+
+        let msg = ExecuteMsg::UpdateReserve {
+            asset_info: AssetInfo::NativeToken {
+                denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            },
+            config,
+        };
+        match execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MOCK_ADDRESS_0, &[]),
+            msg,
+        ) {
+            Err(ContractError::InvalidReserveConfig {}) => (),
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
+
+    let res: ReserveResponse = from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Reserve {
+                asset_info: AssetInfo::NativeToken {
+                    denom: MOCK_NATIVE_TOKEN_0.to_string(),
+                },
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.config, mock_reserve_config());
+}
+
+/// ## Description
+/// Test if creating and updating reserve works properly
+/// ## Test case 4
+/// Input: Reserve at the maximum borrow rate borrowed in full, then a century of blocks
+/// Output: Interest still accrued when querying, updating and repaying, and the loan repaid
+/// in full
+#[test]
+fn create_reserve_test_case_4() {
+    let mut deps = mock_dependencies(&[]);
+    mock_reserve(deps.as_mut(), 1000u128);
+
+    let config = ReserveConfig {
+        slope2: Decimal256::from_ratio(99u64, 1_000_000_000u64),
+        ..mock_reserve_config()
+    };
+    let msg = ExecuteMsg::UpdateReserve {
+        asset_info: AssetInfo::NativeToken {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+        },
+        config: config.clone(),
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_0, &[]),
+        msg,
+    )
+    .unwrap();
+    mock_borrow(deps.as_mut(), 1000u128, 2000u128).unwrap();
+
+    let env = mock_env_after(100u64 * 365u64 * 17280u64);
+    let reserve = from_binary::<ReserveResponse>(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::Reserve {
+                asset_info: AssetInfo::NativeToken {
+                    denom: MOCK_NATIVE_TOKEN_0.to_string(),
+                },
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(reserve.utilization, Decimal256::one());
+    assert_eq!(
+        reserve.borrow_rate,
+        Decimal256::from_ratio(100u64, 1_000_000_000u64)
+    );
+    assert!(reserve.borrow_index > Decimal256::from_ratio(10u128.pow(27u32), 1u128));
+
+    let msg = ExecuteMsg::UpdateReserve {
+        asset_info: AssetInfo::NativeToken {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+        },
+        config,
+    };
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        mock_info(MOCK_ADDRESS_0, &[]),
+        msg,
+    )
+    .unwrap();
+
+    let loan = from_binary::<LoanResponse>(
+        &query(deps.as_ref(), env.clone(), QueryMsg::Loan { id: 1u64 }).unwrap(),
+    )
+    .unwrap();
+    let info = mock_info(
+        MOCK_ADDRESS_2,
+        &[Coin {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            amount: loan.debt,
+        }],
+    );
+    execute(
+        deps.as_mut(),
+        env.clone(),
+        info,
+        ExecuteMsg::RepayNativeToken { id: 1u64 },
+    )
+    .unwrap();
+    assert!(query(deps.as_ref(), env, QueryMsg::Loan { id: 1u64 }).is_err());
+}
+
+/// ## Description
+/// Test if depositing and withdrawing works properly
+/// ## Test case 0
+/// Input: Deposit into a reserve, then withdraw by sending share tokens
+/// Output: Share tokens minted to the lender, then burnt with the withdrawn asset sent to
+/// the lender
+#[test]
+fn deposit_test_case_0() {
+    let mut deps = mock_dependencies(&[]);
+    mock_reserve(deps.as_mut(), 1000u128);
+
+    let info = mock_info(
+        MOCK_ADDRESS_1,
+        &[Coin {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            amount: Uint128::from(500u128),
+        }],
+    );
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::DepositNativeToken {},
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: MOCK_SHARE_TOKEN_0.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Mint {
+                recipient: MOCK_ADDRESS_1.to_string(),
+                amount: Uint128::from(500u128),
+            })
+            .unwrap(),
+            funds: vec![],
+        })
+    );
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: MOCK_ADDRESS_1.to_string(),
+        amount: Uint128::from(400u128),
+        msg: to_binary(&Cw20HookMsg::Withdraw {}).unwrap(),
+    });
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_SHARE_TOKEN_0, &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages
+            .into_iter()
+            .map(|msg| msg.msg)
+            .collect::<Vec<CosmosMsg>>(),
+        vec![
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_SHARE_TOKEN_0.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Burn {
+                    amount: Uint128::from(400u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: MOCK_ADDRESS_1.to_string(),
+                amount: vec![Coin {
+                    denom: MOCK_NATIVE_TOKEN_0.to_string(),
+                    amount: Uint128::from(400u128),
+                }],
+            }),
+        ]
+    );
+
+    let reserve = from_binary::<ReserveResponse>(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Reserve {
+                asset_info: AssetInfo::NativeToken {
+                    denom: MOCK_NATIVE_TOKEN_0.to_string(),
+                },
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(reserve.liquidity, Uint128::from(1100u128));
+    assert_eq!(reserve.total_shares, Uint128::from(1100u128));
+
+    let msg = ExecuteMsg::Receive(Cw20ReceiveMsg {
+        sender: MOCK_ADDRESS_1.to_string(),
+        amount: Uint128::from(400u128),
+        msg: to_binary(&Cw20HookMsg::Withdraw {}).unwrap(),
+    });
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CW20_TOKEN_0, &[]),
+        msg,
+    ) {
+        Err(ContractError::InvalidAsset {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+/// ## Description
+/// Test if depositing works properly
+/// ## Test case 1
+/// Input: Deposit of an asset without reserve
+/// Output: Error
+#[test]
+fn deposit_test_case_1() {
+    let mut deps = mock_dependencies(&[]);
+    mock_reserve(deps.as_mut(), 1000u128);
+
+    let info = mock_info(
+        MOCK_ADDRESS_1,
+        &[Coin {
+            denom: MOCK_NATIVE_TOKEN_1.to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    );
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::DepositNativeToken {},
+    ) {
+        Err(ContractError::ReserveNotFound {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+/// ## Description
+/// Test if borrowing works properly
+/// ## Test case 0
+/// Input: Borrow half of the reserve at the maximum loan-to-value ratio
+/// Output: Borrowed asset sent to the borrower and debt growing with utilization
+#[test]
+fn borrow_test_case_0() {
+    let mut deps = mock_dependencies(&[]);
+    mock_reserve(deps.as_mut(), 1000u128);
+
+    let res = mock_borrow(deps.as_mut(), 500u128, 1000u128).unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: MOCK_ADDRESS_2.to_string(),
+            amount: vec![Coin {
+                denom: MOCK_NATIVE_TOKEN_0.to_string(),
+                amount: Uint128::from(500u128),
+            }],
+        })
+    );
+
+    let reserve = from_binary::<ReserveResponse>(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Reserve {
+                asset_info: AssetInfo::NativeToken {
+                    denom: MOCK_NATIVE_TOKEN_0.to_string(),
+                },
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(reserve.liquidity, Uint128::from(500u128));
+    assert_eq!(reserve.utilization, Decimal256::percent(50u64));
+    assert_eq!(
+        reserve.borrow_rate,
+        Decimal256::from_ratio(625u64, 1_000_000_000_000u64)
+    );
+
+    assert_eq!(
+        from_binary::<LoanResponse>(
+            &query(
+                deps.as_ref(),
+                mock_env_after(100_000_000u64),
+                QueryMsg::Loan { id: 1u64 }
+            )
+            .unwrap(),
+        )
+        .unwrap(),
+        LoanResponse {
+            id: 1u64,
+            borrower: MOCK_ADDRESS_2.to_string(),
+            asset_info: AssetInfo::NativeToken {
+                denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            },
+            collateral: Asset {
+                info: AssetInfo::NativeToken {
+                    denom: MOCK_NATIVE_TOKEN_1.to_string(),
+                },
+                amount: Uint128::from(1000u128),
+            },
+            debt: Uint128::from(533u128),
+            principal: Uint128::from(500u128),
+            borrowed_at: mock_env().block.height,
+            debt_ratio: Decimal256::percent(50u64),
+        }
+    );
+}
+
+/// ## Description
+/// Test if borrowing works properly
+/// ## Test case 1
+/// Input: Borrow above the maximum loan-to-value ratio, above the liquidity and
+/// against the wrong collateral
+/// Output: Error
+#[test]
+fn borrow_test_case_1() {
+    let mut deps = mock_dependencies(&[]);
+    mock_reserve(deps.as_mut(), 1000u128);
+
+    match mock_borrow(deps.as_mut(), 501u128, 1000u128) {
+        Err(ContractError::InsufficientCollateral {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    match mock_borrow(deps.as_mut(), 1001u128, 3000u128) {
+        Err(ContractError::InsufficientLiquidity {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info(
+        MOCK_ADDRESS_2,
+        &[Coin {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    );
+    let msg = ExecuteMsg::BorrowNativeToken {
+        asset_info: AssetInfo::NativeToken {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+        },
+        amount: Uint128::from(100u128),
+    };
+    match execute(deps.as_mut(), mock_env(), info, msg) {
+        Err(ContractError::InvalidAsset {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+/// ## Description
+/// Test if borrowing works properly
+/// ## Test case 2
+/// Input: Borrows from a reserve requiring an allowlisted borrower, a repaid loan, no
+/// liquidated loan and a maximum outstanding debt, before and after a loan is repaid
+/// Output: Error for a borrower not meeting the requirements, loans within them, and the
+/// requirements returned with the reserve config
+#[test]
+fn borrow_test_case_2() {
+    let mut deps = mock_dependencies(&[]);
+    mock_reserve(deps.as_mut(), 2000u128);
+
+    let update_reserve = |deps: DepsMut, config: ReserveConfig| {
+        let msg = ExecuteMsg::UpdateReserve {
+            asset_info: AssetInfo::NativeToken {
+                denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            },
+            config,
+        };
+        execute(deps, mock_env(), mock_info(MOCK_ADDRESS_0, &[]), msg)
+    };
+
+    match update_reserve(
+        deps.as_mut(),
+        ReserveConfig {
+            borrower_allowlist: Some(vec!["Mock_Address_2".to_string()]),
+            ..mock_reserve_config()
+        },
+    ) {
+        Err(ContractError::InvalidAddress {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    update_reserve(
+        deps.as_mut(),
+        ReserveConfig {
+            borrower_allowlist: Some(vec![MOCK_ADDRESS_1.to_string()]),
+            ..mock_reserve_config()
+        },
+    )
+    .unwrap();
+    match mock_borrow(deps.as_mut(), 100u128, 1000u128) {
+        Err(ContractError::BorrowerRequirementsNotMet {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    update_reserve(
+        deps.as_mut(),
+        ReserveConfig {
+            borrower_allowlist: Some(vec![MOCK_ADDRESS_2.to_string()]),
+            min_loans_repaid: Some(1u64),
+            ..mock_reserve_config()
+        },
+    )
+    .unwrap();
+    match mock_borrow(deps.as_mut(), 100u128, 1000u128) {
+        Err(ContractError::BorrowerRequirementsNotMet {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let config = ReserveConfig {
+        borrower_allowlist: Some(vec![MOCK_ADDRESS_2.to_string()]),
+        max_loans_liquidated: Some(0u64),
+        max_outstanding_debt: Some(Uint128::from(600u128)),
+        ..mock_reserve_config()
+    };
+    update_reserve(deps.as_mut(), config.clone()).unwrap();
+    mock_borrow(deps.as_mut(), 400u128, 1000u128).unwrap();
+    match mock_borrow(deps.as_mut(), 300u128, 1000u128) {
+        Err(ContractError::BorrowerRequirementsNotMet {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    mock_borrow(deps.as_mut(), 200u128, 1000u128).unwrap();
+
+    let reserve = from_binary::<ReserveResponse>(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Reserve {
+                asset_info: AssetInfo::NativeToken {
+                    denom: MOCK_NATIVE_TOKEN_0.to_string(),
+                },
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(reserve.config, config);
+
+    let info = mock_info(
+        MOCK_ADDRESS_2,
+        &[Coin {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            amount: Uint128::from(400u128),
+        }],
+    );
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::RepayNativeToken { id: 1u64 },
+    )
+    .unwrap();
+
+    update_reserve(
+        deps.as_mut(),
+        ReserveConfig {
+            min_loans_repaid: Some(1u64),
+            ..config
+        },
+    )
+    .unwrap();
+    match mock_borrow(deps.as_mut(), 401u128, 1000u128) {
+        Err(ContractError::BorrowerRequirementsNotMet {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    mock_borrow(deps.as_mut(), 400u128, 1000u128).unwrap();
+}
+
+/// ## Description
+/// Test if repaying works properly
+/// ## Test case 0
+/// Input: Partial repayment followed by an overpaying full repayment
+/// Output: Collateral returned to the borrower, excess refunded and interest earned by
+/// the lender
+#[test]
+fn repay_test_case_0() {
+    let mut deps = mock_dependencies(&[]);
+    mock_reserve(deps.as_mut(), 1000u128);
+    mock_borrow(deps.as_mut(), 500u128, 1000u128).unwrap();
+
+    let info = mock_info(
+        MOCK_ADDRESS_2,
+        &[Coin {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            amount: Uint128::from(300u128),
+        }],
+    );
+    let res = execute(
+        deps.as_mut(),
+        mock_env_after(100_000_000u64),
+        info,
+        ExecuteMsg::RepayNativeToken { id: 1u64 },
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+
+    let loan = from_binary::<LoanResponse>(
+        &query(
+            deps.as_ref(),
+            mock_env_after(100_000_000u64),
+            QueryMsg::Loan { id: 1u64 },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(loan.debt, Uint128::from(234u128));
+
+    let info = mock_info(
+        MOCK_ADDRESS_2,
+        &[Coin {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            amount: Uint128::from(300u128),
+        }],
+    );
+    let res = execute(
+        deps.as_mut(),
+        mock_env_after(100_000_000u64),
+        info,
+        ExecuteMsg::RepayNativeToken { id: 1u64 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages
+            .into_iter()
+            .map(|msg| msg.msg)
+            .collect::<Vec<CosmosMsg>>(),
+        vec![
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: MOCK_ADDRESS_2.to_string(),
+                amount: vec![Coin {
+                    denom: MOCK_NATIVE_TOKEN_1.to_string(),
+                    amount: Uint128::from(1000u128),
+                }],
+            }),
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: MOCK_ADDRESS_2.to_string(),
+                amount: vec![Coin {
+                    denom: MOCK_NATIVE_TOKEN_0.to_string(),
+                    amount: Uint128::from(66u128),
+                }],
+            }),
+        ]
+    );
+
+    assert!(query(
+        deps.as_ref(),
+        mock_env_after(100_000_000u64),
+        QueryMsg::Loan { id: 1u64 }
+    )
+    .is_err());
+    let reserve = from_binary::<ReserveResponse>(
+        &query(
+            deps.as_ref(),
+            mock_env_after(100_000_000u64),
+            QueryMsg::Reserve {
+                asset_info: AssetInfo::NativeToken {
+                    denom: MOCK_NATIVE_TOKEN_0.to_string(),
+                },
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(reserve.liquidity, Uint128::from(1034u128));
+    assert_eq!(reserve.total_debt, Uint128::zero());
+    assert_eq!(reserve.total_shares, Uint128::from(1000u128));
+}
+
+/// ## Description
+/// Test if protocol-wide statistics work properly
+/// ## Test case 0
+/// Input: Borrow from a reserve, make borrow request with the same collateral, then repay
+/// the loan in part and in full
+/// Output: Collateral escrowed by the request and the loan, and principal outstanding, volume
+/// lent, interest paid and active loans of the borrowed asset, repayments going to interest
+/// first
+#[test]
+fn stats_test_case_0() {
+    let mut deps = mock_dependencies(&[]);
+    mock_reserve(deps.as_mut(), 1000u128);
+    mock_borrow(deps.as_mut(), 500u128, 1000u128).unwrap();
+
+    let info = mock_info(
+        MOCK_ADDRESS_0,
+        &[Coin {
+            denom: MOCK_NATIVE_TOKEN_1.to_string(),
+            amount: Uint128::from(500u128),
+        }],
+    );
+    let msg = ExecuteMsg::MakeBorrowRequestNativeToken {
+        period: Duration::Height(17200u64),
+        lender: None,
+        expires_at: None,
+    };
+    execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let stats = |deps: Deps| {
+        from_binary::<StatsResponse>(&query(deps, mock_env(), QueryMsg::Stats {}).unwrap())
+            .unwrap()
+            .assets
+    };
+    let asset_stats = |denom: &str,
+                       collateral_escrowed: u128,
+                       principal_outstanding: u128,
+                       interest_paid: u128,
+                       active_loans: u64| AssetStatsResponse {
+        asset_info: AssetInfo::NativeToken {
+            denom: denom.to_string(),
+        },
+        collateral_escrowed: Uint128::from(collateral_escrowed),
+        principal_outstanding: Uint128::from(principal_outstanding),
+        volume_lent: Uint128::from(if denom == MOCK_NATIVE_TOKEN_0 {
+            500u128
+        } else {
+            0u128
+        }),
+        interest_paid: Uint128::from(interest_paid),
+        active_loans,
+        defaulted_loans: 0u64,
+        liquidated_loans: 0u64,
+    };
+    assert_eq!(
+        stats(deps.as_ref()),
+        vec![
+            asset_stats(MOCK_NATIVE_TOKEN_0, 0u128, 500u128, 0u128, 1u64),
+            asset_stats(MOCK_NATIVE_TOKEN_1, 1500u128, 0u128, 0u128, 0u64),
+        ]
+    );
+
+    let info = mock_info(
+        MOCK_ADDRESS_2,
+        &[Coin {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            amount: Uint128::from(300u128),
+        }],
+    );
+    let msg = ExecuteMsg::RepayNativeToken { id: 1u64 };
+    execute(
+        deps.as_mut(),
+        mock_env_after(100_000_000u64),
+        info.clone(),
+        msg.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        stats(deps.as_ref()),
+        vec![
+            asset_stats(MOCK_NATIVE_TOKEN_0, 0u128, 233u128, 33u128, 1u64),
+            asset_stats(MOCK_NATIVE_TOKEN_1, 1500u128, 0u128, 0u128, 0u64),
+        ]
+    );
+
+    execute(deps.as_mut(), mock_env_after(100_000_000u64), info, msg).unwrap();
+    assert_eq!(
+        stats(deps.as_ref()),
+        vec![
+            asset_stats(MOCK_NATIVE_TOKEN_0, 0u128, 0u128, 34u128, 0u64),
+            asset_stats(MOCK_NATIVE_TOKEN_1, 500u128, 0u128, 0u128, 0u64),
+        ]
+    );
+}
+
+/// ## Description
+/// Test if liquidating works properly
+/// ## Test case 0
+/// Input: Healthy loan, then the same loan after the collateral price halves
+/// Output: Error for the healthy loan, then collateral worth the debt plus bonus sent to
+/// the liquidator and the rest returned to the borrower
+#[test]
+fn liquidate_test_case_0() {
+    let mut deps = mock_dependencies(&[]);
+    mock_reserve(deps.as_mut(), 1000u128);
+    mock_borrow(deps.as_mut(), 500u128, 1000u128).unwrap();
+
+    let info = mock_info(
+        MOCK_ADDRESS_1,
+        &[Coin {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            amount: Uint128::from(500u128),
+        }],
+    );
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        info.clone(),
+        ExecuteMsg::LiquidateNativeToken { id: 1u64 },
+    ) {
+        Err(ContractError::NotLiquidatable {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UpdateReserve {
+        asset_info: AssetInfo::NativeToken {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+        },
+        config: ReserveConfig {
+            collateral_price: Decimal256::percent(60u64),
+            ..mock_reserve_config()
+        },
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_0, &[]),
+        msg,
+    )
+    .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::LiquidateNativeToken { id: 1u64 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages
+            .into_iter()
+            .map(|msg| msg.msg)
+            .collect::<Vec<CosmosMsg>>(),
+        vec![
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: MOCK_ADDRESS_1.to_string(),
+                amount: vec![Coin {
+                    denom: MOCK_NATIVE_TOKEN_1.to_string(),
+                    amount: Uint128::from(875u128),
+                }],
+            }),
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: MOCK_ADDRESS_2.to_string(),
+                amount: vec![Coin {
+                    denom: MOCK_NATIVE_TOKEN_1.to_string(),
+                    amount: Uint128::from(125u128),
+                }],
+            }),
+        ]
+    );
+}
+
+/// ## Description
+/// Test if simulating repayment works properly
+/// ## Test case 0
+/// Input: Loan simulated 1000 blocks ahead, then repaid with the amount due at that height
+/// Output: Amount due split into interest and principal, repaid in full with nothing left,
+/// and an error for a height in the past
+#[test]
+fn simulate_repay_test_case_0() {
+    let mut deps = mock_dependencies(&[]);
+    mock_reserve(deps.as_mut(), 1000u128);
+    mock_borrow(deps.as_mut(), 500u128, 1000u128).unwrap();
+
+    let env = mock_env_after(100_000_000u64);
+    let simulation = from_binary::<SimulateRepayResponse>(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SimulateRepay {
+                id: 1u64,
+                at_height: Some(env.block.height),
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(simulation.at_height, env.block.height);
+    assert_eq!(simulation.debt, simulation.interest + simulation.principal);
+    assert_eq!(simulation.principal, Uint128::from(500u128));
+    assert!(!simulation.interest.is_zero());
+
+    let current = from_binary::<SimulateRepayResponse>(
+        &query(
+            deps.as_ref(),
+            env.clone(),
+            QueryMsg::SimulateRepay {
+                id: 1u64,
+                at_height: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(current, simulation);
+
+    assert!(query(
+        deps.as_ref(),
+        env.clone(),
+        QueryMsg::SimulateRepay {
+            id: 1u64,
+            at_height: Some(mock_env().block.height),
+        },
+    )
+    .is_err());
+
+    let info = mock_info(
+        MOCK_ADDRESS_2,
+        &[Coin {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            amount: simulation.debt,
+        }],
+    );
+    let res = execute(
+        deps.as_mut(),
+        env,
+        info,
+        ExecuteMsg::RepayNativeToken { id: 1u64 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes[2],
+        Attribute::new("repaid", simulation.debt.to_string())
+    );
+    assert_eq!(res.attributes[3], Attribute::new("remaining", "0"));
+}
+
+/// ## Description
+/// Test if simulating liquidation and loan health work properly
+/// ## Test case 0
+/// Input: Healthy loan, then the same loan after the collateral price drops below its
+/// liquidation price, then liquidated
+/// Output: Health and outcome of the healthy loan, then the outcome of the liquidation
+/// matching the collateral actually seized and returned
+#[test]
+fn simulate_liquidation_test_case_0() {
+    let mut deps = mock_dependencies(&[]);
+    mock_reserve(deps.as_mut(), 1000u128);
+    mock_borrow(deps.as_mut(), 500u128, 1000u128).unwrap();
+
+    let health = from_binary::<LoanHealthResponse>(
+        &query(deps.as_ref(), mock_env(), QueryMsg::LoanHealth { id: 1u64 }).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        health,
+        LoanHealthResponse {
+            id: 1u64,
+            collateral_value: Uint128::from(1000u128),
+            debt: Uint128::from(500u128),
+            ltv: Some(Decimal256::percent(50u64)),
+            liquidation_threshold: Decimal256::percent(75u64),
+            liquidation_price: Some(Decimal256::from_ratio(2u64, 3u64)),
+        }
+    );
+
+    let simulation = from_binary::<SimulateLiquidationResponse>(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SimulateLiquidation { id: 1u64 },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        simulation,
+        SimulateLiquidationResponse {
+            id: 1u64,
+            liquidatable: false,
+            lender_payout: Uint128::from(500u128),
+            bonus: Uint128::from(25u128),
+            seized_collateral: Uint128::from(525u128),
+            borrower_refund: Uint128::from(475u128),
+        }
+    );
+
+    let msg = ExecuteMsg::UpdateReserve {
+        asset_info: AssetInfo::NativeToken {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+        },
+        config: ReserveConfig {
+            collateral_price: Decimal256::percent(60u64),
+            ..mock_reserve_config()
+        },
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_0, &[]),
+        msg,
+    )
+    .unwrap();
+
+    let simulation = from_binary::<SimulateLiquidationResponse>(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::SimulateLiquidation { id: 1u64 },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(simulation.liquidatable);
+
+    let info = mock_info(
+        MOCK_ADDRESS_1,
+        &[Coin {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            amount: simulation.lender_payout,
+        }],
+    );
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::LiquidateNativeToken { id: 1u64 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages
+            .into_iter()
+            .map(|msg| msg.msg)
+            .collect::<Vec<CosmosMsg>>(),
+        vec![
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: MOCK_ADDRESS_1.to_string(),
+                amount: vec![Coin {
+                    denom: MOCK_NATIVE_TOKEN_1.to_string(),
+                    amount: simulation.seized_collateral,
+                }],
+            }),
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: MOCK_ADDRESS_2.to_string(),
+                amount: vec![Coin {
+                    denom: MOCK_NATIVE_TOKEN_1.to_string(),
+                    amount: simulation.borrower_refund,
+                }],
+            }),
+        ]
+    );
+}
+
+/// ## Description
+/// Test if querying liquidatable loans works properly
+/// ## Test case 0
+/// Input: Three loans as the collateral price drops, then one liquidated and another
+/// partially repaid
+/// Output: Loans over the liquidation threshold from the highest debt per unit of collateral,
+/// paged by debt ratio, and dropped once liquidated or healthy again
+#[test]
+fn liquidatable_loans_test_case_0() {
+    let mut deps = mock_dependencies(&[]);
+    mock_reserve(deps.as_mut(), 2000u128);
+    for amount in [400u128, 500u128, 300u128] {
+        mock_borrow(deps.as_mut(), amount, 1000u128).unwrap();
+    }
+
+    let query_loans =
+        |deps: Deps, start_after: Option<LoanCursor>, limit: Option<u32>| -> Vec<LoanResponse> {
+            from_binary::<Vec<LoanResponse>>(
+                &query(
+                    deps,
+                    mock_env(),
+                    QueryMsg::LiquidatableLoans { start_after, limit },
+                )
+                .unwrap(),
+            )
+            .unwrap()
+        };
+    let query_ids = |deps: Deps, start_after: Option<LoanCursor>, limit: Option<u32>| {
+        query_loans(deps, start_after, limit)
+            .iter()
+            .map(|loan| loan.id)
+            .collect::<Vec<u64>>()
+    };
+    let set_price = |deps: DepsMut, price: Decimal256| {
+        let msg = ExecuteMsg::UpdateReserve {
+            asset_info: AssetInfo::NativeToken {
+                denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            },
+            config: ReserveConfig {
+                collateral_price: price,
+                ..mock_reserve_config()
+            },
+        };
+        execute(deps, mock_env(), mock_info(MOCK_ADDRESS_0, &[]), msg).unwrap();
+    };
+
+    assert_eq!(query_ids(deps.as_ref(), None, None), Vec::<u64>::new());
+
+    set_price(deps.as_mut(), Decimal256::percent(60u64));
+    assert_eq!(query_ids(deps.as_ref(), None, None), vec![2u64]);
+
+    set_price(deps.as_mut(), Decimal256::percent(50u64));
+    assert_eq!(query_ids(deps.as_ref(), None, None), vec![2u64, 1u64]);
+    let page = query_loans(deps.as_ref(), None, Some(1u32));
+    assert_eq!(
+        page.iter().map(|loan| loan.id).collect::<Vec<u64>>(),
+        vec![2u64]
+    );
+    let cursor = mock_loan_cursor(&page[0]);
+    assert_eq!(
+        query_ids(deps.as_ref(), Some(cursor.clone()), Some(1u32)),
+        vec![1u64]
+    );
+
+    let info = mock_info(
+        MOCK_ADDRESS_1,
+        &[Coin {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            amount: Uint128::from(500u128),
+        }],
+    );
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::LiquidateNativeToken { id: 2u64 },
+    )
+    .unwrap();
+    assert_eq!(query_ids(deps.as_ref(), None, None), vec![1u64]);
+    assert_eq!(query_ids(deps.as_ref(), Some(cursor), None), vec![1u64]);
+
+    let info = mock_info(
+        MOCK_ADDRESS_2,
+        &[Coin {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            amount: Uint128::from(100u128),
+        }],
+    );
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::RepayNativeToken { id: 1u64 },
+    )
+    .unwrap();
+    assert_eq!(query_ids(deps.as_ref(), None, None), Vec::<u64>::new());
+}
+
+/// ## Description
+/// Test if querying liquidatable loans works properly
+/// ## Test case 1
+/// Input: Three liquidatable loans paged one at a time, with the loan a page ended at repaid
+/// in full before the next page
+/// Output: The next page starting after the repaid loan instead of over
+#[test]
+fn liquidatable_loans_test_case_1() {
+    let mut deps = mock_dependencies(&[]);
+    mock_reserve(deps.as_mut(), 2000u128);
+    for amount in [400u128, 500u128, 300u128] {
+        mock_borrow(deps.as_mut(), amount, 1000u128).unwrap();
+    }
+
+    let msg = ExecuteMsg::UpdateReserve {
+        asset_info: AssetInfo::NativeToken {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+        },
+        config: ReserveConfig {
+            collateral_price: Decimal256::percent(35u64),
+            ..mock_reserve_config()
+        },
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_0, &[]),
+        msg,
+    )
+    .unwrap();
+
+    let query_loans = |deps: Deps, start_after: Option<LoanCursor>| -> Vec<LoanResponse> {
+        from_binary::<Vec<LoanResponse>>(
+            &query(
+                deps,
+                mock_env(),
+                QueryMsg::LiquidatableLoans {
+                    start_after,
+                    limit: Some(1u32),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+
+    let page = query_loans(deps.as_ref(), None);
+    assert_eq!(page[0].id, 2u64);
+    let page = query_loans(deps.as_ref(), Some(mock_loan_cursor(&page[0])));
+    assert_eq!(page[0].id, 1u64);
+
+    let info = mock_info(
+        MOCK_ADDRESS_2,
+        &[Coin {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            amount: Uint128::from(400u128),
+        }],
+    );
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::RepayNativeToken { id: 1u64 },
+    )
+    .unwrap();
+    assert!(query(deps.as_ref(), mock_env(), QueryMsg::Loan { id: 1u64 }).is_err());
+
+    let page = query_loans(deps.as_ref(), Some(mock_loan_cursor(&page[0])));
+    assert_eq!(
+        page.iter().map(|loan| loan.id).collect::<Vec<u64>>(),
+        vec![3u64]
+    );
+}
+
+/// ## Description
+/// Test if querying liquidatable loans works properly
+/// ## Test case 2
+/// Input: Healthy loan listed first with the same rounded debt ratio as a liquidatable loan
+/// Output: Liquidatable loan listed past the healthy loan
+#[test]
+fn liquidatable_loans_test_case_2() {
+    let mut deps = mock_dependencies(&[]);
+    mock_reserve(deps.as_mut(), 2000u128);
+
+    let msg = ExecuteMsg::UpdateReserve {
+        asset_info: AssetInfo::NativeToken {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+        },
+        config: ReserveConfig {
+            collateral_price: Decimal256::from_ratio(
+                444_444_444_444_444_444u128,
+                1_000_000_000_000_000_000u128,
+            ),
+            ..mock_reserve_config()
+        },
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_0, &[]),
+        msg,
+    )
+    .unwrap();
+
+    // A debt ratio of exactly 1/3 is rounded down to the ratio of the healthy loan, which
+    // is listed first as ties are broken by the highest id
+    let borrower = deps.api.addr_canonicalize(MOCK_ADDRESS_2).unwrap();
+    for (id, scaled_debt, collateral) in [
+        (
+            1u64,
+            1_000_000_000_000_000_000u128,
+            3_000_000_000_000_000_000u128,
+        ),
+        (
+            2u64,
+            333_333_333_333_333_333u128,
+            1_000_000_000_000_000_000u128,
+        ),
+    ] {
+        let loan = Loan {
+            id,
+            borrower: borrower.clone(),
+            asset_info: AssetInfo::NativeToken {
+                denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            },
+            collateral: Asset {
+                info: AssetInfo::NativeToken {
+                    denom: MOCK_NATIVE_TOKEN_1.to_string(),
+                },
+                amount: Uint128::from(collateral),
+            },
+            scaled_debt: Uint128::from(scaled_debt),
+            principal: Uint128::from(scaled_debt),
+            borrowed_at: mock_env().block.height,
+        };
+        store_loan(deps.as_mut().storage, &loan).unwrap();
+    }
+
+    let loans = from_binary::<Vec<LoanResponse>>(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::LiquidatableLoans {
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        loans.iter().map(|loan| loan.id).collect::<Vec<u64>>(),
+        vec![1u64]
+    );
+}
+
+/// ## Description
+/// Test if borrower profile works properly
+/// ## Test case 0
+/// Input: Two loans, one repaid in full and the other liquidated
+/// Output: Profile counting the loans taken, repaid and liquidated and the principal borrowed,
+/// and statistics with no loan or collateral left
+#[test]
+fn borrower_profile_test_case_0() {
+    let mut deps = mock_dependencies(&[]);
+    mock_reserve(deps.as_mut(), 1000u128);
+    mock_borrow(deps.as_mut(), 200u128, 400u128).unwrap();
+    mock_borrow(deps.as_mut(), 300u128, 600u128).unwrap();
+
+    let info = mock_info(
+        MOCK_ADDRESS_2,
+        &[Coin {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            amount: Uint128::from(200u128),
+        }],
+    );
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::RepayNativeToken { id: 1u64 },
+    )
+    .unwrap();
+
+    let msg = ExecuteMsg::UpdateReserve {
+        asset_info: AssetInfo::NativeToken {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+        },
+        config: ReserveConfig {
+            collateral_price: Decimal256::percent(50u64),
+            ..mock_reserve_config()
+        },
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_0, &[]),
+        msg,
+    )
+    .unwrap();
+
+    let info = mock_info(
+        MOCK_ADDRESS_1,
+        &[Coin {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            amount: Uint128::from(300u128),
+        }],
+    );
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::LiquidateNativeToken { id: 2u64 },
+    )
+    .unwrap();
+
+    assert_eq!(
+        from_binary::<BorrowerProfileResponse>(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::BorrowerProfile {
+                    address: MOCK_ADDRESS_2.to_string(),
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap(),
+        BorrowerProfileResponse {
+            address: MOCK_ADDRESS_2.to_string(),
+            loans_taken: 2u64,
+            loans_repaid_on_time: 1u64,
+            loans_repaid_late: 0u64,
+            loans_defaulted: 0u64,
+            loans_liquidated: 1u64,
+            principal_borrowed: vec![Asset {
+                info: AssetInfo::NativeToken {
+                    denom: MOCK_NATIVE_TOKEN_0.to_string(),
+                },
+                amount: Uint128::from(500u128),
+            }],
+        }
+    );
+
+    let stats = from_binary::<StatsResponse>(
+        &query(deps.as_ref(), mock_env(), QueryMsg::Stats {}).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(stats.assets[0].principal_outstanding, Uint128::zero());
+    assert_eq!(stats.assets[0].volume_lent, Uint128::from(500u128));
+    assert_eq!(stats.assets[0].active_loans, 0u64);
+    assert_eq!(stats.assets[0].liquidated_loans, 1u64);
+    assert_eq!(stats.assets[1].collateral_escrowed, Uint128::zero());
+}
+
+/// ## Description
+/// Test if flash loan works properly
+/// ## Test case 0
+/// Input: Flash loan of the whole reserve, then pool operations and verification before and
+/// after the flash loan is returned with its fee
+/// Output: Flash loan, callback and verification sent, other operations rejected until the
+/// flash loan is returned and the fee credited to lenders
+#[test]
+fn flash_loan_test_case_0() {
+    let mut deps = mock_dependencies(&[Coin {
+        denom: MOCK_NATIVE_TOKEN_0.to_string(),
+        amount: Uint128::from(1000u128),
+    }]);
+    mock_reserve(deps.as_mut(), 1000u128);
+
+    let asset_info = AssetInfo::NativeToken {
+        denom: MOCK_NATIVE_TOKEN_0.to_string(),
+    };
+    let callback_msg = to_binary("callback").unwrap();
+    let msg = ExecuteMsg::FlashLoan {
+        asset: asset_info.clone(),
+        amount: Uint128::from(1000u128),
+        callback_msg: callback_msg.clone(),
+    };
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_2, &[]),
+        msg,
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages
+            .into_iter()
+            .map(|msg| msg.msg)
+            .collect::<Vec<CosmosMsg>>(),
+        vec![
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: MOCK_ADDRESS_2.to_string(),
+                amount: vec![Coin {
+                    denom: MOCK_NATIVE_TOKEN_0.to_string(),
+                    amount: Uint128::from(1000u128),
+                }],
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_ADDRESS_2.to_string(),
+                msg: callback_msg,
+                funds: vec![],
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_CONTRACT_ADDR.to_string(),
+                msg: to_binary(&ExecuteMsg::FinishFlashLoan {}).unwrap(),
+                funds: vec![],
+            }),
+        ]
+    );
+
+    let info = mock_info(
+        MOCK_ADDRESS_1,
+        &[Coin {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    );
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        ExecuteMsg::DepositNativeToken {},
+    ) {
+        Err(ContractError::FlashLoanInProgress {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_2, &[]),
+        ExecuteMsg::FinishFlashLoan {},
+    ) {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::FinishFlashLoan {},
+    ) {
+        Err(ContractError::FlashLoanNotReturned {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    deps.querier.update_balance(
+        MOCK_CONTRACT_ADDR,
+        vec![Coin {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            amount: Uint128::from(1001u128),
+        }],
+    );
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_CONTRACT_ADDR, &[]),
+        ExecuteMsg::FinishFlashLoan {},
+    )
+    .unwrap();
+
+    let reserve = from_binary::<ReserveResponse>(
+        &query(deps.as_ref(), mock_env(), QueryMsg::Reserve { asset_info }).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(reserve.liquidity, Uint128::from(1001u128));
+    assert_eq!(reserve.total_shares, Uint128::from(1000u128));
+}
+
+/// ## Description
+/// Test if flash loan works properly
+/// ## Test case 1
+/// Input: Flash loan exceeding the liquidity of the reserve
+/// Output: Error
+#[test]
+fn flash_loan_test_case_1() {
+    let mut deps = mock_dependencies(&[]);
+    mock_reserve(deps.as_mut(), 1000u128);
+
+    let msg = ExecuteMsg::FlashLoan {
+        asset: AssetInfo::NativeToken {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+        },
+        amount: Uint128::from(1001u128),
+        callback_msg: to_binary("callback").unwrap(),
+    };
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_2, &[]),
+        msg,
+    ) {
+        Err(ContractError::InsufficientLiquidity {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+/// ## Description
+/// Test if batch works properly
+/// ## Test case 0
+/// Input: Borrow request, borrow and deposit in one batch with the sum of their funds
+/// Output: Every operation run with its own funds and their messages merged
+#[test]
+fn batch_test_case_0() {
+    let mut deps = mock_dependencies(&[]);
+    mock_reserve(deps.as_mut(), 1000u128);
+
+    let info = mock_info(
+        MOCK_ADDRESS_2,
+        &[
+            Coin {
+                denom: MOCK_NATIVE_TOKEN_1.to_string(),
+                amount: Uint128::from(500u128),
+            },
+            Coin {
+                denom: MOCK_NATIVE_TOKEN_0.to_string(),
+                amount: Uint128::from(50u128),
+            },
+        ],
+    );
+    let msg = ExecuteMsg::Batch(vec![
+        BatchMsg {
+            msg: ExecuteMsg::MakeBorrowRequestNativeToken {
+                period: Duration::Height(17200u64),
+                lender: None,
+                expires_at: None,
+            },
+            funds: vec![Coin {
+                denom: MOCK_NATIVE_TOKEN_1.to_string(),
+                amount: Uint128::from(100u128),
+            }],
+        },
+        BatchMsg {
+            msg: ExecuteMsg::BorrowNativeToken {
+                asset_info: AssetInfo::NativeToken {
+                    denom: MOCK_NATIVE_TOKEN_0.to_string(),
+                },
+                amount: Uint128::from(200u128),
+            },
+            funds: vec![Coin {
+                denom: MOCK_NATIVE_TOKEN_1.to_string(),
+                amount: Uint128::from(400u128),
+            }],
+        },
+        BatchMsg {
+            msg: ExecuteMsg::DepositNativeToken {},
+            funds: vec![Coin {
+                denom: MOCK_NATIVE_TOKEN_0.to_string(),
+                amount: Uint128::from(50u128),
+            }],
+        },
+    ]);
+    let res = execute(deps.as_mut(), mock_env(), info, msg).unwrap();
+    assert_eq!(
+        res.messages
+            .into_iter()
+            .map(|msg| msg.msg)
+            .collect::<Vec<CosmosMsg>>(),
+        vec![
+            CosmosMsg::Bank(BankMsg::Send {
+                to_address: MOCK_ADDRESS_2.to_string(),
+                amount: vec![Coin {
+                    denom: MOCK_NATIVE_TOKEN_0.to_string(),
+                    amount: Uint128::from(200u128),
+                }],
+            }),
+            CosmosMsg::Wasm(WasmMsg::Execute {
+                contract_addr: MOCK_SHARE_TOKEN_0.to_string(),
+                msg: to_binary(&Cw20ExecuteMsg::Mint {
+                    recipient: MOCK_ADDRESS_2.to_string(),
+                    amount: Uint128::from(50u128),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+        ]
+    );
+
+    let res = from_binary::<BorrowRequestResponse>(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BorrowRequestById { id: 1u64 },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.collateral.amount, Uint128::from(100u128));
+
+    let res = from_binary::<LoanResponse>(
+        &query(deps.as_ref(), mock_env(), QueryMsg::Loan { id: 1u64 }).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(res.collateral.amount, Uint128::from(400u128));
+}
+
+/// ## Description
+/// Test if batch works properly
+/// ## Test case 1
+/// Input: Batch with funds not matching its operations, with an operation that cannot be
+/// batched and with a failing operation
+/// Output: Error
+#[test]
+fn batch_test_case_1() {
+    let mut deps = mock_dependencies(&[]);
+    mock_reserve(deps.as_mut(), 1000u128);
+
+    let deposit = |amount: u128| BatchMsg {
+        msg: ExecuteMsg::DepositNativeToken {},
+        funds: vec![Coin {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            amount: Uint128::from(amount),
+        }],
+    };
+    let info = mock_info(
+        MOCK_ADDRESS_1,
+        &[Coin {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            amount: Uint128::from(100u128),
+        }],
+    );
+
+    let msg = ExecuteMsg::Batch(vec![deposit(50u128), deposit(60u128)]);
+    match execute(deps.as_mut(), mock_env(), info.clone(), msg) {
+        Err(ContractError::InvalidFunds {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::Batch(vec![
+        deposit(100u128),
+        BatchMsg {
+            msg: ExecuteMsg::Receive(Cw20ReceiveMsg {
+                sender: MOCK_ADDRESS_1.to_string(),
+                amount: Uint128::from(100u128),
+                msg: to_binary(&Cw20HookMsg::Deposit {}).unwrap(),
+            }),
+            funds: vec![],
+        },
+    ]);
+    match execute(deps.as_mut(), mock_env(), info.clone(), msg) {
+        Err(ContractError::InvalidBatchMsg {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::Batch(vec![
+        deposit(100u128),
+        BatchMsg {
+            msg: ExecuteMsg::CancelBorrowRequest { id: 1u64 },
+            funds: vec![],
+        },
+    ]);
+    match execute(deps.as_mut(), mock_env(), info, msg) {
+        Err(ContractError::BorrowRequestNotFound {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+/// ## Description
+/// Test if batch works properly
+/// ## Test case 2
+/// Input: Funds given to cancelling a borrow request and to pruning expired requests, in a
+/// batch and on their own
+/// Output: Error without the request cancelled
+#[test]
+fn batch_test_case_2() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info(MOCK_ADDRESS_0, &[]);
+    let msg = InstantiateMsg {
+        token_code_id: MOCK_TOKEN_CODE_ID,
+    };
+
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let funds = vec![Coin {
+        denom: MOCK_NATIVE_TOKEN_0.to_string(),
+        amount: Uint128::from(100u128),
+    }];
+    let msg = ExecuteMsg::MakeBorrowRequestNativeToken {
+        period: Duration::Height(17200u64),
+        lender: None,
+        expires_at: None,
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_0, &funds),
+        msg,
+    )
+    .unwrap();
+
+    for msg in [
+        ExecuteMsg::CancelBorrowRequest { id: 1u64 },
+        ExecuteMsg::PruneExpired { limit: None },
+    ] {
+        let batch = ExecuteMsg::Batch(vec![BatchMsg {
+            msg: msg.clone(),
+            funds: funds.clone(),
+        }]);
+        match execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MOCK_ADDRESS_0, &funds),
+            batch,
+        ) {
+            Err(ContractError::InvalidFunds {}) => (),
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+
+        match execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(MOCK_ADDRESS_0, &funds),
+            msg,
+        ) {
+            Err(ContractError::InvalidFunds {}) => (),
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
+
+    assert!(query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::BorrowRequestById { id: 1u64 }
+    )
+    .is_ok());
+}
+
+/// ## Description
+/// Test if opening IBC channels works properly
+/// ## Test case 0
+/// Input: Channel before its counterparty is allowed, counterparty allowed by non-owner and
+/// by owner, then ordered channel, channel of another version and valid channel
+/// Output: Error, error, then error, error and channel connected
+#[test]
+fn ibc_channel_test_case_0() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info(MOCK_ADDRESS_0, &[]);
+    let msg = InstantiateMsg {
+        token_code_id: MOCK_TOKEN_CODE_ID,
+    };
+
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    match ibc_channel_open(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_channel_open_try(MOCK_CHANNEL_0, IbcOrder::Unordered, IBC_VERSION),
+    ) {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::SetIbcCounterparty {
+        connection_id: MOCK_CONNECTION_0.to_string(),
+        port_id: Some(MOCK_COUNTERPARTY_PORT_0.to_string()),
+    };
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_1, &[]),
+        msg.clone(),
+    ) {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_0, &[]),
+        msg,
+    )
+    .unwrap();
+
+    match ibc_channel_open(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_channel_open_try(MOCK_CHANNEL_0, IbcOrder::Ordered, IBC_VERSION),
+    ) {
+        Err(ContractError::InvalidIbcOrder {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    match ibc_channel_open(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_channel_open_try(MOCK_CHANNEL_0, IbcOrder::Unordered, "ics20-1"),
+    ) {
+        Err(ContractError::InvalidIbcVersion {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    mock_ibc_channel(deps.as_mut(), MOCK_CHANNEL_0);
+}
+
+/// ## Description
+/// Test if receiving borrow requests over IBC works properly
+/// ## Test case 0
+/// Input: Packet with positive collateral, then packet with zero collateral
+/// Output: Borrow request with the collateral denom prefixed by its channel acknowledged,
+/// then error acknowledged without any borrow request made
+#[test]
+fn ibc_packet_receive_test_case_0() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info(MOCK_ADDRESS_0, &[]);
+    let msg = InstantiateMsg {
+        token_code_id: MOCK_TOKEN_CODE_ID,
+    };
+
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    mock_ibc_channel(deps.as_mut(), MOCK_CHANNEL_0);
+
+    let res = ibc_packet_receive(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_packet_recv(MOCK_CHANNEL_0, &mock_lending_packet(1000u128)).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        from_binary::<LendingAck>(&res.acknowledgement).unwrap(),
+        LendingAck::Result(to_binary(&1u64).unwrap()),
+    );
+
+    let borrow_request = from_binary::<BorrowRequestResponse>(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BorrowRequestById { id: 1u64 },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        borrow_request.collateral,
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: format!("our-port/{}/{}", MOCK_CHANNEL_0, MOCK_NATIVE_TOKEN_0),
+            },
+            amount: Uint128::from(1000u128),
+        }
+    );
+
+    let res = ibc_packet_receive(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_packet_recv(MOCK_CHANNEL_0, &mock_lending_packet(0u128)).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        from_binary::<LendingAck>(&res.acknowledgement).unwrap(),
+        LendingAck::Error(ContractError::InvalidAmount {}.to_string()),
+    );
+    assert_eq!(
+        from_binary::<StateResponse>(
+            &query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap()
+        )
+        .unwrap()
+        .request_count,
+        1u64,
+    );
+}
+
+/// ## Description
+/// Test if receiving borrow requests over IBC works properly
+/// ## Test case 1
+/// Input: Packet with collateral, then packet whose collateral overflows the escrowed total
+/// after the request count is increased
+/// Output: Remote collateral counted apart from the assets held by the contract, then error
+/// acknowledged without any storage change
+#[test]
+fn ibc_packet_receive_test_case_1() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info(MOCK_ADDRESS_0, &[]);
+    let msg = InstantiateMsg {
+        token_code_id: MOCK_TOKEN_CODE_ID,
+    };
+
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    mock_ibc_channel(deps.as_mut(), MOCK_CHANNEL_0);
+
+    ibc_packet_receive(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_packet_recv(MOCK_CHANNEL_0, &mock_lending_packet(1000u128)).unwrap(),
+    )
+    .unwrap();
+
+    let expected = StatsResponse {
+        assets: vec![],
+        remote_collateral: vec![RemoteCollateralStatsResponse {
+            channel_id: MOCK_CHANNEL_0.to_string(),
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            collateral_escrowed: Uint128::from(1000u128),
+        }],
+    };
+    assert_eq!(
+        from_binary::<StatsResponse>(
+            &query(deps.as_ref(), mock_env(), QueryMsg::Stats {}).unwrap()
+        )
+        .unwrap(),
+        expected,
+    );
+
+    let res = ibc_packet_receive(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_packet_recv(MOCK_CHANNEL_0, &mock_lending_packet(u128::MAX)).unwrap(),
+    )
+    .unwrap();
+    match from_binary::<LendingAck>(&res.acknowledgement).unwrap() {
+        LendingAck::Error(_) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    assert_eq!(
+        from_binary::<StateResponse>(
+            &query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap()
+        )
+        .unwrap()
+        .request_count,
+        1u64,
+    );
+    assert!(query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::BorrowRequestById { id: 2u64 }
+    )
+    .is_err());
+    assert_eq!(
+        from_binary::<StatsResponse>(
+            &query(deps.as_ref(), mock_env(), QueryMsg::Stats {}).unwrap()
+        )
+        .unwrap(),
+        expected,
+    );
+}
+
+/// ## Description
+/// Test if releasing remote collateral works properly
+/// ## Test case 0
+/// Input: Cancel a remote borrow request, then error acknowledged, timeout and success
+/// acknowledged to each release
+/// Output: Release packet sent, then the borrow request restored twice and removed at last
+#[test]
+fn ibc_release_collateral_test_case_0() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info(MOCK_ADDRESS_0, &[]);
+    let msg = InstantiateMsg {
+        token_code_id: MOCK_TOKEN_CODE_ID,
+    };
+
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    mock_ibc_channel(deps.as_mut(), MOCK_CHANNEL_0);
+
+    ibc_packet_receive(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_packet_recv(MOCK_CHANNEL_0, &mock_lending_packet(1000u128)).unwrap(),
+    )
+    .unwrap();
+
+    let packet = LendingPacket::ReleaseCollateral {
+        id: 1u64,
+        refund_address: MOCK_ADDRESS_2.to_string(),
+        collateral: Coin {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            amount: Uint128::from(1000u128),
+        },
+    };
+    let cancel = ExecuteMsg::CancelBorrowRequest { id: 1u64 };
+    let borrow_request = QueryMsg::BorrowRequestById { id: 1u64 };
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_1, &[]),
+        cancel.clone(),
+    )
+    .unwrap();
+    match &res.messages[0].msg {
+        CosmosMsg::Ibc(IbcMsg::SendPacket {
+            channel_id, data, ..
+        }) => {
+            assert_eq!(channel_id, MOCK_CHANNEL_0);
+            assert_eq!(from_binary::<LendingPacket>(data).unwrap(), packet);
+        }
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+    assert!(query(deps.as_ref(), mock_env(), borrow_request.clone()).is_err());
+
+    ibc_packet_ack(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_packet_ack(
+            MOCK_CHANNEL_0,
+            &packet,
+            IbcAcknowledgement::encode_json(&LendingAck::Error("error".to_string())).unwrap(),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(query(deps.as_ref(), mock_env(), borrow_request.clone()).is_ok());
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_1, &[]),
+        cancel.clone(),
+    )
+    .unwrap();
+    ibc_packet_timeout(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_packet_timeout(MOCK_CHANNEL_0, &packet).unwrap(),
+    )
+    .unwrap();
+    assert!(query(deps.as_ref(), mock_env(), borrow_request.clone()).is_ok());
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_1, &[]),
+        cancel.clone(),
+    )
+    .unwrap();
+    ibc_packet_ack(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_packet_ack(
+            MOCK_CHANNEL_0,
+            &packet,
+            IbcAcknowledgement::encode_json(&LendingAck::Result(Binary::default())).unwrap(),
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert!(query(deps.as_ref(), mock_env(), borrow_request).is_err());
+
+    match ibc_packet_timeout(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_packet_timeout(MOCK_CHANNEL_0, &packet).unwrap(),
+    ) {
+        Err(ContractError::BorrowRequestNotFound {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+/// ## Description
+/// Test if pruning expired borrow requests works properly
+/// ## Test case 1
+/// Input: Expired remote borrow request whose channel has closed, pruned, then removed by
+/// non-owner and owner
+/// Output: Request reported as stranded and kept escrowed, then removed from storage and
+/// statistics by the owner only
+#[test]
+fn prune_expired_test_case_1() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info(MOCK_ADDRESS_0, &[]);
+    let msg = InstantiateMsg {
+        token_code_id: MOCK_TOKEN_CODE_ID,
+    };
+
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    mock_ibc_channel(deps.as_mut(), MOCK_CHANNEL_0);
+
+    let packet = match mock_lending_packet(1000u128) {
+        LendingPacket::MakeBorrowRequest {
+            borrower,
+            refund_address,
+            collateral,
+            period,
+            lender,
+            ..
+        } => LendingPacket::MakeBorrowRequest {
+            borrower,
+            refund_address,
+            collateral,
+            period,
+            lender,
+            expires_at: Some(Expiration::AtHeight(mock_env().block.height + 10u64)),
+        },
+        _ => panic!("DO NOT ENTER HERE"),
+    };
+    ibc_packet_receive(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_packet_recv(MOCK_CHANNEL_0, &packet).unwrap(),
+    )
+    .unwrap();
+
+    let remove = ExecuteMsg::RemoveStrandedBorrowRequest { id: 1u64 };
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_0, &[]),
+        remove.clone(),
+    ) {
+        Err(ContractError::ChannelStillOpen {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    ibc_channel_close(
+        deps.as_mut(),
+        mock_env(),
+        mock_ibc_channel_close_init(MOCK_CHANNEL_0, IbcOrder::Unordered, IBC_VERSION),
+    )
+    .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env_after(11u64),
+        mock_info(MOCK_ADDRESS_2, &[]),
+        ExecuteMsg::PruneExpired { limit: None },
+    )
+    .unwrap();
+    assert!(res.messages.is_empty());
+    assert_eq!(
+        res.attributes,
+        vec![
+            Attribute::new("action", "prune_expired"),
+            Attribute::new("ids", ""),
+            Attribute::new("stranded", "1"),
+        ]
+    );
+    assert!(query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::BorrowRequestById { id: 1u64 }
+    )
+    .is_ok());
+
+    let collateral_escrowed = |deps: Deps| {
+        from_binary::<StatsResponse>(&query(deps, mock_env(), QueryMsg::Stats {}).unwrap())
+            .unwrap()
+            .remote_collateral[0]
+            .collateral_escrowed
+    };
+    assert_eq!(collateral_escrowed(deps.as_ref()), Uint128::from(1000u128));
+
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_1, &[]),
+        remove.clone(),
+    ) {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_0, &[]),
+        remove.clone(),
+    )
+    .unwrap();
+    assert_eq!(
+        res.attributes,
+        vec![
+            Attribute::new("action", "remove_stranded_borrow_request"),
+            Attribute::new("id", "1"),
+            Attribute::new("channel_id", MOCK_CHANNEL_0),
+            Attribute::new("refund_address", MOCK_ADDRESS_2),
+            Attribute::new("collateral", format!("1000{}", MOCK_NATIVE_TOKEN_0)),
+        ]
+    );
+    assert!(query(
+        deps.as_ref(),
+        mock_env(),
+        QueryMsg::BorrowRequestById { id: 1u64 }
+    )
+    .is_err());
+    assert_eq!(collateral_escrowed(deps.as_ref()), Uint128::zero());
+
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_0, &[]),
+        remove,
+    ) {
+        Err(ContractError::BorrowRequestNotFound {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+}
+
+/// ## Description
+/// Test if making borrow request with tokens transferred over ICS-20 works properly
+/// ## Test case 0
+/// Input: Denom trace of another token, malformed denom trace, then denom trace of the tokens
+/// Output: Error, error, then borrow request for the borrower with the IBC denom
+#[test]
+fn ibc_hook_test_case_0() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info(MOCK_ADDRESS_0, &[]);
+    let msg = InstantiateMsg {
+        token_code_id: MOCK_TOKEN_CODE_ID,
+    };
+
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+    mock_ibc_hook(deps.as_mut(), "transfer/channel-0");
+
+    let ibc_denom = "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2";
+    let info = mock_info(
+        MOCK_ADDRESS_2,
+        &[Coin {
+            denom: ibc_denom.to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    );
+    let msg = |denom_trace: &str| {
+        ExecuteMsg::IbcHook(IbcHookMsg::MakeBorrowRequest {
+            borrower: MOCK_ADDRESS_1.to_string(),
+            denom_trace: denom_trace.to_string(),
+            period: Duration::Height(17200u64),
+            lender: None,
+            expires_at: None,
+        })
+    };
+
+    for denom_trace in ["transfer/channel-1/uatom", "uatom"] {
+        match execute(deps.as_mut(), mock_env(), info.clone(), msg(denom_trace)) {
+            Err(ContractError::InvalidDenomTrace {}) => (),
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        info,
+        msg("transfer/channel-0/uatom"),
+    )
+    .unwrap();
+
+    let borrow_request = from_binary::<Vec<BorrowRequestResponse>>(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BorrowRequestByAddr {
+                borrower: MOCK_ADDRESS_1.to_string(),
+                include_directed: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        borrow_request[0].collateral,
+        Asset {
+            info: AssetInfo::NativeToken {
+                denom: ibc_denom.to_string(),
+            },
+            amount: Uint128::from(1000u128),
+        }
+    );
+}
+
+/// ## Description
+/// Test if making borrow request with tokens transferred over ICS-20 works properly
+/// ## Test case 1
+/// Input: Borrow request run by someone else than the borrower without a hook, set by
+/// non-owner, run by the borrower instead of the hook, then over another transfer channel
+/// Output: Error each time, then borrow request run by the borrower without a hook
+#[test]
+fn ibc_hook_test_case_1() {
+    let mut deps = mock_dependencies(&[]);
+    let info = mock_info(MOCK_ADDRESS_0, &[]);
+    let msg = InstantiateMsg {
+        token_code_id: MOCK_TOKEN_CODE_ID,
+    };
+
+    instantiate(deps.as_mut(), mock_env(), info, msg).unwrap();
+
+    let funds = [Coin {
+        denom: "ibc/27394FB092D2ECCD56123C74F36E4C1F926001CEADA9CA97EA622B25F41E5EB2".to_string(),
+        amount: Uint128::from(1000u128),
+    }];
+    let msg = ExecuteMsg::IbcHook(IbcHookMsg::MakeBorrowRequest {
+        borrower: MOCK_ADDRESS_1.to_string(),
+        denom_trace: "transfer/channel-0/uatom".to_string(),
+        period: Duration::Height(17200u64),
+        lender: None,
+        expires_at: None,
+    });
+
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_2, &funds),
+        msg.clone(),
+    ) {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_1, &[]),
+        ExecuteMsg::SetIbcHook {
+            hook: Some(MOCK_ADDRESS_1.to_string()),
+            transfer_channels: vec![],
+        },
+    ) {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    mock_ibc_hook(deps.as_mut(), "transfer/channel-1");
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_1, &funds),
+        msg.clone(),
+    ) {
+        Err(ContractError::Unauthorized {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_2, &funds),
+        msg.clone(),
+    ) {
+        Err(ContractError::TransferChannelNotAllowed {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_0, &[]),
+        ExecuteMsg::SetIbcHook {
+            hook: None,
+            transfer_channels: vec!["transfer/channel-0".to_string()],
+        },
+    )
+    .unwrap();
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_1, &funds),
+        msg,
+    )
+    .unwrap();
+}
+
+/// ## Description
+/// Test if validating addresses works properly
+/// ## Test case 0
+/// Input: Lender not normalized or too short, CW20 token asset not normalized in a reserve and
+/// in a borrow, and borrower and lender not normalized in queries
+/// Output: Invalid address error for each
+#[test]
+fn address_validation_test_case_0() {
+    let mut deps = mock_dependencies(&[]);
+    mock_reserve(deps.as_mut(), 1000u128);
+
+    let info = mock_info(
+        MOCK_ADDRESS_0,
+        &[Coin {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    );
+    for lender in ["Mock_Address_1", "ab"] {
+        let msg = ExecuteMsg::MakeBorrowRequestNativeToken {
+            period: Duration::Height(17200u64),
+            lender: Some(lender.to_string()),
+            expires_at: None,
+        };
+        match execute(deps.as_mut(), mock_env(), info.clone(), msg) {
+            Err(ContractError::InvalidAddress {}) => (),
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
+
+    let cw20 = AssetInfo::Token {
+        contract_addr: Addr::unchecked("CW20_Token_0"),
+    };
+    let msg = ExecuteMsg::CreateReserve {
+        asset_info: cw20.clone(),
+        config: mock_reserve_config(),
+    };
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_0, &[]),
+        msg,
+    ) {
+        Err(ContractError::InvalidAddress {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = ExecuteMsg::UpdateReserve {
+        asset_info: AssetInfo::NativeToken {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+        },
+        config: ReserveConfig {
+            collateral_info: cw20.clone(),
+            ..mock_reserve_config()
+        },
+    };
+    match execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_0, &[]),
+        msg,
+    ) {
+        Err(ContractError::InvalidAddress {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let info = mock_info(
+        MOCK_ADDRESS_2,
+        &[Coin {
+            denom: MOCK_NATIVE_TOKEN_1.to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    );
+    let msg = ExecuteMsg::BorrowNativeToken {
+        asset_info: cw20,
+        amount: Uint128::from(100u128),
+    };
+    match execute(deps.as_mut(), mock_env(), info, msg) {
+        Err(ContractError::InvalidAddress {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    for msg in [
+        QueryMsg::BorrowRequestByAddr {
+            borrower: "Mock_Address_0".to_string(),
+            include_directed: None,
+        },
+        QueryMsg::RequestsDirectedTo {
+            lender: "Mock_Address_1".to_string(),
+            start_after: None,
+            limit: None,
+        },
+        QueryMsg::BorrowerProfile {
+            address: "ab".to_string(),
+        },
+    ] {
+        match query(deps.as_ref(), mock_env(), msg) {
+            Err(StdError::GenericErr { msg, .. }) if msg == "Invalid address" => (),
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
+}
+
+/// ## Description
+/// Test if migrating works properly
+/// ## Test case 0
+/// Input: Storage of 0.1.0 with borrow requests of two borrowers, migrated without and then
+/// with the config, then migrated again
+/// Output: Error without the config, then requests, id map, indexes, statistics and config
+/// upgraded and the version bumped, and nothing to do when migrated again
+#[test]
+fn migrate_test_case_0() {
+    let mut deps = mock_dependencies(&[]);
+    mock_storage_0_1_0(deps.as_mut());
+
+    match migrate(
+        deps.as_mut(),
+        mock_env(),
+        MigrateMsg {
+            owner: None,
+            token_code_id: None,
+        },
+    ) {
+        Err(ContractError::InvalidMigrateMsg {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    let msg = MigrateMsg {
+        owner: Some(MOCK_ADDRESS_0.to_string()),
+        token_code_id: Some(MOCK_TOKEN_CODE_ID),
+    };
+    migrate(deps.as_mut(), mock_env(), msg.clone()).unwrap();
+    assert_eq!(
+        get_contract_version(deps.as_ref().storage).unwrap(),
+        ContractVersion {
+            contract: "jihoonsong-lending".to_string(),
+            version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    );
+
+    let state = from_binary::<StateResponse>(
+        &query(deps.as_ref(), mock_env(), QueryMsg::State {}).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        state,
+        StateResponse {
+            request_count: 3u64,
+            response_count: 0u64,
+            loan_count: 0u64,
+        }
+    );
+
+    let borrow_request = from_binary::<BorrowRequestResponse>(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BorrowRequestById { id: 2u64 },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(borrow_request.period, Duration::Height(100u64));
+    assert_eq!(borrow_request.lender, None);
+    assert_eq!(borrow_request.expires_at, None);
+
+    let all = from_binary::<Vec<BorrowRequestResponse>>(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BorrowRequests {
+                start_after: None,
+                limit: None,
+                include_directed: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        all.iter().map(|br| br.id).collect::<Vec<u64>>(),
+        vec![1u64, 2u64, 3u64]
+    );
+
+    let by_collateral = from_binary::<Vec<BorrowRequestResponse>>(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::BorrowRequestsByCollateral {
+                asset_info: AssetInfo::NativeToken {
+                    denom: MOCK_NATIVE_TOKEN_0.to_string(),
+                },
+                start_after: None,
+                limit: None,
+                include_directed: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        by_collateral.iter().map(|br| br.id).collect::<Vec<u64>>(),
+        vec![1u64, 3u64]
+    );
+
+    let stats = from_binary::<StatsResponse>(
+        &query(deps.as_ref(), mock_env(), QueryMsg::Stats {}).unwrap(),
+    )
+    .unwrap();
+    assert_eq!(
+        stats
+            .assets
+            .iter()
+            .map(|stats| stats.collateral_escrowed)
+            .collect::<Vec<Uint128>>(),
+        vec![Uint128::from(500u128), Uint128::from(3000u128)]
+    );
+
+    let msg_set = ExecuteMsg::SetIbcCounterparty {
+        connection_id: MOCK_CONNECTION_0.to_string(),
+        port_id: Some(MOCK_COUNTERPARTY_PORT_0.to_string()),
+    };
+    execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_0, &[]),
+        msg_set,
+    )
+    .unwrap();
+
+    let res = execute(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_1, &[]),
+        ExecuteMsg::CancelBorrowRequest { id: 3u64 },
+    )
+    .unwrap();
+    assert_eq!(
+        res.messages[0].msg,
+        CosmosMsg::Bank(BankMsg::Send {
+            to_address: MOCK_ADDRESS_1.to_string(),
+            amount: vec![Coin {
+                denom: MOCK_NATIVE_TOKEN_0.to_string(),
+                amount: Uint128::from(2000u128),
+            }],
+        })
+    );
+
+    let info = mock_info(
+        MOCK_ADDRESS_0,
+        &[Coin {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            amount: Uint128::from(1000u128),
+        }],
+    );
+    let msg_make = ExecuteMsg::MakeBorrowRequestNativeToken {
+        period: Duration::Height(17200u64),
+        lender: None,
+        expires_at: None,
+    };
+    let res = execute(deps.as_mut(), mock_env(), info, msg_make).unwrap();
+    assert_eq!(res.attributes[1], Attribute::new("request_count", "4"));
+
+    let res = migrate(deps.as_mut(), mock_env(), msg).unwrap();
+    assert_eq!(
+        res.attributes[1],
+        Attribute::new("from_version", env!("CARGO_PKG_VERSION"))
+    );
+}
+
+/// ## Description
+/// Test if migrating works properly
+/// ## Test case 1
+/// Input: Storage of another contract, then of a newer and of a malformed version
+/// Output: Error for each
+#[test]
+fn migrate_test_case_1() {
+    let mut deps = mock_dependencies(&[]);
+    mock_storage_0_1_0(deps.as_mut());
+    let msg = MigrateMsg {
+        owner: Some(MOCK_ADDRESS_0.to_string()),
+        token_code_id: Some(MOCK_TOKEN_CODE_ID),
+    };
+
+    set_contract_version(deps.as_mut().storage, "another-contract", "0.1.0").unwrap();
+    match migrate(deps.as_mut(), mock_env(), msg.clone()) {
+        Err(ContractError::CannotMigrateContract {}) => (),
+        _ => panic!("DO NOT ENTER HERE"),
+    }
+
+    for version in ["99.0.0", "0.1"] {
+        set_contract_version(deps.as_mut().storage, "jihoonsong-lending", version).unwrap();
+        match migrate(deps.as_mut(), mock_env(), msg.clone()) {
+            Err(ContractError::CannotMigrateVersion {}) => (),
+            _ => panic!("DO NOT ENTER HERE"),
+        }
+    }
+}
+
+/// ## Description
+/// Test if the checked-in schema matches the message and response types
+/// ## Test case 0
+/// Input: Schema of every message and response type
+/// Output: Exactly the files in the schema directory, which `cargo run --example schema`
+/// regenerates
+#[test]
+fn schema_test_case_0() {
+    let out_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("schema");
+    let schemas = schemas();
+
+    // Files are named after the snake case title, as `export_schema_with_title` does
+    let file_name = |title: &str| {
+        let mut file = String::new();
+        for (index, ch) in title.char_indices() {
+            if index != 0 && ch.is_uppercase() {
+                file.push('_');
+            }
+            file.push(ch.to_ascii_lowercase());
+        }
+        format!("{}.json", file)
+    };
+
+    for (title, mut schema) in schemas.iter().cloned() {
+        if let Some(metadata) = &mut schema.schema.metadata {
+            metadata.title = Some(title.to_string());
+        }
+        assert_eq!(
+            read_to_string(out_dir.join(file_name(title))).unwrap(),
+            serde_json::to_string_pretty(&schema).unwrap() + "\n",
+            "schema/{} is out of date, run `cargo run --example schema`",
+            file_name(title),
+        );
+    }
+
+    let mut files: Vec<String> = read_dir(&out_dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+        .collect();
+    files.sort();
+    let mut expected: Vec<String> = schemas.iter().map(|(title, _)| file_name(title)).collect();
+    expected.sort();
+    assert_eq!(files, expected);
+}
+
+/// Sets mock address 2 as the IBC hook with the given transfer channel
+fn mock_ibc_hook(deps: DepsMut, transfer_channel: &str) {
+    execute(
+        deps,
+        mock_env(),
+        mock_info(MOCK_ADDRESS_0, &[]),
+        ExecuteMsg::SetIbcHook {
+            hook: Some(MOCK_ADDRESS_2.to_string()),
+            transfer_channels: vec![transfer_channel.to_string()],
+        },
+    )
+    .unwrap();
+}
+
+/// Allows the mock counterparty and connects the given channel to it
+fn mock_ibc_channel(mut deps: DepsMut, channel_id: &str) {
+    execute(
+        deps.branch(),
+        mock_env(),
+        mock_info(MOCK_ADDRESS_0, &[]),
+        ExecuteMsg::SetIbcCounterparty {
+            connection_id: MOCK_CONNECTION_0.to_string(),
+            port_id: Some(MOCK_COUNTERPARTY_PORT_0.to_string()),
+        },
+    )
+    .unwrap();
+
+    ibc_channel_open(
+        deps.branch(),
+        mock_env(),
+        mock_ibc_channel_open_try(channel_id, IbcOrder::Unordered, IBC_VERSION),
+    )
+    .unwrap();
+    ibc_channel_connect(
+        deps,
+        mock_env(),
+        mock_ibc_channel_connect_ack(channel_id, IbcOrder::Unordered, IBC_VERSION),
+    )
+    .unwrap();
+}
+
+/// Returns a packet making borrow request for mock address 1 against the given amount of
+/// collateral locked on the remote chain
+fn mock_lending_packet(amount: u128) -> LendingPacket {
+    LendingPacket::MakeBorrowRequest {
+        borrower: MOCK_ADDRESS_1.to_string(),
+        refund_address: MOCK_ADDRESS_2.to_string(),
+        collateral: Coin {
+            denom: MOCK_NATIVE_TOKEN_0.to_string(),
+            amount: Uint128::from(amount),
+        },
+        period: Duration::Height(17200u64),
+        lender: None,
+        expires_at: None,
+    }
+}
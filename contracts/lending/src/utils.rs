@@ -1,8 +1,75 @@
 use astroport::asset::{Asset, AssetInfo};
-use cosmwasm_std::{Addr, Uint128};
+use cosmwasm_std::{
+    to_binary, Addr, Api, BankMsg, Binary, CanonicalAddr, Coin, CosmosMsg, StdError, StdResult,
+    Uint128, WasmMsg,
+};
+use cw20::Cw20ExecuteMsg;
+use sha2::{Digest, Sha256};
 
 use crate::error::ContractError;
 
+/// ## Description
+/// Returns the address validated by the chain, which must be normalized
+/// Otherwise returns [`ContractError::InvalidAddress`]
+/// ## Params
+/// * **api** is an object of type [`Api`]
+///
+/// * **addr** is an object of type [`str`]
+pub fn addr_validate(api: &dyn Api, addr: &str) -> Result<Addr, ContractError> {
+    api.addr_validate(addr)
+        .map_err(|_| ContractError::InvalidAddress {})
+}
+
+/// ## Description
+/// Returns the canonical form of a validated address, which is how addresses are stored
+/// and used as keys
+/// Otherwise returns [`ContractError::InvalidAddress`]
+/// ## Params
+/// * **api** is an object of type [`Api`]
+///
+/// * **addr** is an object of type [`str`]
+pub fn addr_canonicalize(api: &dyn Api, addr: &str) -> Result<CanonicalAddr, ContractError> {
+    let addr = addr_validate(api, addr)?;
+
+    api.addr_canonicalize(addr.as_str())
+        .map_err(|_| ContractError::InvalidAddress {})
+}
+
+/// ## Description
+/// Returns the asset info with the contract address of a CW20 token validated
+/// Otherwise returns [`ContractError`] if the address is invalid or the denom of a native
+/// token is empty
+/// ## Params
+/// * **api** is an object of type [`Api`]
+///
+/// * **asset_info** is an object of type [`AssetInfo`]
+pub fn validate_asset_info(
+    api: &dyn Api,
+    asset_info: AssetInfo,
+) -> Result<AssetInfo, ContractError> {
+    match asset_info {
+        AssetInfo::Token { contract_addr } => Ok(AssetInfo::Token {
+            contract_addr: addr_validate(api, contract_addr.as_str())?,
+        }),
+        AssetInfo::NativeToken { denom } if denom.is_empty() => Err(ContractError::InvalidAsset {}),
+        AssetInfo::NativeToken { denom } => Ok(AssetInfo::NativeToken { denom }),
+    }
+}
+
+/// ## Description
+/// Returns the addresses validated
+/// Otherwise returns [`ContractError::InvalidAddress`] if any of them is invalid
+/// ## Params
+/// * **api** is an object of type [`Api`]
+///
+/// * **addrs** is a [`Vec`] of [`String`]
+pub fn validate_addresses(api: &dyn Api, addrs: Vec<String>) -> Result<Vec<String>, ContractError> {
+    addrs
+        .iter()
+        .map(|addr| addr_validate(api, addr).map(|addr| addr.to_string()))
+        .collect()
+}
+
 /// ## Description
 /// Returns an object of type [`Asset`]
 /// Otherwise returns [`ContractError`]
@@ -38,3 +105,87 @@ pub fn to_native_token(denom: String, amount: Uint128) -> Result<Asset, Contract
         amount,
     })
 }
+
+/// ## Description
+/// Returns the IBC denom of an ICS-20 denom trace, which is `ibc/` followed by the
+/// uppercase hex SHA-256 hash of the trace
+/// Otherwise returns [`ContractError`] if the trace is not one or more port and channel
+/// pairs followed by a base denom
+/// ## Params
+/// * **denom_trace** is an object of type [`str`]
+pub fn to_ibc_denom(denom_trace: &str) -> Result<String, ContractError> {
+    let segments: Vec<&str> = denom_trace.split('/').collect();
+    let hops = segments
+        .chunks(2)
+        .take_while(|hop| hop.len() == 2 && !hop[0].is_empty() && hop[1].starts_with("channel-"))
+        .count();
+    let base_denom = segments[(hops * 2).min(segments.len())..].join("/");
+    if hops == 0 || base_denom.is_empty() {
+        return Err(ContractError::InvalidDenomTrace {});
+    }
+
+    Ok(format!(
+        "ibc/{}",
+        hex::encode_upper(Sha256::digest(denom_trace.as_bytes()))
+    ))
+}
+
+/// ## Description
+/// Returns a message of type [`CosmosMsg`] which sends the asset to the recipient
+/// Otherwise returns [`StdError`]
+/// ## Params
+/// * **asset** is an object of type [`Asset`]
+///
+/// * **recipient** is [`Addr`]
+pub fn to_transfer_msg(asset: &Asset, recipient: &Addr) -> StdResult<CosmosMsg> {
+    match &asset.info {
+        AssetInfo::Token { contract_addr } => Ok(CosmosMsg::Wasm(WasmMsg::Execute {
+            contract_addr: contract_addr.to_string(),
+            msg: to_binary(&Cw20ExecuteMsg::Transfer {
+                recipient: recipient.to_string(),
+                amount: asset.amount,
+            })?,
+            funds: vec![],
+        })),
+        AssetInfo::NativeToken { denom } => Ok(CosmosMsg::Bank(BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![Coin {
+                denom: denom.to_string(),
+                amount: asset.amount,
+            }],
+        })),
+    }
+}
+
+/// ## Description
+/// Returns the contract address in the data of a reply to `MsgInstantiateContract`,
+/// which is the length-delimited string of the first field of the protobuf response
+/// Otherwise returns [`StdError`]
+/// ## Params
+/// * **data** is an object of type [`Binary`]
+pub fn parse_instantiate_address(data: &Binary) -> StdResult<String> {
+    let err = || StdError::parse_err("MsgInstantiateContractResponse", "invalid data");
+    let bytes = data.as_slice();
+
+    if bytes.first() != Some(&0x0a) {
+        return Err(err());
+    }
+
+    let mut len = 0usize;
+    let mut pos = 1usize;
+    loop {
+        let byte = *bytes.get(pos).ok_or_else(err)?;
+        len |= ((byte & 0x7f) as usize) << (7 * (pos - 1));
+        pos += 1;
+        if byte & 0x80 == 0 {
+            break;
+        }
+        if pos > 5 {
+            return Err(err());
+        }
+    }
+
+    let addr = bytes.get(pos..pos + len).ok_or_else(err)?;
+
+    String::from_utf8(addr.to_vec()).map_err(|_| err())
+}
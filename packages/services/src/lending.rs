@@ -1,10 +1,17 @@
-use astroport::asset::Asset;
+use astroport::asset::{Asset, AssetInfo};
+use cosmwasm_std::{Binary, Coin, Decimal256, Uint128};
+use cw0::{Duration, Expiration};
 use cw20::Cw20ReceiveMsg;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 
+pub mod events;
+
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct InstantiateMsg {}
+pub struct InstantiateMsg {
+    /// Code id of the CW20 contract instantiated as the share token of each reserve
+    pub token_code_id: u64,
+}
 
 /// ## ExecuteMsg
 /// This structure describes execute messages
@@ -12,11 +19,148 @@ pub struct InstantiateMsg {}
 #[serde(rename_all = "snake_case")]
 pub enum ExecuteMsg {
     /// ## Description
-    /// Make borrow request with CW20 token
-    MakeBorrowRequestCw20Token(Cw20ReceiveMsg),
+    /// Receive CW20 token and run the attached [`Cw20HookMsg`]
+    Receive(Cw20ReceiveMsg),
+    /// ## Description
+    /// Run the attached [`IbcHookMsg`] with tokens transferred over ICS-20, as executed by an
+    /// IBC hook middleware on arrival of a transfer whose memo carries this message. Only the
+    /// hook set by the owner can execute it, or the borrower if none is set
+    IbcHook(IbcHookMsg),
+    /// ## Description
+    /// Make borrow request with native token, which only the lender can fund if given and
+    /// which can no longer be funded once expired if given
+    MakeBorrowRequestNativeToken {
+        period: Duration,
+        lender: Option<String>,
+        expires_at: Option<Expiration>,
+    },
+    /// ## Description
+    /// Cancel a borrow request that has not been borrowed yet and refund its collateral,
+    /// only the borrower can execute it
+    CancelBorrowRequest { id: u64 },
+    /// ## Description
+    /// Remove up to the given number of expired borrow requests and refund their collateral,
+    /// anyone can execute it
+    PruneExpired { limit: Option<u32> },
+    /// ## Description
+    /// Create a pooled reserve of the given asset, only the owner can execute it
+    CreateReserve {
+        asset_info: AssetInfo,
+        config: ReserveConfig,
+    },
+    /// ## Description
+    /// Update the config of a pooled reserve, only the owner can execute it
+    UpdateReserve {
+        asset_info: AssetInfo,
+        config: ReserveConfig,
+    },
+    /// ## Description
+    /// Deposit native token into its reserve in exchange for minted share tokens
+    DepositNativeToken {},
+    /// ## Description
+    /// Borrow from a reserve against native token collateral
+    BorrowNativeToken {
+        asset_info: AssetInfo,
+        amount: Uint128,
+    },
+    /// ## Description
+    /// Repay a pooled loan with native token
+    RepayNativeToken { id: u64 },
+    /// ## Description
+    /// Repay an unhealthy pooled loan with native token in exchange for its collateral
+    LiquidateNativeToken { id: u64 },
+    /// ## Description
+    /// Borrow from a reserve within a single transaction. The amount is sent to the caller,
+    /// the callback is executed on the caller, and the amount plus the flash loan fee must
+    /// be returned by the end of the callback
+    FlashLoan {
+        asset: AssetInfo,
+        amount: Uint128,
+        callback_msg: Binary,
+    },
+    /// ## Description
+    /// Verify that the ongoing flash loan has been returned, only the contract itself can
+    /// execute it
+    FinishFlashLoan {},
+    /// ## Description
+    /// Run several native token operations in order, all or nothing. The funds sent must
+    /// be exactly the sum of the funds of every [`BatchMsg`]
+    Batch(Vec<BatchMsg>),
+    /// ## Description
+    /// Allow channels over the given connection to a counterpart contract of the given port,
+    /// or disallow them if no port is given, only the owner can execute it
+    SetIbcCounterparty {
+        connection_id: String,
+        port_id: Option<String>,
+    },
+    /// ## Description
+    /// Set the IBC hook middleware allowed to execute [`IbcHookMsg`], or let borrowers
+    /// execute it themselves if none is given, and the `{port}/{channel}` transfer channels
+    /// tokens can arrive over, only the owner can execute it
+    SetIbcHook {
+        hook: Option<String>,
+        transfer_channels: Vec<String>,
+    },
+    /// ## Description
+    /// Remove a borrow request whose remote collateral can no longer be released because
+    /// its channel has closed, only the owner can execute it
+    RemoveStrandedBorrowRequest { id: u64 },
+    /// ## Description
+    /// Claim every CW20 payout to the caller whose transfer failed
+    ClaimPending {},
+}
+
+/// ## Description
+/// This structure describes an operation of [`ExecuteMsg::Batch`] and the funds it uses.
+/// Only making and cancelling borrow requests, pruning expired requests and the native
+/// token operations of pooled reserves can be batched.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BatchMsg {
+    /// Operation to run
+    pub msg: ExecuteMsg,
+    /// Funds sent along with the operation
+    pub funds: Vec<Coin>,
+}
+
+/// Version of the channels between the lending contract and its counterpart contracts
+pub const IBC_VERSION: &str = "lending-1";
+
+/// ## Description
+/// This structure describes packets between the lending contract and a counterpart contract
+/// on a remote chain, which locks collateral there on behalf of borrowers
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LendingPacket {
+    /// ## Description
+    /// Make borrow request against the collateral locked by the counterpart contract.
+    /// The id of the borrow request is acknowledged on success
+    MakeBorrowRequest {
+        /// Borrower address on the chain of the lending contract
+        borrower: String,
+        /// Address on the remote chain the collateral is refunded to
+        refund_address: String,
+        /// Collateral locked on the remote chain
+        collateral: Coin,
+        period: Duration,
+        lender: Option<String>,
+        expires_at: Option<Expiration>,
+    },
     /// ## Description
-    /// Make borrow request with native token
-    MakeBorrowRequestNativeToken { period: u64 },
+    /// Release the collateral of a cancelled or expired borrow request to its refund address
+    ReleaseCollateral {
+        id: u64,
+        refund_address: String,
+        collateral: Coin,
+    },
+}
+
+/// ## Description
+/// This structure describes the acknowledgement of a [`LendingPacket`]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LendingAck {
+    Result(Binary),
+    Error(String),
 }
 
 /// ## Cw20HookMsg
@@ -25,8 +169,49 @@ pub enum ExecuteMsg {
 #[serde(rename_all = "snake_case")]
 pub enum Cw20HookMsg {
     /// ## Description
-    /// Make borrow request
-    MakeBorrowRequest { period: u64 },
+    /// Make borrow request, which only the lender can fund if given and which can no longer
+    /// be funded once expired if given
+    MakeBorrowRequest {
+        period: Duration,
+        lender: Option<String>,
+        expires_at: Option<Expiration>,
+    },
+    /// ## Description
+    /// Deposit into the reserve of the sent token in exchange for minted share tokens
+    Deposit {},
+    /// ## Description
+    /// Burn the sent share tokens in exchange for the underlying asset of their reserve
+    Withdraw {},
+    /// ## Description
+    /// Borrow from a reserve against the sent token as collateral
+    Borrow {
+        asset_info: AssetInfo,
+        amount: Uint128,
+    },
+    /// ## Description
+    /// Repay a pooled loan
+    Repay { id: u64 },
+    /// ## Description
+    /// Repay an unhealthy pooled loan in exchange for its collateral
+    Liquidate { id: u64 },
+}
+
+/// ## IbcHookMsg
+/// This structure describes the IBC hook message
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum IbcHookMsg {
+    /// ## Description
+    /// Make borrow request on behalf of the borrower with the transferred tokens. The denom
+    /// trace, `{port}/{channel}/.../{base denom}`, must hash to the IBC denom of the tokens
+    /// and start with an allowed transfer channel
+    MakeBorrowRequest {
+        borrower: String,
+        denom_trace: String,
+        period: Duration,
+        lender: Option<String>,
+        expires_at: Option<Expiration>,
+    },
 }
 
 /// ## Description
@@ -35,12 +220,76 @@ pub enum Cw20HookMsg {
 #[serde(rename_all = "snake_case")]
 pub enum QueryMsg {
     State {},
-    BorrowRequestById { id: u64 },
-    BorrowRequestByAddr { borrower: String },
+    BorrowRequestById {
+        id: u64,
+    },
+    BorrowRequestByAddr {
+        borrower: String,
+        include_directed: Option<bool>,
+    },
+    BorrowRequests {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        include_directed: Option<bool>,
+    },
+    BorrowRequestsByCollateral {
+        asset_info: AssetInfo,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+        include_directed: Option<bool>,
+    },
+    /// ## Description
+    /// Returns open borrow requests only the given lender may fund. Replaces
+    /// `loans_by_lender`, which returned the same requests under a misleading name
+    RequestsDirectedTo {
+        lender: String,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    /// ## Description
+    /// Returns open borrow requests expiring before the given expiration, those expiring at
+    /// a height for a height and those expiring at a time for a time. Pages start after an
+    /// expiry height, or time in nanoseconds, and id
+    RequestsExpiringBefore {
+        expiration: Expiration,
+        start_after: Option<(u64, u64)>,
+        limit: Option<u32>,
+    },
+    Reserve {
+        asset_info: AssetInfo,
+    },
+    Loan {
+        id: u64,
+    },
+    SimulateRepay {
+        id: u64,
+        at_height: Option<u64>,
+    },
+    SimulateLiquidation {
+        id: u64,
+    },
+    LoanHealth {
+        id: u64,
+    },
+    LiquidatableLoans {
+        start_after: Option<LoanCursor>,
+        limit: Option<u32>,
+    },
+    BorrowerProfile {
+        address: String,
+    },
+    Stats {},
 }
 
+/// ## Description
+/// This structure describes migrate message
 #[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
-pub struct MigrateMsg {}
+pub struct MigrateMsg {
+    /// Owner to set when migrating from a version without config
+    pub owner: Option<String>,
+    /// Share token code id to set when migrating from a version without config
+    pub token_code_id: Option<u64>,
+}
 
 /// ## Description
 /// This structure describes state response message
@@ -50,6 +299,8 @@ pub struct StateResponse {
     pub request_count: u64,
     /// The number of responses has been made
     pub response_count: u64,
+    /// The number of pooled loans has been made
+    pub loan_count: u64,
 }
 
 /// ## Description
@@ -60,10 +311,236 @@ pub struct BorrowRequestResponse {
     pub id: u64,
     // Collateral asset
     pub collateral: Asset,
-    // Borrowing block period
-    pub period: u64,
+    // Borrowing period, either in blocks or in seconds
+    pub period: Duration,
+    // The only lender who can fund this request, if directed
+    pub lender: Option<String>,
+    // A block height or time after which this request can no longer be funded
+    pub expires_at: Option<Expiration>,
+    // Whether this request has expired without being borrowed
+    pub expired: bool,
     // An id of borrow response, which responding to this request
     pub borrowed_from: u64,
     // A block height that borrowing occured at
     pub borrowed_at: u64,
+    // A block time in seconds that borrowing occured at
+    pub borrowed_at_time: u64,
+    // A block height or time that borrowing ends at, if borrowed
+    pub deadline: Option<Expiration>,
+    // Remaining blocks or seconds until the deadline, if borrowed
+    pub remaining: Option<Duration>,
+}
+
+/// ## Description
+/// This structure describes the configurable parameters of a pooled reserve.
+/// Rates are per block, the borrow rate follows a kinked utilization curve.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReserveConfig {
+    /// Asset accepted as collateral when borrowing from the reserve
+    pub collateral_info: AssetInfo,
+    /// Price of one unit of collateral in units of the reserve asset
+    pub collateral_price: Decimal256,
+    /// Maximum loan-to-value ratio a loan can be opened at
+    pub max_ltv: Decimal256,
+    /// Loan-to-value ratio above which a loan can be liquidated
+    pub liquidation_threshold: Decimal256,
+    /// Share of the repaid debt paid to liquidators on top in collateral
+    pub liquidation_bonus: Decimal256,
+    /// Share of a flash loan paid to lenders on top when it is returned
+    pub flash_loan_fee: Decimal256,
+    /// Borrow rate at zero utilization
+    pub base_rate: Decimal256,
+    /// Borrow rate increase from zero up to the optimal utilization
+    pub slope1: Decimal256,
+    /// Borrow rate increase from the optimal utilization up to full utilization
+    pub slope2: Decimal256,
+    /// Utilization at which the curve switches from slope1 to slope2
+    pub optimal_utilization: Decimal256,
+    /// Borrowers allowed to borrow from the reserve, or anyone if not set
+    pub borrower_allowlist: Option<Vec<String>>,
+    /// Minimum number of loans a borrower must have repaid in full to borrow
+    pub min_loans_repaid: Option<u64>,
+    /// Maximum number of loans a borrower may have had liquidated to borrow
+    pub max_loans_liquidated: Option<u64>,
+    /// Maximum debt a borrower may owe the reserve across its loans once it has borrowed
+    pub max_outstanding_debt: Option<Uint128>,
+}
+
+/// ## Description
+/// This structure describes reserve response message
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct ReserveResponse {
+    /// Asset lent out by the reserve
+    pub asset_info: AssetInfo,
+    /// CW20 token minted to lenders as a claim on the reserve
+    pub share_token: String,
+    /// Config of the reserve
+    pub config: ReserveConfig,
+    /// Amount available to borrow or withdraw
+    pub liquidity: Uint128,
+    /// Amount owed by borrowers including accrued interest
+    pub total_debt: Uint128,
+    /// Total supply of the share token
+    pub total_shares: Uint128,
+    /// Debt growth since the reserve was created
+    pub borrow_index: Decimal256,
+    /// Ratio of total debt to liquidity plus total debt
+    pub utilization: Decimal256,
+    /// Current per-block borrow rate
+    pub borrow_rate: Decimal256,
+}
+
+/// ## Description
+/// This structure describes pooled loan response message
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LoanResponse {
+    // Id, which is start from 1
+    pub id: u64,
+    // Borrower address
+    pub borrower: String,
+    // Borrowed asset
+    pub asset_info: AssetInfo,
+    // Collateral asset
+    pub collateral: Asset,
+    // Amount owed including accrued interest
+    pub debt: Uint128,
+    // Borrowed amount not yet repaid
+    pub principal: Uint128,
+    // A block height that borrowing occured at
+    pub borrowed_at: u64,
+    // Debt divided by the borrow index per unit of collateral, which liquidatable loans are
+    // ordered by
+    pub debt_ratio: Decimal256,
+}
+
+/// ## Description
+/// This structure describes the position of a pooled loan to start a page of liquidatable
+/// loans after, which stays valid once the loan is repaid or liquidated
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LoanCursor {
+    // Borrowed asset of the loan
+    pub asset_info: AssetInfo,
+    // Debt ratio of the loan as last returned
+    pub debt_ratio: Decimal256,
+    // Id of the loan
+    pub id: u64,
+}
+
+/// ## Description
+/// This structure describes simulate repay response message
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateRepayResponse {
+    // Id of the loan
+    pub id: u64,
+    // A block height that the repayment is simulated at
+    pub at_height: u64,
+    // Amount due to repay the loan in full
+    pub debt: Uint128,
+    // Part of the amount due which is interest
+    pub interest: Uint128,
+    // Part of the amount due which is principal
+    pub principal: Uint128,
+}
+
+/// ## Description
+/// This structure describes simulate liquidation response message
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct SimulateLiquidationResponse {
+    // Id of the loan
+    pub id: u64,
+    // Whether the loan can be liquidated at the current block
+    pub liquidatable: bool,
+    // Amount the liquidator repays, all of which is paid out to lenders of the reserve
+    pub lender_payout: Uint128,
+    // Bonus paid to the liquidator on top of the debt in units of the borrowed asset
+    pub bonus: Uint128,
+    // Collateral seized by the liquidator
+    pub seized_collateral: Uint128,
+    // Collateral returned to the borrower
+    pub borrower_refund: Uint128,
+}
+
+/// ## Description
+/// This structure describes loan health response message
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LoanHealthResponse {
+    // Id of the loan
+    pub id: u64,
+    // Value of the collateral in units of the borrowed asset
+    pub collateral_value: Uint128,
+    // Amount owed including accrued interest
+    pub debt: Uint128,
+    // Ratio of the debt to the collateral value, if the collateral is worth anything
+    pub ltv: Option<Decimal256>,
+    // Loan-to-value ratio above which the loan can be liquidated
+    pub liquidation_threshold: Decimal256,
+    // Collateral price below which the loan can be liquidated, if it has collateral
+    pub liquidation_price: Option<Decimal256>,
+}
+
+/// ## Description
+/// This structure describes borrower profile response message
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BorrowerProfileResponse {
+    // Borrower address
+    pub address: String,
+    // The number of loans taken
+    pub loans_taken: u64,
+    // The number of loans repaid in full by their deadline. Pooled loans have no deadline,
+    // so repaying one in full always counts here
+    pub loans_repaid_on_time: u64,
+    // The number of loans repaid in full after their deadline. Reserved until peer-to-peer
+    // loans with a deadline can be funded, so it stays zero
+    pub loans_repaid_late: u64,
+    // The number of loans defaulted on. Reserved until peer-to-peer loans with a deadline
+    // can be funded, so it stays zero
+    pub loans_defaulted: u64,
+    // The number of loans liquidated
+    pub loans_liquidated: u64,
+    // Total principal borrowed per asset
+    pub principal_borrowed: Vec<Asset>,
+}
+
+/// ## Description
+/// This structure describes protocol-wide statistics response message
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct StatsResponse {
+    /// Statistics of every asset used as collateral or borrowed
+    pub assets: Vec<AssetStatsResponse>,
+    /// Statistics of collateral locked on remote chains by counterpart contracts
+    pub remote_collateral: Vec<RemoteCollateralStatsResponse>,
+}
+
+/// ## Description
+/// This structure describes statistics of collateral locked on a remote chain
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RemoteCollateralStatsResponse {
+    /// Channel to the counterpart contract
+    pub channel_id: String,
+    /// Denom of the collateral on the remote chain
+    pub denom: String,
+    /// Collateral held by borrow requests
+    pub collateral_escrowed: Uint128,
+}
+
+/// ## Description
+/// This structure describes protocol-wide statistics of an asset
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct AssetStatsResponse {
+    pub asset_info: AssetInfo,
+    /// Collateral held by borrow requests and pooled loans
+    pub collateral_escrowed: Uint128,
+    /// Principal of pooled loans not yet repaid
+    pub principal_outstanding: Uint128,
+    /// Total amount ever lent by pooled loans
+    pub volume_lent: Uint128,
+    /// Total interest ever paid on pooled loans
+    pub interest_paid: Uint128,
+    /// The number of pooled loans not yet repaid or liquidated
+    pub active_loans: u64,
+    /// The number of loans defaulted on. Reserved until peer-to-peer loans with a deadline
+    /// can be funded, so it stays zero
+    pub defaulted_loans: u64,
+    /// The number of pooled loans liquidated
+    pub liquidated_loans: u64,
 }
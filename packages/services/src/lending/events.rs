@@ -0,0 +1,644 @@
+use astroport::asset::{Asset, AssetInfo};
+use cosmwasm_std::{Addr, Attribute, Event, StdError, StdResult, Timestamp, Uint128};
+use cw0::{Duration, Expiration};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+/// Prefix the chain adds to the type of events emitted by contracts
+pub const WASM_EVENT_PREFIX: &str = "wasm-";
+
+/// Type of the event emitted when a borrow request is made
+pub const EVENT_BORROW_REQUEST_CREATED: &str = "borrow_request_created";
+/// Type of the event emitted when a borrow request is cancelled by its borrower
+pub const EVENT_BORROW_REQUEST_CANCELLED: &str = "borrow_request_cancelled";
+/// Type of the event emitted when an expired borrow request is pruned
+pub const EVENT_BORROW_REQUEST_EXPIRED: &str = "borrow_request_expired";
+/// Type of the event emitted when a lender deposits into a pooled reserve
+pub const EVENT_DEPOSITED: &str = "deposited";
+/// Type of the event emitted when a lender withdraws from a pooled reserve
+pub const EVENT_WITHDRAWN: &str = "withdrawn";
+/// Type of the event emitted when a pooled loan is taken
+pub const EVENT_BORROWED: &str = "borrowed";
+/// Type of the event emitted when a pooled loan is repaid in part or in full
+pub const EVENT_REPAID: &str = "repaid";
+/// Type of the event emitted when a pooled loan is liquidated
+pub const EVENT_LIQUIDATED: &str = "liquidated";
+/// Type of the event emitted when a flash loan is taken
+pub const EVENT_FLASH_LOANED: &str = "flash_loaned";
+/// Type of the event emitted when a failed CW20 payout is left claimable
+pub const EVENT_PAYOUT_DEFERRED: &str = "payout_deferred";
+/// Type of the event emitted when a claimable payout is claimed
+pub const EVENT_PENDING_CLAIMED: &str = "pending_claimed";
+
+/// Borrow request id attribute of borrow request events
+pub const ATTR_ID: &str = "id";
+/// Borrower address attribute of borrow request events
+pub const ATTR_BORROWER: &str = "borrower";
+/// Collateral denom attribute of borrow request events, set if the collateral is a native token
+pub const ATTR_COLLATERAL_DENOM: &str = "collateral_denom";
+/// Collateral contract attribute of borrow request events, set if the collateral is a CW20 token
+pub const ATTR_COLLATERAL_CONTRACT: &str = "collateral_contract";
+/// Collateral amount attribute of borrow request events
+pub const ATTR_AMOUNT: &str = "amount";
+/// Borrowing period attribute of borrow request events in blocks, set if the period is in blocks
+pub const ATTR_PERIOD_HEIGHT: &str = "period_height";
+/// Borrowing period attribute of borrow request events in seconds, set if the period is in seconds
+pub const ATTR_PERIOD_TIME: &str = "period_time";
+/// Lender address attribute of borrow request events, set if the request is directed
+pub const ATTR_LENDER: &str = "lender";
+/// Expiry block height attribute of borrow request events, set if the request expires at a height
+pub const ATTR_EXPIRES_AT_HEIGHT: &str = "expires_at_height";
+/// Expiry time attribute of borrow request events in nanoseconds, set if the request expires at
+/// a time
+pub const ATTR_EXPIRES_AT_TIME: &str = "expires_at_time";
+/// Asset denom attribute of reserve events, set if the asset is a native token
+pub const ATTR_DENOM: &str = "denom";
+/// Asset contract attribute of reserve events, set if the asset is a CW20 token
+pub const ATTR_CONTRACT: &str = "contract";
+/// Collateral amount attribute of loan events, whose asset amount is [`ATTR_AMOUNT`]
+pub const ATTR_COLLATERAL_AMOUNT: &str = "collateral_amount";
+/// Share token amount attribute of deposit and withdraw events
+pub const ATTR_SHARES: &str = "shares";
+/// Pooled loan id attribute of loan events
+pub const ATTR_LOAN_ID: &str = "loan_id";
+/// Payer address attribute of repay events
+pub const ATTR_PAYER: &str = "payer";
+/// Debt left after the repayment attribute of repay events
+pub const ATTR_REMAINING: &str = "remaining";
+/// Liquidator address attribute of liquidate events
+pub const ATTR_LIQUIDATOR: &str = "liquidator";
+/// Fee attribute of flash loan events
+pub const ATTR_FEE: &str = "fee";
+/// Recipient address attribute of payout events
+pub const ATTR_RECIPIENT: &str = "recipient";
+
+/// ## Description
+/// This structure describes the events emitted by the lending contract
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum LendingEvent {
+    BorrowRequestCreated(BorrowRequestEvent),
+    BorrowRequestCancelled(BorrowRequestEvent),
+    BorrowRequestExpired(BorrowRequestEvent),
+    Deposited(LiquidityEvent),
+    Withdrawn(LiquidityEvent),
+    Borrowed(BorrowEvent),
+    Repaid(RepayEvent),
+    Liquidated(LiquidateEvent),
+    FlashLoaned(FlashLoanEvent),
+    PayoutDeferred(PayoutEvent),
+    PendingClaimed(PayoutEvent),
+}
+
+/// ## Description
+/// This structure describes the borrow request carried by borrow request events
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BorrowRequestEvent {
+    pub id: u64,
+    pub borrower: String,
+    pub collateral: Asset,
+    pub period: Duration,
+    pub lender: Option<String>,
+    pub expires_at: Option<Expiration>,
+}
+
+/// ## Description
+/// This structure describes the reserve liquidity carried by deposit and withdraw events
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LiquidityEvent {
+    pub lender: String,
+    pub asset: Asset,
+    pub shares: Uint128,
+}
+
+/// ## Description
+/// This structure describes the pooled loan carried by borrow events
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct BorrowEvent {
+    pub loan_id: u64,
+    pub borrower: String,
+    pub asset: Asset,
+    pub collateral: Asset,
+}
+
+/// ## Description
+/// This structure describes the repayment carried by repay events
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct RepayEvent {
+    pub loan_id: u64,
+    pub payer: String,
+    pub asset: Asset,
+    pub remaining: Uint128,
+}
+
+/// ## Description
+/// This structure describes the liquidation carried by liquidate events, with the debt
+/// repaid as asset and the collateral seized by the liquidator
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct LiquidateEvent {
+    pub loan_id: u64,
+    pub liquidator: String,
+    pub asset: Asset,
+    pub seized: Asset,
+}
+
+/// ## Description
+/// This structure describes the flash loan carried by flash loan events
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct FlashLoanEvent {
+    pub borrower: String,
+    pub asset: Asset,
+    pub fee: Uint128,
+}
+
+/// ## Description
+/// This structure describes the payout carried by payout events
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, JsonSchema)]
+pub struct PayoutEvent {
+    pub recipient: String,
+    pub asset: Asset,
+}
+
+impl LendingEvent {
+    /// ## Description
+    /// Returns the type of the event, without the prefix the chain adds
+    pub fn ty(&self) -> &'static str {
+        match self {
+            LendingEvent::BorrowRequestCreated(_) => EVENT_BORROW_REQUEST_CREATED,
+            LendingEvent::BorrowRequestCancelled(_) => EVENT_BORROW_REQUEST_CANCELLED,
+            LendingEvent::BorrowRequestExpired(_) => EVENT_BORROW_REQUEST_EXPIRED,
+            LendingEvent::Deposited(_) => EVENT_DEPOSITED,
+            LendingEvent::Withdrawn(_) => EVENT_WITHDRAWN,
+            LendingEvent::Borrowed(_) => EVENT_BORROWED,
+            LendingEvent::Repaid(_) => EVENT_REPAID,
+            LendingEvent::Liquidated(_) => EVENT_LIQUIDATED,
+            LendingEvent::FlashLoaned(_) => EVENT_FLASH_LOANED,
+            LendingEvent::PayoutDeferred(_) => EVENT_PAYOUT_DEFERRED,
+            LendingEvent::PendingClaimed(_) => EVENT_PENDING_CLAIMED,
+        }
+    }
+
+    /// ## Description
+    /// Returns the lending event of the given type and attributes, or [`None`] if the type
+    /// is not of a lending event. The type may carry the prefix the chain adds, and
+    /// attributes unknown to the event are ignored
+    /// Otherwise returns [`StdError`] if an attribute is missing or malformed
+    /// ## Params
+    /// * **ty** is an object of type [`str`]
+    ///
+    /// * **attributes** is a slice of [`Attribute`]
+    pub fn parse_attributes(ty: &str, attributes: &[Attribute]) -> StdResult<Option<Self>> {
+        let ty = ty.strip_prefix(WASM_EVENT_PREFIX).unwrap_or(ty);
+        let event = match ty {
+            EVENT_BORROW_REQUEST_CREATED => LendingEvent::BorrowRequestCreated(
+                BorrowRequestEvent::parse_attributes(attributes)?,
+            ),
+            EVENT_BORROW_REQUEST_CANCELLED => LendingEvent::BorrowRequestCancelled(
+                BorrowRequestEvent::parse_attributes(attributes)?,
+            ),
+            EVENT_BORROW_REQUEST_EXPIRED => LendingEvent::BorrowRequestExpired(
+                BorrowRequestEvent::parse_attributes(attributes)?,
+            ),
+            EVENT_DEPOSITED => {
+                LendingEvent::Deposited(LiquidityEvent::parse_attributes(attributes)?)
+            }
+            EVENT_WITHDRAWN => {
+                LendingEvent::Withdrawn(LiquidityEvent::parse_attributes(attributes)?)
+            }
+            EVENT_BORROWED => LendingEvent::Borrowed(BorrowEvent::parse_attributes(attributes)?),
+            EVENT_REPAID => LendingEvent::Repaid(RepayEvent::parse_attributes(attributes)?),
+            EVENT_LIQUIDATED => {
+                LendingEvent::Liquidated(LiquidateEvent::parse_attributes(attributes)?)
+            }
+            EVENT_FLASH_LOANED => {
+                LendingEvent::FlashLoaned(FlashLoanEvent::parse_attributes(attributes)?)
+            }
+            EVENT_PAYOUT_DEFERRED => {
+                LendingEvent::PayoutDeferred(PayoutEvent::parse_attributes(attributes)?)
+            }
+            EVENT_PENDING_CLAIMED => {
+                LendingEvent::PendingClaimed(PayoutEvent::parse_attributes(attributes)?)
+            }
+            _ => return Ok(None),
+        };
+
+        Ok(Some(event))
+    }
+
+    /// ## Description
+    /// Returns the lending event of the given event, or [`None`] if it is not a lending event
+    /// Otherwise returns [`StdError`] if an attribute is missing or malformed
+    /// ## Params
+    /// * **event** is an object of type [`Event`]
+    pub fn parse(event: &Event) -> StdResult<Option<Self>> {
+        Self::parse_attributes(&event.ty, &event.attributes)
+    }
+
+    /// ## Description
+    /// Returns the lending events among the given events in order, skipping the others
+    /// Otherwise returns [`StdError`] if an attribute of a lending event is missing or malformed
+    /// ## Params
+    /// * **events** is a slice of [`Event`]
+    pub fn parse_events(events: &[Event]) -> StdResult<Vec<Self>> {
+        events
+            .iter()
+            .filter_map(|event| Self::parse(event).transpose())
+            .collect()
+    }
+}
+
+impl From<LendingEvent> for Event {
+    fn from(event: LendingEvent) -> Self {
+        let ty = event.ty();
+        let attributes = match event {
+            LendingEvent::BorrowRequestCreated(borrow_request)
+            | LendingEvent::BorrowRequestCancelled(borrow_request)
+            | LendingEvent::BorrowRequestExpired(borrow_request) => borrow_request.to_attributes(),
+            LendingEvent::Deposited(liquidity) | LendingEvent::Withdrawn(liquidity) => {
+                liquidity.to_attributes()
+            }
+            LendingEvent::Borrowed(borrow) => borrow.to_attributes(),
+            LendingEvent::Repaid(repay) => repay.to_attributes(),
+            LendingEvent::Liquidated(liquidate) => liquidate.to_attributes(),
+            LendingEvent::FlashLoaned(flash_loan) => flash_loan.to_attributes(),
+            LendingEvent::PayoutDeferred(payout) | LendingEvent::PendingClaimed(payout) => {
+                payout.to_attributes()
+            }
+        };
+
+        Event::new(ty).add_attributes(attributes)
+    }
+}
+
+impl BorrowRequestEvent {
+    /// ## Description
+    /// Returns one attribute per field, leaving out fields that are not set
+    pub fn to_attributes(&self) -> Vec<Attribute> {
+        let mut attrs = vec![
+            Attribute::new(ATTR_ID, self.id.to_string()),
+            Attribute::new(ATTR_BORROWER, &self.borrower),
+        ];
+        attrs.extend(asset_attributes(
+            &self.collateral,
+            ATTR_COLLATERAL_DENOM,
+            ATTR_COLLATERAL_CONTRACT,
+            ATTR_AMOUNT,
+        ));
+        attrs.push(match self.period {
+            Duration::Height(blocks) => Attribute::new(ATTR_PERIOD_HEIGHT, blocks.to_string()),
+            Duration::Time(seconds) => Attribute::new(ATTR_PERIOD_TIME, seconds.to_string()),
+        });
+        if let Some(lender) = &self.lender {
+            attrs.push(Attribute::new(ATTR_LENDER, lender));
+        }
+        match self.expires_at {
+            Some(Expiration::AtHeight(height)) => {
+                attrs.push(Attribute::new(ATTR_EXPIRES_AT_HEIGHT, height.to_string()))
+            }
+            Some(Expiration::AtTime(time)) => attrs.push(Attribute::new(
+                ATTR_EXPIRES_AT_TIME,
+                time.nanos().to_string(),
+            )),
+            Some(Expiration::Never {}) | None => (),
+        }
+
+        attrs
+    }
+
+    /// ## Description
+    /// Returns the borrow request of the given attributes, ignoring unknown ones
+    /// Otherwise returns [`StdError`] if an attribute is missing or malformed
+    /// ## Params
+    /// * **attributes** is a slice of [`Attribute`]
+    pub fn parse_attributes(attributes: &[Attribute]) -> StdResult<Self> {
+        let period = match (
+            parse_attribute::<u64>(attributes, ATTR_PERIOD_HEIGHT)?,
+            parse_attribute::<u64>(attributes, ATTR_PERIOD_TIME)?,
+        ) {
+            (Some(blocks), None) => Duration::Height(blocks),
+            (None, Some(seconds)) => Duration::Time(seconds),
+            _ => return Err(invalid_attribute(ATTR_PERIOD_HEIGHT)),
+        };
+
+        let expires_at = match (
+            parse_attribute::<u64>(attributes, ATTR_EXPIRES_AT_HEIGHT)?,
+            parse_attribute::<u64>(attributes, ATTR_EXPIRES_AT_TIME)?,
+        ) {
+            (None, None) => None,
+            (Some(height), None) => Some(Expiration::AtHeight(height)),
+            (None, Some(nanos)) => Some(Expiration::AtTime(Timestamp::from_nanos(nanos))),
+            _ => return Err(invalid_attribute(ATTR_EXPIRES_AT_HEIGHT)),
+        };
+
+        Ok(BorrowRequestEvent {
+            id: required_number(attributes, ATTR_ID)?,
+            borrower: required_attribute(attributes, ATTR_BORROWER)?,
+            collateral: parse_asset(
+                attributes,
+                ATTR_COLLATERAL_DENOM,
+                ATTR_COLLATERAL_CONTRACT,
+                ATTR_AMOUNT,
+            )?,
+            period,
+            lender: find_attribute(attributes, ATTR_LENDER).map(|lender| lender.to_string()),
+            expires_at,
+        })
+    }
+}
+
+impl LiquidityEvent {
+    /// ## Description
+    /// Returns one attribute per field
+    pub fn to_attributes(&self) -> Vec<Attribute> {
+        let mut attrs = vec![Attribute::new(ATTR_LENDER, &self.lender)];
+        attrs.extend(asset_attributes(
+            &self.asset,
+            ATTR_DENOM,
+            ATTR_CONTRACT,
+            ATTR_AMOUNT,
+        ));
+        attrs.push(Attribute::new(ATTR_SHARES, self.shares.to_string()));
+
+        attrs
+    }
+
+    /// ## Description
+    /// Returns the reserve liquidity of the given attributes, ignoring unknown ones
+    /// Otherwise returns [`StdError`] if an attribute is missing or malformed
+    /// ## Params
+    /// * **attributes** is a slice of [`Attribute`]
+    pub fn parse_attributes(attributes: &[Attribute]) -> StdResult<Self> {
+        Ok(LiquidityEvent {
+            lender: required_attribute(attributes, ATTR_LENDER)?,
+            asset: parse_asset(attributes, ATTR_DENOM, ATTR_CONTRACT, ATTR_AMOUNT)?,
+            shares: required_amount(attributes, ATTR_SHARES)?,
+        })
+    }
+}
+
+impl BorrowEvent {
+    /// ## Description
+    /// Returns one attribute per field
+    pub fn to_attributes(&self) -> Vec<Attribute> {
+        let mut attrs = vec![
+            Attribute::new(ATTR_LOAN_ID, self.loan_id.to_string()),
+            Attribute::new(ATTR_BORROWER, &self.borrower),
+        ];
+        attrs.extend(asset_attributes(
+            &self.asset,
+            ATTR_DENOM,
+            ATTR_CONTRACT,
+            ATTR_AMOUNT,
+        ));
+        attrs.extend(asset_attributes(
+            &self.collateral,
+            ATTR_COLLATERAL_DENOM,
+            ATTR_COLLATERAL_CONTRACT,
+            ATTR_COLLATERAL_AMOUNT,
+        ));
+
+        attrs
+    }
+
+    /// ## Description
+    /// Returns the pooled loan of the given attributes, ignoring unknown ones
+    /// Otherwise returns [`StdError`] if an attribute is missing or malformed
+    /// ## Params
+    /// * **attributes** is a slice of [`Attribute`]
+    pub fn parse_attributes(attributes: &[Attribute]) -> StdResult<Self> {
+        Ok(BorrowEvent {
+            loan_id: required_number(attributes, ATTR_LOAN_ID)?,
+            borrower: required_attribute(attributes, ATTR_BORROWER)?,
+            asset: parse_asset(attributes, ATTR_DENOM, ATTR_CONTRACT, ATTR_AMOUNT)?,
+            collateral: parse_asset(
+                attributes,
+                ATTR_COLLATERAL_DENOM,
+                ATTR_COLLATERAL_CONTRACT,
+                ATTR_COLLATERAL_AMOUNT,
+            )?,
+        })
+    }
+}
+
+impl RepayEvent {
+    /// ## Description
+    /// Returns one attribute per field
+    pub fn to_attributes(&self) -> Vec<Attribute> {
+        let mut attrs = vec![
+            Attribute::new(ATTR_LOAN_ID, self.loan_id.to_string()),
+            Attribute::new(ATTR_PAYER, &self.payer),
+        ];
+        attrs.extend(asset_attributes(
+            &self.asset,
+            ATTR_DENOM,
+            ATTR_CONTRACT,
+            ATTR_AMOUNT,
+        ));
+        attrs.push(Attribute::new(ATTR_REMAINING, self.remaining.to_string()));
+
+        attrs
+    }
+
+    /// ## Description
+    /// Returns the repayment of the given attributes, ignoring unknown ones
+    /// Otherwise returns [`StdError`] if an attribute is missing or malformed
+    /// ## Params
+    /// * **attributes** is a slice of [`Attribute`]
+    pub fn parse_attributes(attributes: &[Attribute]) -> StdResult<Self> {
+        Ok(RepayEvent {
+            loan_id: required_number(attributes, ATTR_LOAN_ID)?,
+            payer: required_attribute(attributes, ATTR_PAYER)?,
+            asset: parse_asset(attributes, ATTR_DENOM, ATTR_CONTRACT, ATTR_AMOUNT)?,
+            remaining: required_amount(attributes, ATTR_REMAINING)?,
+        })
+    }
+}
+
+impl LiquidateEvent {
+    /// ## Description
+    /// Returns one attribute per field
+    pub fn to_attributes(&self) -> Vec<Attribute> {
+        let mut attrs = vec![
+            Attribute::new(ATTR_LOAN_ID, self.loan_id.to_string()),
+            Attribute::new(ATTR_LIQUIDATOR, &self.liquidator),
+        ];
+        attrs.extend(asset_attributes(
+            &self.asset,
+            ATTR_DENOM,
+            ATTR_CONTRACT,
+            ATTR_AMOUNT,
+        ));
+        attrs.extend(asset_attributes(
+            &self.seized,
+            ATTR_COLLATERAL_DENOM,
+            ATTR_COLLATERAL_CONTRACT,
+            ATTR_COLLATERAL_AMOUNT,
+        ));
+
+        attrs
+    }
+
+    /// ## Description
+    /// Returns the liquidation of the given attributes, ignoring unknown ones
+    /// Otherwise returns [`StdError`] if an attribute is missing or malformed
+    /// ## Params
+    /// * **attributes** is a slice of [`Attribute`]
+    pub fn parse_attributes(attributes: &[Attribute]) -> StdResult<Self> {
+        Ok(LiquidateEvent {
+            loan_id: required_number(attributes, ATTR_LOAN_ID)?,
+            liquidator: required_attribute(attributes, ATTR_LIQUIDATOR)?,
+            asset: parse_asset(attributes, ATTR_DENOM, ATTR_CONTRACT, ATTR_AMOUNT)?,
+            seized: parse_asset(
+                attributes,
+                ATTR_COLLATERAL_DENOM,
+                ATTR_COLLATERAL_CONTRACT,
+                ATTR_COLLATERAL_AMOUNT,
+            )?,
+        })
+    }
+}
+
+impl FlashLoanEvent {
+    /// ## Description
+    /// Returns one attribute per field
+    pub fn to_attributes(&self) -> Vec<Attribute> {
+        let mut attrs = vec![Attribute::new(ATTR_BORROWER, &self.borrower)];
+        attrs.extend(asset_attributes(
+            &self.asset,
+            ATTR_DENOM,
+            ATTR_CONTRACT,
+            ATTR_AMOUNT,
+        ));
+        attrs.push(Attribute::new(ATTR_FEE, self.fee.to_string()));
+
+        attrs
+    }
+
+    /// ## Description
+    /// Returns the flash loan of the given attributes, ignoring unknown ones
+    /// Otherwise returns [`StdError`] if an attribute is missing or malformed
+    /// ## Params
+    /// * **attributes** is a slice of [`Attribute`]
+    pub fn parse_attributes(attributes: &[Attribute]) -> StdResult<Self> {
+        Ok(FlashLoanEvent {
+            borrower: required_attribute(attributes, ATTR_BORROWER)?,
+            asset: parse_asset(attributes, ATTR_DENOM, ATTR_CONTRACT, ATTR_AMOUNT)?,
+            fee: required_amount(attributes, ATTR_FEE)?,
+        })
+    }
+}
+
+impl PayoutEvent {
+    /// ## Description
+    /// Returns one attribute per field
+    pub fn to_attributes(&self) -> Vec<Attribute> {
+        let mut attrs = vec![Attribute::new(ATTR_RECIPIENT, &self.recipient)];
+        attrs.extend(asset_attributes(
+            &self.asset,
+            ATTR_DENOM,
+            ATTR_CONTRACT,
+            ATTR_AMOUNT,
+        ));
+
+        attrs
+    }
+
+    /// ## Description
+    /// Returns the payout of the given attributes, ignoring unknown ones
+    /// Otherwise returns [`StdError`] if an attribute is missing or malformed
+    /// ## Params
+    /// * **attributes** is a slice of [`Attribute`]
+    pub fn parse_attributes(attributes: &[Attribute]) -> StdResult<Self> {
+        Ok(PayoutEvent {
+            recipient: required_attribute(attributes, ATTR_RECIPIENT)?,
+            asset: parse_asset(attributes, ATTR_DENOM, ATTR_CONTRACT, ATTR_AMOUNT)?,
+        })
+    }
+}
+
+/// Returns the attributes of an asset, its denom or contract depending on its kind followed
+/// by its amount
+fn asset_attributes(
+    asset: &Asset,
+    denom_key: &str,
+    contract_key: &str,
+    amount_key: &str,
+) -> Vec<Attribute> {
+    vec![
+        match &asset.info {
+            AssetInfo::NativeToken { denom } => Attribute::new(denom_key, denom),
+            AssetInfo::Token { contract_addr } => Attribute::new(contract_key, contract_addr),
+        },
+        Attribute::new(amount_key, asset.amount.to_string()),
+    ]
+}
+
+/// Returns the asset of the given attributes, which must have either its denom or contract
+fn parse_asset(
+    attributes: &[Attribute],
+    denom_key: &str,
+    contract_key: &str,
+    amount_key: &str,
+) -> StdResult<Asset> {
+    let info = match (
+        find_attribute(attributes, denom_key),
+        find_attribute(attributes, contract_key),
+    ) {
+        (Some(denom), None) => AssetInfo::NativeToken {
+            denom: denom.to_string(),
+        },
+        (None, Some(contract_addr)) => AssetInfo::Token {
+            contract_addr: Addr::unchecked(contract_addr),
+        },
+        _ => return Err(invalid_attribute(denom_key)),
+    };
+
+    Ok(Asset {
+        info,
+        amount: required_amount(attributes, amount_key)?,
+    })
+}
+
+/// Returns the value of the first attribute of the given key, which must exist
+fn required_attribute(attributes: &[Attribute], key: &str) -> StdResult<String> {
+    find_attribute(attributes, key)
+        .map(|value| value.to_string())
+        .ok_or_else(|| invalid_attribute(key))
+}
+
+/// Returns the value of the first attribute of the given key parsed as a number, which must
+/// exist
+fn required_number<T: std::str::FromStr>(attributes: &[Attribute], key: &str) -> StdResult<T> {
+    parse_attribute(attributes, key)?.ok_or_else(|| invalid_attribute(key))
+}
+
+/// Returns the value of the first attribute of the given key parsed as an amount, which must
+/// exist
+fn required_amount(attributes: &[Attribute], key: &str) -> StdResult<Uint128> {
+    required_number::<u128>(attributes, key).map(Uint128::from)
+}
+
+/// Returns the value of the first attribute of the given key
+fn find_attribute<'a>(attributes: &'a [Attribute], key: &str) -> Option<&'a str> {
+    attributes
+        .iter()
+        .find(|attr| attr.key == key)
+        .map(|attr| attr.value.as_str())
+}
+
+/// Returns the value of the first attribute of the given key parsed as a number
+fn parse_attribute<T: std::str::FromStr>(
+    attributes: &[Attribute],
+    key: &str,
+) -> StdResult<Option<T>> {
+    find_attribute(attributes, key)
+        .map(|value| value.parse::<T>().map_err(|_| invalid_attribute(key)))
+        .transpose()
+}
+
+fn invalid_attribute(key: &str) -> StdError {
+    StdError::parse_err(
+        "LendingEvent",
+        format!("missing or invalid attribute {}", key),
+    )
+}
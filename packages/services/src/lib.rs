@@ -1 +1 @@
-pub mod lending;
\ No newline at end of file
+pub mod lending;